@@ -14,9 +14,14 @@ import React from "react";
 const SPX_VRFY_ADDRESS = process.env
   .NEXT_PUBLIC_SPX_VRFY_ADDRESS as `0x${string}`;
 
+// spx-prover 服务（cargo run --bin spx-prover）
+const ZK_SERVER = "http://localhost:3002";
+
 export default function ZkCheckPage() {
   const { writeContractAsync } = useWriteContract();
   const [loading, setLoading] = useState(false);
+  // 最近一次生成成功的任务，下载时用
+  const [jobId, setJobId] = useState<string | null>(null);
 
   const { data, isLoading, isError, refetch } = useReadContract({
     address: SPX_VRFY_ADDRESS,
@@ -37,10 +42,18 @@ export default function ZkCheckPage() {
     zkbinfileInputRef.current?.click();
   };
 
-  const downloadSeal = () => {
-    window.location.href = "http://localhost:3002/api/download-seal";
+  const download = (file: string) => {
+    if (!jobId) {
+      alert("请先生成 ZK");
+      return;
+    }
+    window.location.href = `${ZK_SERVER}/receipt/${jobId}/${file}`;
   };
 
+  const downloadSeal = () => download("seal.bin");
+
+  const downloadJournal = () => download("journal.bin");
+
   const sighandleFileChange = async (
     event: React.ChangeEvent<HTMLInputElement>,
   ) => {
@@ -49,21 +62,29 @@ export default function ZkCheckPage() {
 
     event.target.value = "";
 
-    const formData = new FormData();
-    formData.append("sigfile", file);
-
     setLoading(true);
     try {
-      const res = await fetch("http://localhost:3002/api/zkverify", {
+      const res = await fetch(`${ZK_SERVER}/prove`, {
         method: "POST",
-        body: formData,
+        headers: { "Content-Type": "application/json" },
+        body: file,
       });
+      let job = await res.json();
+      if (!res.ok) {
+        alert("❌ ZK 生成失败:\n" + job.error);
+        return;
+      }
 
-      const data = await res.json();
-      if (data.status === "success") {
+      // 轮询任务状态，直到证明完成或失败
+      while (job.state === "queued" || job.state === "running") {
+        await new Promise((resolve) => setTimeout(resolve, 3000));
+        job = await (await fetch(`${ZK_SERVER}/jobs/${job.id}`)).json();
+      }
+      if (job.state === "done") {
+        setJobId(job.id);
         alert("✅ ZK 生成成功\n");
       } else {
-        alert("❌ ZK 生成失败:\n" + (data.stderr || data.output || data.error));
+        alert("❌ ZK 生成失败:\n" + job.error);
       }
     } catch (err: any) {
       alert("请求失败: " + err.message);
@@ -74,24 +95,30 @@ export default function ZkCheckPage() {
 
 const zkhandleFileChange = async () => {
   const fileInput = zkbinfileInputRef.current;
-  const file = fileInput?.files?.[0];
+  const files = Array.from(fileInput?.files ?? []);
+  const sealFile = files.find((f) => f.name.includes("seal"));
+  const journalFile = files.find((f) => f.name.includes("journal"));
 
-  if (!file) {
-    alert("请先选择 seal.bin 文件！");
+  if (!sealFile || !journalFile) {
+    alert("请同时选择 seal.bin 和 journal.bin 文件！");
+    if (fileInput) {
+      fileInput.value = "";
+    }
     return;
   }
 
-  const arrayBuffer = await file.arrayBuffer();
-  const sealBytes = new Uint8Array(arrayBuffer); // 注意是 Uint8Array
+  const sealBytes = new Uint8Array(await sealFile.arrayBuffer()); // 注意是 Uint8Array
+  const journalBytes = new Uint8Array(await journalFile.arrayBuffer());
 
   const hexSeal = toHex(sealBytes);
+  const hexJournal = toHex(journalBytes);
 
   try {
     const tx = await writeContractAsync({
       address: SPX_VRFY_ADDRESS,
       abi: SpxVrfyAbi,
       functionName: "set",
-      args: [hexSeal],
+      args: [hexJournal, hexSeal],
     });
 
     alert("✅ 交易确认成功！");
@@ -174,9 +201,18 @@ const zkhandleFileChange = async () => {
         ⏬ 下载 ZK
       </MyButton>
 
+      <MyButton
+        onClick={downloadJournal}
+        bgColor=" rgb(138, 23, 209)"
+        hoverColor=" rgb(213, 36, 249)"
+      >
+        ⏬ 下载 Journal
+      </MyButton>
+
       <input
         type="file"
         accept=".bin"
+        multiple
         ref={zkbinfileInputRef}
         style={{ display: "none" }}
         onChange={zkhandleFileChange}
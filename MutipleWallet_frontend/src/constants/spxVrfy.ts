@@ -48,7 +48,10 @@ export const SpxVrfyAbi = [
   {
     type: "function",
     name: "set",
-    inputs: [{ name: "seal", type: "bytes", internalType: "bytes" }],
+    inputs: [
+      { name: "journal", type: "bytes", internalType: "bytes" },
+      { name: "seal", type: "bytes", internalType: "bytes" },
+    ],
     outputs: [],
     stateMutability: "nonpayable",
   },
@@ -0,0 +1,215 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// This application proves a whole batch of signatures with the spxBatchVrfy
+// guest, so that a single Groth16 seal covers all of them, and writes one
+// Merkle inclusion proof per signature next to the seal.
+
+use alloy_sol_types::{sol, SolType};
+use anyhow::{ensure, Context, Result};
+use apps::{prover::ProverArgs, receipt::ReceiptKind};
+use clap::Parser;
+use methods::{SPXBATCHVRFY_ELF, SPXBATCHVRFY_ID};
+use risc0_ethereum_contracts::encode_seal;
+use risc0_zkvm::sha::{Impl, Sha256};
+use serde::Serialize;
+use spx_host::{SignatureBundle, Status};
+use std::fs;
+
+alloy::sol!(
+    #[sol(all_derives)]
+    "../contracts/ISpxBatchVrfy.sol"
+);
+
+/// Sibling of a node on the way from a leaf to the root.
+#[derive(Debug, Serialize)]
+struct ProofStep {
+    sibling: String,
+    /// Whether the sibling is hashed on the left.
+    left: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ItemProof {
+    index: usize,
+    verified: bool,
+    leaf: String,
+    proof: Vec<ProofStep>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchProofs {
+    param_set: String,
+    root: String,
+    items: Vec<ItemProof>,
+}
+
+/// Arguments of the batchGen CLI.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Signature files in the sig.json format, or signature_result_*.json
+    /// files of the frontend backend, one per batch item
+    #[clap(long, required = true, num_args = 1..)]
+    sig: Vec<String>,
+
+    /// SPHINCS+ parameter set all signatures were produced with
+    #[clap(long, default_value = "sm3-128s-simple")]
+    param_set: String,
+
+    /// Kind of receipt to prove; only groth16 receipts get a batch_seal.bin
+    #[clap(long, value_enum, default_value_t = ReceiptKind::Groth16)]
+    kind: ReceiptKind,
+
+    #[clap(flatten)]
+    prover: ProverArgs,
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Impl::hash_bytes(data).as_bytes().try_into().unwrap()
+}
+
+/// Must match `leaf` in the spxBatchVrfy guest.
+fn leaf(param_set: &str, item: &SpxBatchItem, verified: bool) -> [u8; 32] {
+    let mut preimage = [0u8; 97];
+    preimage[..32].copy_from_slice(&sha256(param_set.as_bytes()));
+    preimage[32..64].copy_from_slice(&sha256(&item.publicKey));
+    preimage[64..96].copy_from_slice(&sha256(&item.message));
+    preimage[96] = verified as u8;
+    sha256(&preimage)
+}
+
+fn parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut node = [0u8; 64];
+    node[..32].copy_from_slice(left);
+    node[32..].copy_from_slice(right);
+    sha256(&node)
+}
+
+/// Returns the root over `leaves` together with the inclusion proof of each
+/// leaf, following the same odd-node rule as the guest.
+fn merkle_proofs(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<ProofStep>>) {
+    let mut proofs: Vec<Vec<ProofStep>> = leaves.iter().map(|_| Vec::new()).collect();
+    if leaves.is_empty() {
+        return ([0u8; 32], proofs);
+    }
+
+    let mut level = leaves.to_vec();
+    // Position of every original leaf in the current level
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    while level.len() > 1 {
+        for (proof, pos) in proofs.iter_mut().zip(positions.iter_mut()) {
+            let sibling = *pos ^ 1;
+            if sibling < level.len() {
+                proof.push(ProofStep {
+                    sibling: hex::encode(level[sibling]),
+                    left: sibling < *pos,
+                });
+            }
+            *pos /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => parent(left, right),
+                [odd] => *odd,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    (level[0], proofs)
+}
+
+fn read_item(path: &str, param_set: &str) -> Result<SpxBatchItem> {
+    let bundle = SignatureBundle::load_for(path, param_set)?;
+    Ok(SpxBatchItem {
+        publicKey: bundle.public_key.into(),
+        signature: bundle.signature.into(),
+        message: bundle.message.into(),
+    })
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+    args.prover.check(args.kind)?;
+
+    let items = args
+        .sig
+        .iter()
+        .map(|path| read_item(path, &args.param_set))
+        .collect::<Result<Vec<_>>>()?;
+
+    let send = <sol! { (string, SpxBatchItem[]) }>::abi_encode_params(&(args.param_set, items.clone()));
+
+    let receipt = args
+        .prover
+        .prove_elf(SPXBATCHVRFY_ELF, SPXBATCHVRFY_ID, &send, args.kind)?;
+
+    let journal = receipt.journal.bytes.clone();
+    let output = SpxBatchJournal::abi_decode(&journal, true).context("decoding journal")?;
+    let status = Status::try_from(output.status)?;
+    ensure!(
+        status == Status::Verified,
+        "the guest could not check the batch: {status}"
+    );
+
+    println!("Parameter set: {}", output.paramSet);
+    println!("Verified: {}/{}", output.verifiedCount, output.count);
+    println!("Root: {}", output.root);
+
+    // 用 journal 里的结果位图重建叶子，核对根后为每条签名导出 Merkle 证明
+    let verified: Vec<bool> = (0..items.len())
+        .map(|i| output.results[i / 8] & (1 << (i % 8)) != 0)
+        .collect();
+    let leaves: Vec<[u8; 32]> = items
+        .iter()
+        .zip(&verified)
+        .map(|(item, &verified)| leaf(&output.paramSet, item, verified))
+        .collect();
+    let (root, proofs) = merkle_proofs(&leaves);
+    ensure!(root == output.root.0, "guest root does not match the recomputed root");
+
+    let proofs = BatchProofs {
+        param_set: output.paramSet,
+        root: hex::encode(root),
+        items: proofs
+            .into_iter()
+            .enumerate()
+            .map(|(index, proof)| ItemProof {
+                index,
+                verified: verified[index],
+                leaf: hex::encode(leaves[index]),
+                proof,
+            })
+            .collect(),
+    };
+
+    // Only Groth16 and dev-mode receipts have an on-chain seal; a stale seal
+    // must not pair with the new journal
+    match encode_seal(&receipt) {
+        Ok(seal) => fs::write("ZKbin/batch_seal.bin", seal)?,
+        Err(_) => {
+            println!("Only groth16 receipts have an on-chain seal, batch_seal.bin not written");
+            let _ = fs::remove_file("ZKbin/batch_seal.bin");
+        }
+    }
+    fs::write("ZKbin/batch_journal.bin", &journal)?;
+    fs::write(
+        "ZKbin/batch_proofs.json",
+        serde_json::to_string_pretty(&proofs)?,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// This application serves the spxVrfy prover over HTTP for the frontend: a
+// signature file posted to /prove is queued and proven in the background,
+// /jobs/{id} reports its status, and /receipt/{id} serves the receipt,
+// journal and seal once it is done.
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use apps::{
+    prover::{self, ProverArgs},
+    receipt::ReceiptKind,
+    service::{self, Service, ServiceConfig},
+};
+use axum::http::{header, HeaderValue, Method};
+use clap::Parser;
+use tower_http::cors::CorsLayer;
+
+/// Prove SPHINCS+ signature verifications for HTTP clients.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1:3002")]
+    listen: SocketAddr,
+
+    /// Directory every job gets a directory in, for its signature file,
+    /// receipt.bin, journal.bin and seal.bin
+    #[clap(long, default_value = "jobs")]
+    storage_dir: PathBuf,
+
+    /// Number of jobs proven at the same time
+    #[clap(long, default_value_t = 1)]
+    workers: usize,
+
+    /// Number of jobs that can wait for a worker
+    #[clap(long, default_value_t = 64)]
+    queue: usize,
+
+    /// Kind of receipt to prove; ?kind= may only name this one
+    #[clap(long, value_enum, default_value_t = ReceiptKind::Groth16)]
+    kind: ReceiptKind,
+
+    /// Seconds a finished job is kept before its directory is deleted
+    #[clap(long, default_value_t = 86400)]
+    job_ttl: u64,
+
+    /// Number of finished jobs kept; the oldest are deleted beyond it
+    #[clap(long, default_value_t = 1000)]
+    max_jobs: usize,
+
+    /// Origins browsers may call the service from
+    #[clap(long, default_value = "http://localhost:3000")]
+    allow_origin: Vec<String>,
+
+    #[clap(flatten)]
+    prover: ProverArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+    args.prover.check(args.kind)?;
+
+    let origins = args
+        .allow_origin
+        .iter()
+        .map(|origin| HeaderValue::from_str(origin).with_context(|| format!("origin {origin}")))
+        .collect::<Result<Vec<_>>>()?;
+    let cors = CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::CONTENT_TYPE]);
+
+    let prover = args.prover.clone();
+    let service = Service::start(
+        ServiceConfig {
+            storage: args.storage_dir.clone(),
+            workers: args.workers,
+            queue: args.queue,
+            kind: args.kind,
+            ttl: Duration::from_secs(args.job_ttl),
+            max_jobs: args.max_jobs,
+        },
+        Arc::new(move |input, kind| prover::prove(&prover, input, kind)),
+    )?;
+    let app = service::router(service).layer(cors);
+
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("listening on {}", args.listen))?;
+    println!(
+        "Proving on http://{} with {} worker(s), jobs in {}",
+        args.listen,
+        args.workers,
+        args.storage_dir.display()
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,1047 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// This application proves that a SPHINCS+ signature verifies in the spxVrfy
+// guest, and either writes the seal and journal to disk or publishes them to
+// the deployed SpxVrfy contract. Its `wallet` commands drive ThresholdWallet
+// transactions, whose execution needs such a proof of the TSS signature, and
+// its `market` commands buy spxMarketplace NFTs with one.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    network::EthereumWallet,
+    providers::{Provider, ProviderBuilder},
+    rpc::types::TransactionReceipt,
+    signers::local::PrivateKeySigner,
+};
+use alloy_primitives::{Address, Bytes, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    input::InputArgs,
+    marketplace::{self, ISpxMarketplace},
+    profile,
+    prover::{self, ProverArgs},
+    publish::{self, TxArgs},
+    receipt::{ReceiptKind, SavedReceipt},
+    verify::verify_seal,
+    wallet::{self, IThresholdWallet, IThresholdWalletFactory, TransactionStatus},
+};
+use clap::{Args, Parser, Subcommand};
+use methods::SPXVRFY_ID;
+use spx_host::VerificationJournal;
+use url::Url;
+
+/// Prove, check and publish SPHINCS+ signature verifications.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Prove the signature and write the receipt, journal and seal to disk
+    Prove {
+        #[clap(flatten)]
+        input: InputArgs,
+
+        /// Kind of receipt to prove; only groth16 receipts get a seal.bin
+        #[clap(long, value_enum, default_value_t = ReceiptKind::Groth16)]
+        kind: ReceiptKind,
+
+        #[clap(flatten)]
+        prover: ProverArgs,
+
+        /// Directory the receipt.bin, journal.bin and seal.bin files are
+        /// written to
+        #[clap(long, default_value = "ZKbin")]
+        out_dir: PathBuf,
+    },
+    /// Load a receipt written by `prove`, verify it against the spxVrfy image
+    /// ID and write its journal and seal again
+    Export {
+        /// Receipt file to load
+        #[clap(long, default_value = "ZKbin/receipt.bin")]
+        receipt: PathBuf,
+
+        /// Directory the journal.bin and seal.bin files are written to
+        #[clap(long, default_value = "ZKbin")]
+        out_dir: PathBuf,
+    },
+    /// Run the guest without proving and print the journal and cycle count
+    Execute {
+        #[clap(flatten)]
+        input: InputArgs,
+
+        /// Break the cycles down by verification phase, with the guest's
+        /// phase markers on
+        #[clap(long)]
+        profile: bool,
+
+        /// File the profile is written to as JSON
+        #[clap(long, requires = "profile")]
+        report: Option<PathBuf>,
+    },
+    /// Verify a saved receipt, a seal and journal pair, or a fresh succinct
+    /// proof of `--sig` against the spxVrfy image ID, without an Ethereum node
+    VerifyLocal(VerifyArgs),
+    /// Prove the signature, or load a saved Groth16 receipt, and send the seal
+    /// to `ISpxVrfy.set`
+    Publish(Box<PublishArgs>),
+    /// Create ThresholdWallets and submit, prove, execute, cancel and list
+    /// their transactions
+    Wallet {
+        #[clap(subcommand)]
+        command: WalletCommand,
+    },
+    /// Set the TSS key of an spxMarketplace, list NFTs, approve coins, and
+    /// prove a buyer's signature to buy an NFT with
+    Market {
+        #[clap(subcommand)]
+        command: MarketCommand,
+    },
+    /// Decode a journal written by `prove`, and describe its seal
+    Inspect {
+        /// Journal file to decode
+        #[clap(long, default_value = "ZKbin/journal.bin")]
+        journal: PathBuf,
+
+        /// Seal file to describe
+        #[clap(long)]
+        seal: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// Signature proven with a succinct receipt
+    #[clap(flatten)]
+    input: InputArgs,
+
+    /// Receipt written by `prove`
+    #[clap(long, conflicts_with_all = ["sig", "seal"])]
+    receipt: Option<PathBuf>,
+
+    /// Seal written by `prove --kind groth16`, checked against `--journal`
+    /// as `ISpxVrfy.set` would
+    #[clap(long, requires = "journal", conflicts_with = "sig")]
+    seal: Option<PathBuf>,
+
+    /// Journal the seal is checked against
+    #[clap(long, requires = "seal")]
+    journal: Option<PathBuf>,
+
+    #[clap(flatten)]
+    prover: ProverArgs,
+}
+
+#[derive(Args, Debug)]
+struct PublishArgs {
+    #[clap(flatten)]
+    input: InputArgs,
+
+    /// Receipt written by `prove --kind groth16`, published instead of
+    /// proving `--sig`
+    #[clap(long, conflicts_with = "sig")]
+    receipt: Option<PathBuf>,
+
+    #[clap(flatten)]
+    prover: ProverArgs,
+
+    #[clap(flatten)]
+    tx: TxArgs,
+
+    #[clap(flatten)]
+    chain: ChainArgs,
+
+    /// Application's contract address on Ethereum
+    #[clap(long)]
+    contract: Address,
+}
+
+#[derive(Args, Debug)]
+struct ChainArgs {
+    /// Ethereum chain ID
+    #[clap(long)]
+    chain_id: u64,
+
+    /// Private key of the wallet sending the transaction
+    #[clap(long, env)]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum Node endpoint.
+    #[clap(long)]
+    rpc_url: Url,
+}
+
+#[derive(Subcommand, Debug)]
+enum WalletCommand {
+    /// Create a ThresholdWallet owned by the sender through a factory
+    Create {
+        /// ThresholdWalletFactory address
+        #[clap(long)]
+        factory: Address,
+
+        /// TSS public key to set on the new wallet, as hex
+        #[clap(long)]
+        tss_public_key: Option<Bytes>,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+    /// Submit a transaction, to be executed once the TSS signed it
+    Submit(Box<SubmitArgs>),
+    /// Prove the TSS signature for a pending transaction and write the proof
+    /// to `<out-dir>/<wallet>/<nonce>`
+    Prove {
+        #[clap(flatten)]
+        target: WalletTxArgs,
+
+        #[clap(flatten)]
+        input: InputArgs,
+
+        #[clap(flatten)]
+        prover: ProverArgs,
+
+        /// Ethereum Node endpoint.
+        #[clap(long)]
+        rpc_url: Url,
+
+        #[clap(long, default_value = "ZKbin")]
+        out_dir: PathBuf,
+    },
+    /// Execute a pending transaction with the proof written by `wallet prove`
+    Execute {
+        #[clap(flatten)]
+        target: WalletTxArgs,
+
+        /// Receipt to execute with, `<out-dir>/<wallet>/<nonce>/receipt.bin`
+        /// by default
+        #[clap(long)]
+        receipt: Option<PathBuf>,
+
+        #[clap(long, default_value = "ZKbin")]
+        out_dir: PathBuf,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+    /// Cancel a pending transaction
+    Cancel {
+        #[clap(flatten)]
+        target: WalletTxArgs,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+    /// List the transactions of a wallet
+    List {
+        /// ThresholdWallet address
+        #[clap(long)]
+        wallet: Address,
+
+        /// Ethereum Node endpoint.
+        #[clap(long)]
+        rpc_url: Url,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MarketCommand {
+    /// Set the TSS public key buyers must sign with; only the marketplace
+    /// owner can
+    SetTssKey {
+        /// spxMarketplace address
+        #[clap(long)]
+        marketplace: Address,
+
+        /// TSS public key, as hex
+        #[clap(long)]
+        public_key: Bytes,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+    /// List an NFT of the sender at a price, and let the marketplace transfer
+    /// it
+    List {
+        #[clap(flatten)]
+        target: MarketNftArgs,
+
+        /// Price in spxCoin base units
+        #[clap(long)]
+        price: U256,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+    /// Let the marketplace spend the sender's coins, the price of the NFT by
+    /// default
+    Approve {
+        #[clap(flatten)]
+        target: MarketNftArgs,
+
+        /// Amount to approve instead of the price
+        #[clap(long)]
+        amount: Option<U256>,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+    /// Prove a signature by the marketplace's TSS key of the purchase
+    /// message, `abi.encode(chainid, marketplace, nftId, buyer)`, and write
+    /// the proof to `<out-dir>/<marketplace>/<nft-id>`
+    Prove {
+        #[clap(flatten)]
+        target: MarketNftArgs,
+
+        /// Account that will send `market buy` with the proof
+        #[clap(long)]
+        buyer: Address,
+
+        #[clap(flatten)]
+        input: InputArgs,
+
+        #[clap(flatten)]
+        prover: ProverArgs,
+
+        /// Ethereum Node endpoint.
+        #[clap(long)]
+        rpc_url: Url,
+
+        #[clap(long, default_value = "ZKbin")]
+        out_dir: PathBuf,
+    },
+    /// Buy an NFT with the proof written by `market prove`, which must have
+    /// been made for the sender as `--buyer`
+    Buy {
+        #[clap(flatten)]
+        target: MarketNftArgs,
+
+        /// Receipt to buy with, `<out-dir>/<marketplace>/<nft-id>/receipt.bin`
+        /// by default
+        #[clap(long)]
+        receipt: Option<PathBuf>,
+
+        #[clap(long, default_value = "ZKbin")]
+        out_dir: PathBuf,
+
+        #[clap(flatten)]
+        chain: ChainArgs,
+
+        #[clap(flatten)]
+        tx: TxArgs,
+    },
+}
+
+#[derive(Args, Debug)]
+struct MarketNftArgs {
+    /// spxMarketplace address
+    #[clap(long)]
+    marketplace: Address,
+
+    /// ID of the spxNFT
+    #[clap(long)]
+    nft_id: U256,
+}
+
+#[derive(Args, Debug)]
+struct WalletTxArgs {
+    /// ThresholdWallet address
+    #[clap(long)]
+    wallet: Address,
+
+    /// Nonce of the wallet transaction
+    #[clap(long)]
+    nonce: U256,
+}
+
+#[derive(Args, Debug)]
+struct SubmitArgs {
+    /// ThresholdWallet address
+    #[clap(long)]
+    wallet: Address,
+
+    /// Address the wallet calls
+    #[clap(long)]
+    to: Address,
+
+    /// Value sent with the call, in wei
+    #[clap(long, default_value = "0")]
+    value: U256,
+
+    /// Call data, as hex
+    #[clap(long, default_value = "0x")]
+    data: Bytes,
+
+    /// Gas for the call, `estimateTransactionGas` of the wallet by default
+    #[clap(long)]
+    gas: Option<U256>,
+
+    #[clap(flatten)]
+    chain: ChainArgs,
+
+    #[clap(flatten)]
+    tx: TxArgs,
+}
+
+impl ChainArgs {
+    /// Connects to `--rpc-url` with the signer of `--eth-wallet-private-key`,
+    /// checking that the node is on `--chain-id`.
+    async fn connect(&self) -> Result<(impl Provider, Address)> {
+        let sender = self.eth_wallet_private_key.address();
+        let wallet = EthereumWallet::from(self.eth_wallet_private_key.clone());
+        let provider = ProviderBuilder::new()
+            .wallet(wallet)
+            .on_http(self.rpc_url.clone());
+
+        let chain_id = provider.get_chain_id().await?;
+        ensure!(
+            chain_id == self.chain_id,
+            "the node at {} is on chain {chain_id}, not {}",
+            self.rpc_url,
+            self.chain_id
+        );
+        Ok((provider, sender))
+    }
+}
+
+fn print_journal(journal: &[u8]) -> Result<()> {
+    // The contract decodes the same bytes, so what is printed here is exactly
+    // what gets recorded on-chain.
+    let output = VerificationJournal::decode(journal)?;
+
+    println!("Parameter set: {}", output.param_set);
+    println!("Public key: 0x{}", hex::encode(&output.public_key));
+    println!("Message hash: 0x{}", hex::encode(output.message_hash));
+    println!("Signature verified: {}", output.verified);
+    println!("Status: {} ({})", output.status as u8, output.status);
+    Ok(())
+}
+
+fn prove(input: InputArgs, kind: ReceiptKind, prover: ProverArgs, out_dir: PathBuf) -> Result<()> {
+    prover.check(kind)?;
+    let input = input.load()?;
+    let receipt = prover::prove(&prover, &input, kind)?;
+    let saved = SavedReceipt::new(receipt);
+    print_journal(&saved.receipt.journal.bytes)?;
+
+    saved.write_to(&out_dir)?;
+    println!("Wrote {} receipt to {}", saved.kind(), out_dir.display());
+    Ok(())
+}
+
+fn export(receipt: PathBuf, out_dir: PathBuf) -> Result<()> {
+    let saved = SavedReceipt::load(&receipt)?;
+    saved.verify()?;
+    println!("Receipt kind: {}", saved.kind());
+    print_journal(&saved.receipt.journal.bytes)?;
+
+    saved.write_to(&out_dir)?;
+    match saved.seal() {
+        Ok(seal) => println!(
+            "Wrote journal and {}-byte seal to {}",
+            seal.len(),
+            out_dir.display()
+        ),
+        Err(err) => println!("Wrote journal to {} ({err})", out_dir.display()),
+    }
+    Ok(())
+}
+
+fn execute(input: InputArgs, profile: bool, report: Option<PathBuf>) -> Result<()> {
+    if profile {
+        let profile = profile::profile(&input.load()?)?;
+        println!("{profile}");
+        if let Some(report) = report {
+            fs::write(&report, serde_json::to_string_pretty(&profile)?)
+                .with_context(|| format!("writing {}", report.display()))?;
+            println!("Wrote the profile to {}", report.display());
+        }
+        return Ok(());
+    }
+
+    let session_info = prover::execute(&input.load()?)?;
+    print_journal(&session_info.journal.bytes)?;
+    println!("Cycles: {}", session_info.cycles());
+    println!("Segments: {}", session_info.segments.len());
+    Ok(())
+}
+
+fn verify_local(args: VerifyArgs) -> Result<()> {
+    if let (Some(seal), Some(journal)) = (&args.seal, &args.journal) {
+        let seal = fs::read(seal).with_context(|| format!("reading {}", seal.display()))?;
+        let journal =
+            fs::read(journal).with_context(|| format!("reading {}", journal.display()))?;
+        verify_seal(&seal, &journal, SPXVRFY_ID)?;
+        print_journal(&journal)?;
+        println!("Seal verified against the journal and the spxVrfy image ID");
+        return Ok(());
+    }
+
+    let saved = match &args.receipt {
+        Some(path) => SavedReceipt::load(path)?,
+        None => {
+            ensure!(
+                args.input.sig.is_some(),
+                "--sig, --receipt or --seal is required"
+            );
+            args.prover.check(ReceiptKind::Succinct)?;
+            let input = args.input.load()?;
+            SavedReceipt::new(prover::prove(&args.prover, &input, ReceiptKind::Succinct)?)
+        }
+    };
+    saved.verify()?;
+    print_journal(&saved.receipt.journal.bytes)?;
+    println!(
+        "{} receipt verified against the spxVrfy image ID",
+        saved.kind()
+    );
+    Ok(())
+}
+
+fn publish(args: PublishArgs) -> Result<()> {
+    let saved = match &args.receipt {
+        Some(path) => {
+            let saved = SavedReceipt::load(path)?;
+            saved.verify()?;
+            saved
+        }
+        None => {
+            ensure!(args.input.sig.is_some(), "--sig or --receipt is required");
+            args.prover.check(ReceiptKind::Groth16)?;
+            let input = args.input.load()?;
+            SavedReceipt::new(prover::prove(&args.prover, &input, ReceiptKind::Groth16)?)
+        }
+    };
+
+    // Encode the seal with the selector.
+    let seal = saved.seal()?;
+    println!("Seal size: {} bytes", seal.len());
+
+    let journal = saved.receipt.journal.bytes.clone();
+    print_journal(&journal)?;
+
+    // Initialize the async runtime environment to handle the transaction sending.
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    let published = runtime.block_on(async {
+        // Create an alloy provider for that private key and URL.
+        let (provider, sender) = args.chain.connect().await?;
+        publish::publish(&provider, sender, args.contract, &journal, &seal, &args.tx).await
+    })?;
+    match published {
+        None => println!("Dry run: the verifier accepts the seal and set() does not revert"),
+        Some(published) => {
+            let receipt = &published.receipt;
+            println!("Transaction: {}", receipt.transaction_hash);
+            println!("Block: {}", receipt.block_number.unwrap_or_default());
+            println!("Gas used: {}", receipt.gas_used);
+            println!("Contract check: {}", published.check);
+        }
+    }
+    Ok(())
+}
+
+/// Where `wallet prove` writes the proof of a wallet transaction.
+fn wallet_proof_dir(out_dir: &Path, target: &WalletTxArgs) -> PathBuf {
+    out_dir
+        .join(target.wallet.to_string())
+        .join(target.nonce.to_string())
+}
+
+fn print_receipt(receipt: Option<&TransactionReceipt>) {
+    match receipt {
+        None => println!("Dry run: the transaction does not revert under eth_call"),
+        Some(receipt) => {
+            println!("Transaction: {}", receipt.transaction_hash);
+            println!("Block: {}", receipt.block_number.unwrap_or_default());
+            println!("Gas used: {}", receipt.gas_used);
+        }
+    }
+}
+
+fn wallet(command: WalletCommand) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let command = match command {
+        WalletCommand::Prove {
+            target,
+            input,
+            prover,
+            rpc_url,
+            out_dir,
+        } => return wallet_prove(&runtime, target, input, prover, rpc_url, out_dir),
+        command => command,
+    };
+    runtime.block_on(async {
+        match command {
+            WalletCommand::Create {
+                factory,
+                tss_public_key,
+                chain,
+                tx,
+            } => wallet_create(factory, tss_public_key, chain, tx).await,
+            WalletCommand::Submit(args) => wallet_submit(*args).await,
+            WalletCommand::Prove { .. } => unreachable!("proven outside the runtime"),
+            WalletCommand::Execute {
+                target,
+                receipt,
+                out_dir,
+                chain,
+                tx,
+            } => wallet_execute(target, receipt, out_dir, chain, tx).await,
+            WalletCommand::Cancel { target, chain, tx } => {
+                let (provider, sender) = chain.connect().await?;
+                let contract = IThresholdWallet::new(target.wallet, &provider);
+                let call = contract.cancelTransaction(target.nonce);
+                let receipt = wallet::send(&provider, sender, call, &tx).await?;
+                print_receipt(receipt.as_ref());
+                Ok(())
+            }
+            WalletCommand::List { wallet, rpc_url } => wallet_list(wallet, rpc_url).await,
+        }
+    })
+}
+
+async fn wallet_create(
+    factory: Address,
+    tss_public_key: Option<Bytes>,
+    chain: ChainArgs,
+    tx: TxArgs,
+) -> Result<()> {
+    let (provider, sender) = chain.connect().await?;
+    let contract = IThresholdWalletFactory::new(factory, &provider);
+    let Some(receipt) = wallet::send(&provider, sender, contract.createWallet(), &tx).await? else {
+        print_receipt(None);
+        return Ok(());
+    };
+    let created = receipt
+        .decoded_log::<IThresholdWalletFactory::WalletCreated>()
+        .context("createWallet() emitted no WalletCreated event")?;
+    print_receipt(Some(&receipt));
+    println!("Wallet: {}", created.wallet);
+
+    if let Some(tss_public_key) = tss_public_key {
+        let contract = IThresholdWallet::new(created.wallet, &provider);
+        let call = contract.setTssPublicKey(tss_public_key);
+        let receipt = wallet::send(&provider, sender, call, &tx).await?;
+        print_receipt(receipt.as_ref());
+        println!("TSS public key set");
+    }
+    Ok(())
+}
+
+async fn wallet_submit(args: SubmitArgs) -> Result<()> {
+    let (provider, sender) = args.chain.connect().await?;
+    let contract = IThresholdWallet::new(args.wallet, &provider);
+    let gas = match args.gas {
+        Some(gas) => gas,
+        None => {
+            contract
+                .estimateTransactionGas(args.to, args.value, args.data.clone())
+                .call()
+                .await?
+                ._0
+        }
+    };
+    let call = contract.submitTransaction(args.to, args.value, args.data, gas);
+    let receipt = wallet::send(&provider, sender, call, &args.tx).await?;
+    print_receipt(receipt.as_ref());
+    if let Some(receipt) = receipt {
+        let submitted = receipt
+            .decoded_log::<IThresholdWallet::TransactionSubmitted>()
+            .context("submitTransaction() emitted no TransactionSubmitted event")?;
+        println!("Nonce: {}", submitted.nonce);
+    }
+    Ok(())
+}
+
+/// Checks the transaction and the signature on chain, then proves outside
+/// `runtime`: the blocking Bonsai client panics inside it.
+fn wallet_prove(
+    runtime: &tokio::runtime::Runtime,
+    target: WalletTxArgs,
+    input: InputArgs,
+    prover: ProverArgs,
+    rpc_url: Url,
+    out_dir: PathBuf,
+) -> Result<()> {
+    prover.check(ReceiptKind::Groth16)?;
+    let input = input.load()?;
+    runtime.block_on(async {
+        let provider = ProviderBuilder::new().on_http(rpc_url);
+        let txn = wallet::pending_transaction(&provider, target.wallet, target.nonce).await?;
+        let contract = IThresholdWallet::new(target.wallet, &provider);
+        let tss_public_key = contract.tssPublicKey().call().await?._0;
+        ensure!(
+            input.bundle.public_key == tss_public_key[..],
+            "the signature is by public key 0x{}, but the wallet's TSS public key is {tss_public_key}",
+            hex::encode(&input.bundle.public_key)
+        );
+        let message =
+            wallet::expected_message(&provider, target.wallet, target.nonce, &txn).await?;
+        ensure!(
+            input.bundle.message == message,
+            "the signed message is not transaction {}; sign its transactionMessage() 0x{}",
+            target.nonce,
+            hex::encode(&message)
+        );
+        Ok(())
+    })?;
+
+    let saved = SavedReceipt::new(prover::prove(&prover, &input, ReceiptKind::Groth16)?);
+    print_journal(&saved.receipt.journal.bytes)?;
+    let journal = VerificationJournal::decode(&saved.receipt.journal.bytes)?;
+    ensure!(
+        journal.verified,
+        "the signature does not verify, the wallet would reject it: {}",
+        journal.status
+    );
+
+    let dir = wallet_proof_dir(&out_dir, &target);
+    saved.write_to(&dir)?;
+    println!(
+        "Wrote proof of transaction {} to {}",
+        target.nonce,
+        dir.display()
+    );
+    Ok(())
+}
+
+async fn wallet_execute(
+    target: WalletTxArgs,
+    receipt: Option<PathBuf>,
+    out_dir: PathBuf,
+    chain: ChainArgs,
+    tx: TxArgs,
+) -> Result<()> {
+    let path = receipt.unwrap_or_else(|| wallet_proof_dir(&out_dir, &target).join("receipt.bin"));
+    let saved = SavedReceipt::load(&path)?;
+    saved.verify()?;
+    let seal = saved.seal()?;
+    let journal = saved.receipt.journal.bytes.clone();
+    print_journal(&journal)?;
+
+    let (provider, sender) = chain.connect().await?;
+    let txn = wallet::pending_transaction(&provider, target.wallet, target.nonce).await?;
+    let message = wallet::expected_message(&provider, target.wallet, target.nonce, &txn).await?;
+    wallet::check_journal(
+        &provider,
+        target.wallet,
+        &VerificationJournal::decode(&journal)?,
+        &message,
+    )
+    .await?;
+
+    let contract = IThresholdWallet::new(target.wallet, &provider);
+    let call = contract.executeTransaction(target.nonce, journal.into(), seal.into());
+    let receipt = wallet::send(&provider, sender, call, &tx).await?;
+    print_receipt(receipt.as_ref());
+    if let Some(receipt) = receipt {
+        let executed = receipt
+            .decoded_log::<IThresholdWallet::TransactionExecuted>()
+            .context("executeTransaction() emitted no TransactionExecuted event")?;
+        println!(
+            "Executed transaction {}: {} wei to {}",
+            executed.nonce, executed.value, executed.to
+        );
+    }
+    Ok(())
+}
+
+async fn wallet_list(wallet: Address, rpc_url: Url) -> Result<()> {
+    let provider = ProviderBuilder::new().on_http(rpc_url);
+    let contract = IThresholdWallet::new(wallet, &provider);
+    let next_nonce = contract.nonce().call().await?._0;
+    let timeout = contract.TRANSACTION_TIMEOUT().call().await?._0;
+    let now = wallet::latest_timestamp(&provider).await?;
+
+    println!("Owner: {}", contract.owner().call().await?._0);
+    println!(
+        "TSS public key: {}",
+        contract.tssPublicKey().call().await?._0
+    );
+    let mut nonce = U256::ZERO;
+    while nonce < next_nonce {
+        let txn = contract.getTransaction(nonce).call().await?._0;
+        let status = match txn.status {
+            TransactionStatus::Pending if now > txn.timestamp + timeout => "timed out",
+            TransactionStatus::Pending => "pending",
+            TransactionStatus::Executed => "executed",
+            TransactionStatus::Cancelled => "cancelled",
+            _ => "nonexistent",
+        };
+        println!(
+            "{nonce}: {status}, {} wei to {}, gas {}, {} bytes of data",
+            txn.value,
+            txn.to,
+            txn.gas,
+            txn.data.len()
+        );
+        nonce += U256::from(1);
+    }
+    Ok(())
+}
+
+/// Where `market prove` writes the proof for an NFT.
+fn market_proof_dir(out_dir: &Path, target: &MarketNftArgs) -> PathBuf {
+    out_dir
+        .join(target.marketplace.to_string())
+        .join(target.nft_id.to_string())
+}
+
+fn market(command: MarketCommand) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let command = match command {
+        MarketCommand::Prove {
+            target,
+            buyer,
+            input,
+            prover,
+            rpc_url,
+            out_dir,
+        } => return market_prove(&runtime, target, buyer, input, prover, rpc_url, out_dir),
+        command => command,
+    };
+    runtime.block_on(async {
+        match command {
+            MarketCommand::SetTssKey {
+                marketplace,
+                public_key,
+                chain,
+                tx,
+            } => {
+                let (provider, sender) = chain.connect().await?;
+                let receipt = marketplace::set_tss_public_key(
+                    &provider,
+                    sender,
+                    marketplace,
+                    public_key,
+                    &tx,
+                )
+                .await?;
+                print_receipt(receipt.as_ref());
+                Ok(())
+            }
+            MarketCommand::List {
+                target,
+                price,
+                chain,
+                tx,
+            } => {
+                let (provider, sender) = chain.connect().await?;
+                let receipts = marketplace::list(
+                    &provider,
+                    sender,
+                    target.marketplace,
+                    target.nft_id,
+                    price,
+                    &tx,
+                )
+                .await?;
+                if receipts.is_empty() {
+                    print_receipt(None);
+                }
+                receipts
+                    .iter()
+                    .for_each(|receipt| print_receipt(Some(receipt)));
+                Ok(())
+            }
+            MarketCommand::Approve {
+                target,
+                amount,
+                chain,
+                tx,
+            } => {
+                let (provider, sender) = chain.connect().await?;
+                let amount = match amount {
+                    Some(amount) => amount,
+                    None => {
+                        let market = ISpxMarketplace::new(target.marketplace, &provider);
+                        let price = market.nftPrices(target.nft_id).call().await?._0;
+                        ensure!(
+                            price > U256::ZERO,
+                            "NFT {} is not for sale, give --amount",
+                            target.nft_id
+                        );
+                        price
+                    }
+                };
+                let receipt =
+                    marketplace::approve(&provider, sender, target.marketplace, amount, &tx)
+                        .await?;
+                print_receipt(receipt.as_ref());
+                println!("Approved: {amount}");
+                Ok(())
+            }
+            MarketCommand::Prove { .. } => unreachable!("proven outside the runtime"),
+            MarketCommand::Buy {
+                target,
+                receipt,
+                out_dir,
+                chain,
+                tx,
+            } => market_buy(target, receipt, out_dir, chain, tx).await,
+        }
+    })
+}
+
+/// Checks the sale and the signature on chain, then proves outside
+/// `runtime`, like [`wallet_prove`].
+fn market_prove(
+    runtime: &tokio::runtime::Runtime,
+    target: MarketNftArgs,
+    buyer: Address,
+    input: InputArgs,
+    prover: ProverArgs,
+    rpc_url: Url,
+    out_dir: PathBuf,
+) -> Result<()> {
+    prover.check(ReceiptKind::Groth16)?;
+    let provider = ProviderBuilder::new().on_http(rpc_url);
+    let input = input.load()?;
+    let message = runtime.block_on(async {
+        let market = ISpxMarketplace::new(target.marketplace, &provider);
+        let price = market.nftPrices(target.nft_id).call().await?._0;
+        ensure!(price > U256::ZERO, "NFT {} is not for sale", target.nft_id);
+        let tss_public_key = market.tssPublicKey().call().await?._0;
+        ensure!(
+            input.bundle.public_key == tss_public_key[..],
+            "the signature is by public key 0x{}, but the marketplace's TSS public key is {tss_public_key}",
+            hex::encode(&input.bundle.public_key)
+        );
+        let message =
+            marketplace::expected_message(&provider, target.marketplace, target.nft_id, buyer)
+                .await?;
+        ensure!(
+            input.bundle.message == message,
+            "the signed message is not the purchase of NFT {} by {buyer}; sign 0x{}",
+            target.nft_id,
+            hex::encode(&message)
+        );
+        Ok(message)
+    })?;
+
+    let saved = SavedReceipt::new(prover::prove(&prover, &input, ReceiptKind::Groth16)?);
+    print_journal(&saved.receipt.journal.bytes)?;
+    let journal = VerificationJournal::decode(&saved.receipt.journal.bytes)?;
+    runtime.block_on(marketplace::check_proof(
+        &provider,
+        target.marketplace,
+        &journal,
+        &message,
+    ))?;
+
+    let dir = market_proof_dir(&out_dir, &target);
+    saved.write_to(&dir)?;
+    println!("Wrote proof for NFT {} to {}", target.nft_id, dir.display());
+    Ok(())
+}
+
+async fn market_buy(
+    target: MarketNftArgs,
+    receipt: Option<PathBuf>,
+    out_dir: PathBuf,
+    chain: ChainArgs,
+    tx: TxArgs,
+) -> Result<()> {
+    let path = receipt.unwrap_or_else(|| market_proof_dir(&out_dir, &target).join("receipt.bin"));
+    let saved = SavedReceipt::load(&path)?;
+    saved.verify()?;
+    let seal = saved.seal()?;
+    print_journal(&saved.receipt.journal.bytes)?;
+    let journal = VerificationJournal::decode(&saved.receipt.journal.bytes)?;
+
+    let (provider, sender) = chain.connect().await?;
+    let receipt = marketplace::buy(
+        &provider,
+        sender,
+        target.marketplace,
+        target.nft_id,
+        &journal,
+        &seal,
+        &tx,
+    )
+    .await?;
+    print_receipt(receipt.as_ref());
+    if let Some(receipt) = receipt {
+        let bought = receipt
+            .decoded_log::<ISpxMarketplace::NFTBought>()
+            .context("buyNFT() emitted no NFTBought event")?;
+        println!("Bought NFT {} for {}", bought.nftId, bought.price);
+    }
+    Ok(())
+}
+
+fn inspect(journal: PathBuf, seal: Option<PathBuf>) -> Result<()> {
+    let bytes = fs::read(&journal).with_context(|| format!("reading {}", journal.display()))?;
+    print_journal(&bytes)?;
+
+    if let Some(seal) = seal {
+        let bytes = fs::read(&seal).with_context(|| format!("reading {}", seal.display()))?;
+        println!("Seal size: {} bytes", bytes.len());
+        // encode_seal prefixes the seal with the 4-byte selector of its verifier
+        if let Some(selector) = bytes.get(..4) {
+            println!("Seal selector: 0x{}", hex::encode(selector));
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+
+    match Cli::parse().command {
+        Command::Prove {
+            input,
+            kind,
+            prover,
+            out_dir,
+        } => prove(input, kind, prover, out_dir),
+        Command::Export { receipt, out_dir } => export(receipt, out_dir),
+        Command::Execute {
+            input,
+            profile,
+            report,
+        } => execute(input, profile, report),
+        Command::VerifyLocal(args) => verify_local(args),
+        Command::Publish(args) => publish(*args),
+        Command::Wallet { command } => wallet(command),
+        Command::Market { command } => market(command),
+        Command::Inspect { journal, seal } => inspect(journal, seal),
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading the signature to prove from the command line.
+//!
+//! `--sig` alone is a signature file: sig.json, or a `signature_result_*.json`
+//! of the frontend backend. With `--pk`, the key, signature and message are
+//! given separately, each as `0x`-prefixed hex, a `.hex` file holding hex
+//! text, or any other file holding the raw bytes.
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::Args;
+use spx_host::{check_mlen, GuestInput, SignatureBundle};
+
+/// The signature to prove and its parameter set.
+#[derive(Args, Clone, Debug)]
+pub struct InputArgs {
+    /// Signature file (sig.json or signature_result_*.json), or with --pk
+    /// the detached signature: 0x-hex, a .hex file or a raw binary file
+    #[clap(long)]
+    pub sig: Option<String>,
+
+    /// Public key, as 0x-hex, a .hex file or a raw binary file
+    #[clap(long, requires = "sig")]
+    pub pk: Option<String>,
+
+    /// Signed message, as 0x-hex, a .hex file or a raw binary file
+    #[clap(long, requires = "pk", conflicts_with = "mlen")]
+    pub msg: Option<String>,
+
+    /// With --pk and no --msg, --sig is the signed message
+    /// `signature ‖ message`, whose last mlen bytes are the message
+    #[clap(long, requires = "pk")]
+    pub mlen: Option<usize>,
+
+    /// SPHINCS+ parameter set the signature was produced with
+    #[clap(long, default_value = "sm3-128s-simple")]
+    pub param_set: String,
+}
+
+impl InputArgs {
+    /// Reads the signature, key and message.
+    pub fn bundle(&self) -> Result<SignatureBundle> {
+        let sig = self.sig.as_deref().context("--sig is required")?;
+        let Some(pk) = &self.pk else {
+            ensure!(
+                !sig.starts_with("0x"),
+                "a hex --sig needs --pk, and --msg or --mlen"
+            );
+            return SignatureBundle::load_for(sig, &self.param_set);
+        };
+
+        let public_key = read_bytes(pk).context("reading --pk")?;
+        let sig = read_bytes(sig).context("reading --sig")?;
+        match (&self.msg, self.mlen) {
+            (Some(msg), _) => Ok(SignatureBundle {
+                public_key,
+                signature: sig,
+                message: read_bytes(msg).context("reading --msg")?,
+            }),
+            (None, Some(mlen)) => {
+                check_mlen(sig.len(), mlen, &self.param_set)?;
+                SignatureBundle::from_signed_message(public_key, &sig, mlen)
+            }
+            (None, None) => bail!("--pk needs the message, as --msg or as --mlen"),
+        }
+    }
+
+    /// Loads and validates the guest input, so that a bad signature file
+    /// fails here instead of after proving its rejection.
+    pub fn load(&self) -> Result<GuestInput> {
+        let input = GuestInput::new(&self.param_set, self.bundle()?);
+        input.validate()?;
+        Ok(input)
+    }
+}
+
+/// Reads a command line value: `0x`-prefixed hex, a `.hex` file of hex text,
+/// or the raw bytes of any other file.
+pub fn read_bytes(value: &str) -> Result<Vec<u8>> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        return hex::decode(hex).context("decoding hex");
+    }
+    let path = Path::new(value);
+    let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    if path.extension().is_some_and(|ext| ext == "hex") {
+        let text = String::from_utf8(bytes).context("a .hex file must hold hex text")?;
+        let text: String = text.split_whitespace().collect();
+        return hex::decode(text.strip_prefix("0x").unwrap_or(&text))
+            .with_context(|| format!("decoding the hex in {}", path.display()));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIG_JSON: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../sig.json");
+
+    fn args(sig: &str) -> InputArgs {
+        InputArgs {
+            sig: Some(sig.into()),
+            pk: None,
+            msg: None,
+            mlen: None,
+            param_set: "sm3-128s-simple".into(),
+        }
+    }
+
+    #[test]
+    fn reads_separate_values() {
+        let expected = args(SIG_JSON).load().unwrap().bundle;
+        let dir = std::env::temp_dir().join(format!("spx-input-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (pk, sig, sm) = (dir.join("pk.hex"), dir.join("sig.bin"), dir.join("sm.bin"));
+        fs::write(&pk, format!("0x{}\n", hex::encode(&expected.public_key))).unwrap();
+        fs::write(&sig, &expected.signature).unwrap();
+        fs::write(
+            &sm,
+            [expected.signature.as_slice(), &expected.message].concat(),
+        )
+        .unwrap();
+
+        let mut detached = args(sig.to_str().unwrap());
+        detached.pk = Some(pk.to_str().unwrap().into());
+        detached.msg = Some(format!("0x{}", hex::encode(&expected.message)));
+        assert_eq!(detached.load().unwrap().bundle, expected);
+
+        let mut signed = args(sm.to_str().unwrap());
+        signed.pk = detached.pk.clone();
+        signed.mlen = Some(expected.message.len());
+        assert_eq!(signed.load().unwrap().bundle, expected);
+
+        signed.mlen = Some(expected.message.len() + 1);
+        let err = signed.load().unwrap_err();
+        assert!(err.to_string().contains("mlen"), "{err}");
+
+        signed.mlen = None;
+        assert!(signed.load().is_err(), "no message");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert!(read_bytes("0xabc").is_err());
+        assert!(read_bytes("/nonexistent/sig.bin").is_err());
+        assert!(args("0x00").load().is_err(), "hex --sig without --pk");
+    }
+}
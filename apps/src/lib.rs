@@ -0,0 +1,35 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host-side code shared by the apps binaries: proving the spxVrfy guest, the
+//! prover service and the `ISpxVrfy` bindings. The guest input and journal types live in
+//! `spx-host`.
+
+pub mod input;
+pub mod marketplace;
+pub mod profile;
+pub mod prover;
+pub mod publish;
+pub mod receipt;
+pub mod service;
+#[cfg(test)]
+mod test_utils;
+pub mod verify;
+pub mod wallet;
+
+// `ISpxVrfy` interface automatically generated via the alloy `sol!` macro.
+alloy::sol!(
+    #[sol(rpc, all_derives)]
+    "../contracts/ISpxVrfy.sol"
+);
@@ -0,0 +1,279 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `spxMarketplace`, `spxCoin` and `spxNFT` bindings, and the steps of a
+//! purchase: listing an NFT, approving coins and buying it with a proof.
+//!
+//! `spxMarketplace.getSignatureResult` accepts any non-empty `sig`, so the
+//! contract does not check the proof it is given. [`check_proof`] makes that
+//! check before `buyNFT` is sent with the seal as `sig`, including that the
+//! signed message is the [`purchase_message`] of that NFT and buyer.
+
+use alloy::{
+    contract::Error as ContractError, providers::Provider, rpc::types::TransactionReceipt,
+};
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_sol_types::SolValue;
+use anyhow::{anyhow, ensure, Result};
+use risc0_zkvm::sha::{Impl, Sha256};
+use spx_host::VerificationJournal;
+
+use crate::publish::{self, TxArgs};
+
+// `ISpxMarketplace`, `ISpxCoin` and `ISpxNFT` interfaces automatically
+// generated via the alloy `sol!` macro.
+alloy::sol!(
+    #[sol(rpc, all_derives)]
+    "../contracts/ISpxMarketplace.sol"
+);
+
+/// Describes a revert of a marketplace call. Apart from `Ownable`, the
+/// contracts revert with strings, which the node already includes.
+pub fn explain(err: ContractError) -> anyhow::Error {
+    match err.as_decoded_interface_error::<ISpxMarketplace::ISpxMarketplaceErrors>() {
+        Some(ISpxMarketplace::ISpxMarketplaceErrors::OwnableUnauthorizedAccount(e)) => {
+            anyhow!("only the marketplace owner can do this, not {}", e.account)
+        }
+        None => anyhow!(err),
+    }
+}
+
+/// An NFT listed on the marketplace.
+#[derive(Debug)]
+pub struct Listing {
+    pub seller: Address,
+    pub price: U256,
+}
+
+/// Sets the TSS public key the signatures of buyers must be made with; only
+/// the marketplace owner can.
+pub async fn set_tss_public_key<P: Provider>(
+    provider: &P,
+    sender: Address,
+    marketplace: Address,
+    public_key: Bytes,
+    tx: &TxArgs,
+) -> Result<Option<TransactionReceipt>> {
+    ensure!(!public_key.is_empty(), "the TSS public key is empty");
+    let market = ISpxMarketplace::new(marketplace, provider);
+    publish::send_checked(
+        provider,
+        sender,
+        market.setTSSPublicKey(public_key),
+        tx,
+        explain,
+    )
+    .await
+}
+
+/// Lists `nft_id`, owned by `sender`, at `price` coins, and lets the
+/// marketplace transfer the NFTs of `sender` unless it already can.
+///
+/// Returns the receipts of the transactions sent, none on a dry run.
+pub async fn list<P: Provider>(
+    provider: &P,
+    sender: Address,
+    marketplace: Address,
+    nft_id: U256,
+    price: U256,
+    tx: &TxArgs,
+) -> Result<Vec<TransactionReceipt>> {
+    // `buyNFT` reads a zero price as "not for sale"
+    ensure!(price > U256::ZERO, "the price must be above zero");
+    let market = ISpxMarketplace::new(marketplace, provider);
+    let nft = ISpxNFT::new(market.nft().call().await?._0, provider);
+    let owner = nft.ownerOf(nft_id).call().await?._0;
+    ensure!(
+        owner == sender,
+        "NFT {nft_id} is owned by {owner}, only the owner can list it"
+    );
+
+    let mut receipts = Vec::new();
+    let call = market.setPrice(nft_id, price);
+    receipts.extend(publish::send_checked(provider, sender, call, tx, explain).await?);
+    if !nft.isApprovedForAll(sender, marketplace).call().await?._0 {
+        let call = nft.setApprovalForAll(marketplace, true);
+        receipts.extend(publish::send_checked(provider, sender, call, tx, explain).await?);
+    }
+    Ok(receipts)
+}
+
+/// Lets the marketplace spend `amount` coins of `sender`.
+pub async fn approve<P: Provider>(
+    provider: &P,
+    sender: Address,
+    marketplace: Address,
+    amount: U256,
+    tx: &TxArgs,
+) -> Result<Option<TransactionReceipt>> {
+    let market = ISpxMarketplace::new(marketplace, provider);
+    let coin = ISpxCoin::new(market.coin().call().await?._0, provider);
+    let balance = coin.balanceOf(sender).call().await?._0;
+    ensure!(
+        balance >= amount,
+        "{sender} has {balance} coins, less than the {amount} to approve"
+    );
+    publish::send_checked(
+        provider,
+        sender,
+        coin.approve(marketplace, amount),
+        tx,
+        explain,
+    )
+    .await
+}
+
+/// Checks everything `buyNFT` requires of `buyer` but the signature: that
+/// `nft_id` is for sale, that the marketplace may transfer it, and that the
+/// buyer has and approved enough coins.
+pub async fn check_purchase<P: Provider>(
+    provider: &P,
+    buyer: Address,
+    marketplace: Address,
+    nft_id: U256,
+) -> Result<Listing> {
+    let market = ISpxMarketplace::new(marketplace, provider);
+    let price = market.nftPrices(nft_id).call().await?._0;
+    ensure!(price > U256::ZERO, "NFT {nft_id} is not for sale");
+
+    let nft = ISpxNFT::new(market.nft().call().await?._0, provider);
+    let seller = nft.ownerOf(nft_id).call().await?._0;
+    ensure!(
+        nft.isApprovedForAll(seller, marketplace).call().await?._0,
+        "the seller {seller} did not let the marketplace transfer NFT {nft_id}"
+    );
+
+    let coin = ISpxCoin::new(market.coin().call().await?._0, provider);
+    let balance = coin.balanceOf(buyer).call().await?._0;
+    ensure!(
+        balance >= price,
+        "NFT {nft_id} costs {price} coins, but {buyer} has {balance}"
+    );
+    let allowance = coin.allowance(buyer, marketplace).call().await?._0;
+    ensure!(
+        allowance >= price,
+        "NFT {nft_id} costs {price} coins, but {buyer} approved the marketplace for {allowance}, approve it first"
+    );
+    Ok(Listing { seller, price })
+}
+
+/// The message the TSS key signs to let `buyer` buy `nft_id` from
+/// `marketplace` on chain `chain_id`: `abi.encode(chainid, marketplace,
+/// nftId, buyer)`.
+pub fn purchase_message(
+    chain_id: u64,
+    marketplace: Address,
+    nft_id: U256,
+    buyer: Address,
+) -> Vec<u8> {
+    (U256::from(chain_id), marketplace, nft_id, buyer).abi_encode_params()
+}
+
+/// [`purchase_message`] of `nft_id` and `buyer` on the chain of `provider`.
+pub async fn expected_message<P: Provider>(
+    provider: &P,
+    marketplace: Address,
+    nft_id: U256,
+    buyer: Address,
+) -> Result<Vec<u8>> {
+    let chain_id = provider.get_chain_id().await?;
+    Ok(purchase_message(chain_id, marketplace, nft_id, buyer))
+}
+
+/// Checks that `journal` is a verified signature of `message` by the TSS
+/// public key of the marketplace, the check `getSignatureResult` leaves out.
+pub async fn check_proof<P: Provider>(
+    provider: &P,
+    marketplace: Address,
+    journal: &VerificationJournal,
+    message: &[u8],
+) -> Result<()> {
+    let market = ISpxMarketplace::new(marketplace, provider);
+    let tss_public_key = market.tssPublicKey().call().await?._0;
+    ensure!(
+        !tss_public_key.is_empty(),
+        "marketplace {marketplace} has no TSS public key, set one first"
+    );
+    ensure!(
+        journal.public_key == tss_public_key[..],
+        "the signature is by public key 0x{}, but the marketplace's TSS public key is {tss_public_key}",
+        hex::encode(&journal.public_key)
+    );
+    ensure!(
+        journal.verified,
+        "the journal does not prove a verified signature: {}",
+        journal.status
+    );
+    ensure!(
+        journal.message_hash[..] == *Impl::hash_bytes(message).as_bytes(),
+        "the signed message is not the purchase's, 0x{}",
+        hex::encode(message)
+    );
+    Ok(())
+}
+
+/// Buys `nft_id` for `sender` with the `seal` of a verified proof of
+/// `journal`, after [`check_proof`] against the [`purchase_message`] of
+/// `nft_id` and `sender`, and [`check_purchase`].
+pub async fn buy<P: Provider>(
+    provider: &P,
+    sender: Address,
+    marketplace: Address,
+    nft_id: U256,
+    journal: &VerificationJournal,
+    seal: &[u8],
+    tx: &TxArgs,
+) -> Result<Option<TransactionReceipt>> {
+    ensure!(!seal.is_empty(), "the seal is empty");
+    let message = expected_message(provider, marketplace, nft_id, sender).await?;
+    check_proof(provider, marketplace, journal, &message).await?;
+    check_purchase(provider, sender, marketplace, nft_id).await?;
+
+    let market = ISpxMarketplace::new(marketplace, provider);
+    let call = market.buyNFT(nft_id, seal.to_vec().into());
+    publish::send_checked(provider, sender, call, tx, explain).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_purchase_messages_like_abi_encode() {
+        let marketplace = Address::repeat_byte(0x11);
+        let buyer = Address::repeat_byte(0x22);
+        let message = purchase_message(31337, marketplace, U256::from(7), buyer);
+
+        let word = |i: usize| U256::from_be_slice(&message[32 * i..32 * (i + 1)]);
+        assert_eq!(message.len(), 32 * 4);
+        assert_eq!(word(0), U256::from(31337));
+        assert_eq!(message[32 + 12..64], marketplace[..]);
+        assert_eq!(word(2), U256::from(7));
+        assert_eq!(message[3 * 32 + 12..4 * 32], buyer[..]);
+
+        // Each field is part of the message
+        assert_ne!(
+            purchase_message(31337, marketplace, U256::from(8), buyer),
+            message
+        );
+        assert_ne!(
+            purchase_message(31337, marketplace, U256::from(7), marketplace),
+            message
+        );
+        assert_ne!(
+            purchase_message(1, marketplace, U256::from(7), buyer),
+            message
+        );
+    }
+}
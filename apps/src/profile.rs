@@ -0,0 +1,213 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cycle and segment profile of a spxVrfy execution.
+//!
+//! With `SPX_PROFILE` set, the guest writes `spx-phase <name> <cycles>` to
+//! stderr as each phase starts: `read_input`, `decode_input`, then the
+//! `hash_message`, `fors`, `layer_<i>` and `done` phases of the verification,
+//! `commit_journal` and `end`. A phase lasts until the next marker. The
+//! markers themselves cost a few thousand cycles each, so compare profiles
+//! with each other rather than with plain executions.
+
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use methods::SPXVRFY_ELF;
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use serde::Serialize;
+use spx_host::{GuestInput, VerificationJournal};
+
+/// Prefix of the guest's phase markers on stderr.
+const MARKER: &str = "spx-phase ";
+
+/// Cycles spent in one phase of the guest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PhaseCycles {
+    pub name: String,
+    pub cycles: u64,
+}
+
+/// One segment of the execution.
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentProfile {
+    /// The segment is proven as `2^po2` cycles
+    pub po2: u32,
+    pub cycles: u32,
+}
+
+/// Report of `spx-zk execute --profile`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Profile {
+    pub param_set: String,
+    pub verified: bool,
+    pub status: String,
+    /// User cycles of the whole execution
+    pub user_cycles: u64,
+    /// Cycles the prover pays for, each segment padded to its power of two
+    pub proving_cycles: u64,
+    pub segments: Vec<SegmentProfile>,
+    /// Cycles before the first marker, spent starting the guest
+    pub startup_cycles: u64,
+    pub phases: Vec<PhaseCycles>,
+}
+
+impl Profile {
+    /// Cycles of all `layer_<i>` phases together.
+    pub fn hypertree_cycles(&self) -> u64 {
+        self.phases
+            .iter()
+            .filter(|phase| phase.name.starts_with("layer_"))
+            .map(|phase| phase.cycles)
+            .sum()
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Parameter set: {}", self.param_set)?;
+        writeln!(f, "User cycles: {}", self.user_cycles)?;
+        writeln!(f, "Proving cycles: {}", self.proving_cycles)?;
+        let po2s: Vec<String> = self.segments.iter().map(|s| s.po2.to_string()).collect();
+        writeln!(
+            f,
+            "Segments: {} (po2 {})",
+            self.segments.len(),
+            po2s.join(", ")
+        )?;
+        writeln!(f, "{:<16} {:>12} {:>7}", "phase", "cycles", "share")?;
+        let share = |cycles: u64| cycles as f64 * 100.0 / self.user_cycles.max(1) as f64;
+        writeln!(
+            f,
+            "{:<16} {:>12} {:>6.1}%",
+            "startup",
+            self.startup_cycles,
+            share(self.startup_cycles)
+        )?;
+        for phase in &self.phases {
+            writeln!(
+                f,
+                "{:<16} {:>12} {:>6.1}%",
+                phase.name,
+                phase.cycles,
+                share(phase.cycles)
+            )?;
+        }
+        let hypertree = self.hypertree_cycles();
+        write!(
+            f,
+            "{:<16} {:>12} {:>6.1}%",
+            "(hypertree)",
+            hypertree,
+            share(hypertree)
+        )
+    }
+}
+
+/// Runs the spxVrfy guest on `input` with phase markers on, without proving.
+pub fn profile(input: &GuestInput) -> Result<Profile> {
+    let mut stderr = Vec::new();
+    let env = ExecutorEnv::builder()
+        .write_slice(&input.encode())
+        .env_var("SPX_PROFILE", "1")
+        .stderr(&mut stderr)
+        .build()
+        .context("creating executor env")?;
+    let session_info = default_executor().execute(env, SPXVRFY_ELF)?;
+    let journal = VerificationJournal::decode(&session_info.journal.bytes)?;
+
+    let (startup_cycles, phases) = parse_markers(&String::from_utf8_lossy(&stderr))?;
+    let segments: Vec<SegmentProfile> = session_info
+        .segments
+        .iter()
+        .map(|segment| SegmentProfile {
+            po2: segment.po2,
+            cycles: segment.cycles,
+        })
+        .collect();
+    Ok(Profile {
+        param_set: journal.param_set,
+        verified: journal.verified,
+        status: journal.status.to_string(),
+        user_cycles: session_info.cycles(),
+        proving_cycles: segments.iter().map(|segment| 1u64 << segment.po2).sum(),
+        segments,
+        startup_cycles,
+        phases,
+    })
+}
+
+/// Turns the guest's markers into the cycles before the first one and the
+/// cycles of each phase. Other stderr lines are ignored.
+fn parse_markers(stderr: &str) -> Result<(u64, Vec<PhaseCycles>)> {
+    let mut markers = Vec::new();
+    for line in stderr.lines() {
+        let Some(marker) = line.strip_prefix(MARKER) else {
+            continue;
+        };
+        let (name, cycles) = marker
+            .rsplit_once(' ')
+            .with_context(|| format!("malformed phase marker {line:?}"))?;
+        let cycles: u64 = cycles
+            .parse()
+            .with_context(|| format!("malformed phase marker {line:?}"))?;
+        markers.push((name.to_string(), cycles));
+    }
+    let Some(((_, startup), (last, _))) = markers.first().zip(markers.last()) else {
+        bail!("the guest wrote no phase markers; is it built with profiling support?");
+    };
+    if last != "end" {
+        bail!("the guest stopped after phase {last}");
+    }
+
+    let phases = markers
+        .windows(2)
+        .map(|pair| PhaseCycles {
+            name: pair[0].0.clone(),
+            cycles: pair[1].1.saturating_sub(pair[0].1),
+        })
+        .collect();
+    Ok((*startup, phases))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_markers() {
+        let stderr = "spx-phase read_input 1000\n\
+                      spx-phase decode_input 1500\n\
+                      some guest output\n\
+                      spx-phase hash_message 4000\n\
+                      spx-phase layer_0 9000\n\
+                      spx-phase end 9100\n";
+        let (startup, phases) = parse_markers(stderr).unwrap();
+        assert_eq!(startup, 1000);
+        let phases: Vec<_> = phases.iter().map(|p| (p.name.as_str(), p.cycles)).collect();
+        assert_eq!(
+            phases,
+            [
+                ("read_input", 500),
+                ("decode_input", 2500),
+                ("hash_message", 5000),
+                ("layer_0", 100)
+            ]
+        );
+
+        assert!(parse_markers("").is_err(), "no markers");
+        assert!(parse_markers("spx-phase fors 10\n").is_err(), "no end");
+        assert!(parse_markers("spx-phase end x\n").is_err());
+    }
+}
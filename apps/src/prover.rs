@@ -0,0 +1,265 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running the spxVrfy guest, and choosing the prover that proves it.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::{Args, ValueEnum};
+use methods::{SPXVRFY_ELF, SPXVRFY_ID};
+use risc0_zkvm::{
+    default_executor, default_prover, is_dev_mode, sha::Digest, ExecutorEnv, Receipt, SessionInfo,
+    VerifierContext,
+};
+use spx_host::GuestInput;
+
+use crate::{receipt::ReceiptKind, verify::verify_receipt};
+
+/// Where the guest is proven.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Whatever risc0 picks from `RISC0_PROVER` and the Bonsai variables
+    Default,
+    /// CPU proving on this machine with the `r0vm` installed by rzup, or the
+    /// one `RISC0_SERVER_PATH` points to
+    Local,
+    /// Fake receipts that only verify with `RISC0_DEV_MODE` set, for CI
+    Dev,
+    /// A `--prover-cmd` process, e.g. a wrapper around a GPU prover
+    External,
+}
+
+/// Prover options shared by every command that proves.
+#[derive(Args, Clone, Debug)]
+pub struct ProverArgs {
+    /// Prover backend
+    #[clap(long, value_enum, env = "SPX_PROVER", default_value_t = Backend::Default)]
+    pub prover: Backend,
+
+    /// Command the external backend runs as
+    /// `<cmd> --elf <file> --input <file> --kind <kind> --out <file>`; it
+    /// must write the bincode-encoded receipt to `--out`
+    #[clap(long, env = "SPX_PROVER_CMD", required_if_eq("prover", "external"))]
+    pub prover_cmd: Option<PathBuf>,
+}
+
+impl ProverArgs {
+    /// Fails before any work is done when the backend can't make a `kind`
+    /// receipt. Call it once, before proving: it sets the environment the
+    /// prover reads.
+    ///
+    /// The dev backend sets `RISC0_DEV_MODE` for the whole process, so that
+    /// the fake receipts it makes also verify. Setting the environment is
+    /// only sound while no other thread runs, so [`Self::prove_elf`] does
+    /// not do it again for every proof.
+    pub fn check(&self, kind: ReceiptKind) -> Result<()> {
+        match self.prover {
+            Backend::Default => {}
+            Backend::Local => env::set_var("RISC0_PROVER", "ipc"),
+            Backend::Dev => {
+                env::set_var("RISC0_PROVER", "ipc");
+                env::set_var("RISC0_DEV_MODE", "1");
+            }
+            // The external prover wraps Groth16 itself, wherever it runs
+            Backend::External => {
+                let cmd = self
+                    .prover_cmd
+                    .as_ref()
+                    .context("the external backend needs --prover-cmd")?;
+                which(cmd)
+                    .with_context(|| format!("cannot find prover command {}", cmd.display()))?;
+                return Ok(());
+            }
+        }
+
+        if kind == ReceiptKind::Groth16 && !is_dev_mode() && !uses_bonsai() {
+            check_groth16()?;
+        }
+        Ok(())
+    }
+
+    /// Proves `elf` on `input` with a `kind` receipt, once [`Self::check`]
+    /// passed for `kind`.
+    ///
+    /// Receipts of an external prover are checked against `image_id`, so a
+    /// misbehaving prover command fails here rather than on-chain.
+    pub fn prove_elf(
+        &self,
+        elf: &[u8],
+        image_id: impl Into<Digest>,
+        input: &[u8],
+        kind: ReceiptKind,
+    ) -> Result<Receipt> {
+        if self.prover == Backend::External {
+            let receipt = self.prove_external(elf, input, kind)?;
+            verify_receipt(&receipt, image_id)
+                .context("the external prover returned a receipt that does not verify")?;
+            return Ok(receipt);
+        }
+
+        let env = ExecutorEnv::builder()
+            .write_slice(input)
+            .build()
+            .context("creating executor env")?;
+        let prove_info = default_prover()
+            .prove_with_ctx(env, &VerifierContext::default(), elf, &kind.prover_opts())
+            .with_context(|| match self.prover {
+                Backend::Local | Backend::Dev => {
+                    "proving with r0vm; install it with `rzup install` or set RISC0_SERVER_PATH"
+                }
+                _ => "proving the guest",
+            })?;
+        Ok(prove_info.receipt)
+    }
+
+    fn prove_external(&self, elf: &[u8], input: &[u8], kind: ReceiptKind) -> Result<Receipt> {
+        // One directory per call, as the prover service proves several
+        // inputs at once
+        static CALLS: AtomicU64 = AtomicU64::new(0);
+        let cmd = self
+            .prover_cmd
+            .as_ref()
+            .context("the external backend needs --prover-cmd")?;
+        let dir = env::temp_dir().join(format!(
+            "spx-prove-{}-{}",
+            std::process::id(),
+            CALLS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        let (elf_path, input_path, out_path) = (
+            dir.join("guest.elf"),
+            dir.join("input.bin"),
+            dir.join("receipt.bin"),
+        );
+        fs::write(&elf_path, elf)?;
+        fs::write(&input_path, input)?;
+
+        let result = (|| {
+            let status = Command::new(cmd)
+                .arg("--elf")
+                .arg(&elf_path)
+                .arg("--input")
+                .arg(&input_path)
+                .arg("--kind")
+                .arg(kind.name())
+                .arg("--out")
+                .arg(&out_path)
+                .status()
+                .with_context(|| format!("running prover command {}", cmd.display()))?;
+            ensure!(
+                status.success(),
+                "prover command {} failed: {status}",
+                cmd.display()
+            );
+            let bytes = fs::read(&out_path)
+                .with_context(|| format!("prover command {} wrote no receipt", cmd.display()))?;
+            bincode::deserialize(&bytes).context("decoding the receipt of the prover command")
+        })();
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+}
+
+/// Whether the default prover hands the proof to Bonsai, which wraps Groth16
+/// remotely.
+fn uses_bonsai() -> bool {
+    match env::var("RISC0_PROVER") {
+        Ok(prover) if !prover.is_empty() => prover.eq_ignore_ascii_case("bonsai"),
+        _ => env::var("BONSAI_API_URL").is_ok() && env::var("BONSAI_API_KEY").is_ok(),
+    }
+}
+
+/// Groth16 wrapping runs the risc0 Groth16 prover image in Docker, which
+/// only exists for x86_64 Linux.
+fn check_groth16() -> Result<()> {
+    const ALTERNATIVES: &str =
+        "prove a succinct receipt with --kind succinct, or fake one with --prover dev";
+
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        bail!("Groth16 wrapping is only available on x86_64 Linux; {ALTERNATIVES}");
+    }
+    match Command::new("docker").arg("info").output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(_) => bail!(
+            "Groth16 wrapping needs Docker, but the Docker daemon is not running; {ALTERNATIVES}"
+        ),
+        Err(_) => bail!("Groth16 wrapping needs Docker, which is not installed; {ALTERNATIVES}"),
+    }
+}
+
+/// Resolves `cmd` like the shell would, through `PATH` when it is a bare name.
+fn which(cmd: &Path) -> Result<PathBuf> {
+    if cmd.components().count() > 1 {
+        ensure!(cmd.is_file(), "{} does not exist", cmd.display());
+        return Ok(cmd.to_path_buf());
+    }
+    env::var_os("PATH")
+        .iter()
+        .flat_map(env::split_paths)
+        .map(|dir| dir.join(cmd))
+        .find(|path| path.is_file())
+        .context("not found in PATH")
+}
+
+/// Proves the spxVrfy guest on `input` with a `kind` receipt, once
+/// [`ProverArgs::check`] passed for `kind`.
+///
+/// Only a Groth16 receipt has a seal that can be sent on-chain.
+pub fn prove(prover: &ProverArgs, input: &GuestInput, kind: ReceiptKind) -> Result<Receipt> {
+    prover.prove_elf(SPXVRFY_ELF, SPXVRFY_ID, &input.encode(), kind)
+}
+
+/// Runs the spxVrfy guest on `input` without proving.
+pub fn execute(input: &GuestInput) -> Result<SessionInfo> {
+    let env = ExecutorEnv::builder()
+        .write_slice(&input.encode())
+        .build()
+        .context("creating executor env")?;
+
+    default_executor().execute(env, SPXVRFY_ELF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn external(cmd: &str) -> ProverArgs {
+        ProverArgs {
+            prover: Backend::External,
+            prover_cmd: Some(cmd.into()),
+        }
+    }
+
+    #[test]
+    fn rejects_missing_prover_cmd() {
+        let err = external("/nonexistent/prover")
+            .check(ReceiptKind::Groth16)
+            .unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/prover"), "{err}");
+    }
+
+    #[test]
+    fn reports_failing_prover_cmd() {
+        let err = external("false")
+            .prove_elf(b"elf", SPXVRFY_ID, b"input", ReceiptKind::Succinct)
+            .unwrap_err();
+        assert!(err.to_string().contains("failed"), "{err}");
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sending a seal and journal to `ISpxVrfy.set`, and checking afterwards what
+//! the contract recorded.
+//!
+//! `SpxVrfy.set` catches a failing verifier call and records `check = 2`
+//! instead of reverting, so a transaction that succeeds says nothing about the
+//! proof. The seal is therefore checked with `eth_call` against the verifier
+//! before sending, and `get()`/`getCheck()` are read back after.
+
+use std::time::{Duration, Instant};
+
+use alloy::{
+    contract::{CallBuilder, CallDecoder, Error as ContractError},
+    eips::BlockId,
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+};
+use alloy_primitives::{Address, TxHash, B256};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use clap::Args;
+use methods::SPXVRFY_ID;
+use risc0_ethereum_contracts::IRiscZeroVerifier;
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
+use spx_host::VerificationJournal;
+
+use crate::ISpxVrfy;
+
+/// `getCheck()` after a `set` whose signature verified.
+pub const CHECK_VERIFIED: u128 = 1;
+/// `getCheck()` after a `set` whose proof or signature was rejected.
+pub const CHECK_REJECTED: u128 = 2;
+
+/// How `set` transactions are sent.
+#[derive(Args, Clone, Debug)]
+pub struct TxArgs {
+    /// Blocks to wait for on top of the one that includes the transaction
+    #[clap(long, default_value_t = 1)]
+    pub confirmations: u64,
+
+    /// Times to resend a transaction that could not be sent or was not mined
+    /// in time; resends reuse the nonce with higher fees
+    #[clap(long, default_value_t = 3)]
+    pub retries: u32,
+
+    /// Seconds to wait before the first resend, doubled for every next one
+    #[clap(long, default_value_t = 2)]
+    pub backoff: u64,
+
+    /// Seconds to wait for a sent transaction to be mined and confirmed
+    #[clap(long, default_value_t = 120)]
+    pub timeout: u64,
+
+    /// EIP-1559 max fee per gas in wei, estimated by the node when unset
+    #[clap(long)]
+    pub max_fee_per_gas: Option<u128>,
+
+    /// EIP-1559 max priority fee per gas in wei, estimated by the node when
+    /// unset
+    #[clap(long)]
+    pub max_priority_fee_per_gas: Option<u128>,
+
+    /// Gas limit, the estimate plus 20% when unset
+    #[clap(long)]
+    pub gas_limit: Option<u64>,
+
+    /// Check the seal with `eth_call` and stop before sending
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// A `set` transaction that was mined and whose effect was read back.
+#[derive(Debug)]
+pub struct Published {
+    pub receipt: TransactionReceipt,
+    /// `getCheck()` at the block of the transaction
+    pub check: u128,
+}
+
+/// Sends `journal` and `seal` to the `ISpxVrfy` contract at `contract` from
+/// `sender`, and returns `None` on a dry run.
+///
+/// Fails before sending when the contract accepts another image ID, when its
+/// verifier rejects the seal, or when `set` reverts under `eth_call`; and
+/// after sending when the transaction reverts or the contract did not record
+/// what the journal says.
+pub async fn publish<P: Provider>(
+    provider: &P,
+    sender: Address,
+    contract: Address,
+    journal: &[u8],
+    seal: &[u8],
+    tx: &TxArgs,
+) -> Result<Option<Published>> {
+    let expected = VerificationJournal::decode(journal)?;
+    let contract = ISpxVrfy::new(contract, provider);
+    let gas = preflight(provider, &contract, journal, seal).await?;
+    if tx.dry_run {
+        return Ok(None);
+    }
+
+    let call = contract.set(journal.to_vec().into(), seal.to_vec().into());
+    let receipt = send(provider, sender, &call, gas, tx).await?;
+    let tx_hash = receipt.transaction_hash;
+    ensure!(
+        receipt.status(),
+        "transaction {tx_hash} reverted in block {:?}",
+        receipt.block_number
+    );
+
+    // Read at the block of the transaction, so a later `set` by someone else
+    // can't be mistaken for ours.
+    let block = BlockId::number(
+        receipt
+            .block_number
+            .context("receipt has no block number")?,
+    );
+    let verified = contract.get().block(block).call().await?._0;
+    let check = contract.getCheck().block(block).call().await?._0;
+    match check {
+        CHECK_VERIFIED if expected.verified && verified => {}
+        CHECK_REJECTED if !expected.verified && !verified => {}
+        CHECK_REJECTED if expected.verified => bail!(
+            "transaction {tx_hash} was mined, but the contract rejected the proof and recorded check = {check}"
+        ),
+        _ => bail!(
+            "after transaction {tx_hash} the contract records get() = {verified} and check = {check}, but the journal says verified = {}",
+            expected.verified
+        ),
+    }
+    Ok(Some(Published { receipt, check }))
+}
+
+/// Runs `set` under `eth_call` and returns the gas it needs, after checking
+/// that the contract and its verifier accept the seal.
+async fn preflight<P: Provider>(
+    provider: &P,
+    contract: &ISpxVrfy::ISpxVrfyInstance<(), &P>,
+    journal: &[u8],
+    seal: &[u8],
+) -> Result<u64> {
+    let image_id = contract
+        .imageId()
+        .call()
+        .await
+        .context("reading imageId()")?
+        ._0;
+    let expected = B256::from_slice(Digest::from(SPXVRFY_ID).as_bytes());
+    ensure!(
+        image_id == expected,
+        "the contract accepts proofs of image ID {image_id}, but this build's spxVrfy image ID is {expected}"
+    );
+
+    let verifier = contract
+        .verifier()
+        .call()
+        .await
+        .context("reading verifier()")?
+        ._0;
+    let journal_digest = B256::from_slice(Impl::hash_bytes(journal).as_bytes());
+    IRiscZeroVerifier::new(verifier, provider)
+        .verify(seal.to_vec().into(), image_id, journal_digest)
+        .call()
+        .await
+        .map_err(|err| {
+            anyhow!("the verifier at {verifier} rejects the seal, so set() would record check = {CHECK_REJECTED}: {err}")
+        })?;
+
+    let call = contract.set(journal.to_vec().into(), seal.to_vec().into());
+    call.call().await.context("set() reverts under eth_call")?;
+    call.estimate_gas()
+        .await
+        .context("estimating the gas of set()")
+}
+
+/// Runs `call` under `eth_call`, then sends it with [`send`] unless
+/// `tx.dry_run`, and fails when it reverts.
+///
+/// `explain` turns a revert under `eth_call` into an error naming the
+/// contract's custom error.
+pub async fn send_checked<P: Provider, Q: Provider + Clone, D: CallDecoder + Clone>(
+    provider: &P,
+    sender: Address,
+    call: CallBuilder<(), Q, D>,
+    tx: &TxArgs,
+    explain: impl Fn(ContractError) -> anyhow::Error,
+) -> Result<Option<TransactionReceipt>> {
+    call.call_raw()
+        .await
+        .map_err(&explain)
+        .context("the call reverts under eth_call")?;
+    let gas = call.estimate_gas().await.map_err(&explain)?;
+    if tx.dry_run {
+        return Ok(None);
+    }
+
+    let receipt = send(provider, sender, &call, gas, tx).await?;
+    ensure!(
+        receipt.status(),
+        "transaction {} reverted in block {:?}",
+        receipt.transaction_hash,
+        receipt.block_number
+    );
+    Ok(Some(receipt))
+}
+
+/// Sends `call`, resending with the same nonce and bumped fees until one of
+/// the transactions is mined and confirmed.
+///
+/// The receipt is returned whether or not the transaction reverted.
+pub async fn send<P: Provider, Q: Provider + Clone, D: CallDecoder + Clone>(
+    provider: &P,
+    sender: Address,
+    call: &CallBuilder<(), Q, D>,
+    estimated_gas: u64,
+    tx: &TxArgs,
+) -> Result<TransactionReceipt> {
+    let nonce = provider
+        .get_transaction_count(sender)
+        .pending()
+        .await
+        .context("reading the sender nonce")?;
+    let gas = tx.gas_limit.unwrap_or(estimated_gas + estimated_gas / 5);
+    let (mut max_fee, mut priority_fee) = match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+        (Some(max_fee), Some(priority_fee)) => (max_fee, priority_fee),
+        (max_fee, priority_fee) => {
+            let estimate = provider
+                .estimate_eip1559_fees()
+                .await
+                .context("estimating EIP-1559 fees")?;
+            (
+                max_fee.unwrap_or(estimate.max_fee_per_gas),
+                priority_fee.unwrap_or(estimate.max_priority_fee_per_gas),
+            )
+        }
+    };
+    ensure!(
+        priority_fee <= max_fee,
+        "max priority fee {priority_fee} is above the max fee {max_fee}"
+    );
+
+    let mut sent: Vec<TxHash> = Vec::new();
+    let mut last_err = anyhow!("no attempt made");
+    for attempt in 0..=tx.retries {
+        if attempt > 0 {
+            let delay = Duration::from_secs(tx.backoff << (attempt - 1).min(16));
+            log::warn!("attempt {attempt} failed: {last_err:#}; retrying in {delay:?}");
+            tokio::time::sleep(delay).await;
+            // A replacement needs at least 10% higher fees
+            max_fee += max_fee / 8;
+            priority_fee += priority_fee / 8;
+        }
+
+        // An earlier transaction may have been mined while we waited
+        if let Some(receipt) = find_receipt(provider, &sent, tx).await? {
+            return Ok(receipt);
+        }
+
+        let pending = match call
+            .clone()
+            .nonce(nonce)
+            .gas(gas)
+            .max_fee_per_gas(max_fee)
+            .max_priority_fee_per_gas(priority_fee)
+            .send()
+            .await
+        {
+            Ok(pending) => pending,
+            Err(err) => {
+                last_err = anyhow!(err).context("sending the transaction");
+                continue;
+            }
+        };
+        let tx_hash = *pending.tx_hash();
+        log::info!("sent transaction {tx_hash} with nonce {nonce}");
+        sent.push(tx_hash);
+
+        // One required confirmation means mined, without blocks on top
+        match pending
+            .with_required_confirmations(tx.confirmations + 1)
+            .with_timeout(Some(Duration::from_secs(tx.timeout)))
+            .get_receipt()
+            .await
+        {
+            Ok(receipt) => return Ok(receipt),
+            Err(err) => last_err = anyhow!(err).context(format!("waiting for {tx_hash}")),
+        }
+    }
+
+    match find_receipt(provider, &sent, tx).await? {
+        Some(receipt) => Ok(receipt),
+        None => Err(last_err.context(format!(
+            "the transaction was not mined after {} attempts",
+            tx.retries + 1
+        ))),
+    }
+}
+
+/// Receipt of whichever of the `sent` transactions was mined, once it has
+/// `tx.confirmations` blocks on top of it.
+async fn find_receipt<P: Provider>(
+    provider: &P,
+    sent: &[TxHash],
+    tx: &TxArgs,
+) -> Result<Option<TransactionReceipt>> {
+    for tx_hash in sent {
+        if provider.get_transaction_receipt(*tx_hash).await?.is_some() {
+            return confirm(provider, *tx_hash, tx).await.map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Waits at most `tx.timeout` seconds for `tx.confirmations` blocks on top
+/// of the one that includes the mined `tx_hash`. The receipt is read again
+/// every block, so that a reorganisation moving the transaction is seen.
+async fn confirm<P: Provider>(
+    provider: &P,
+    tx_hash: TxHash,
+    tx: &TxArgs,
+) -> Result<TransactionReceipt> {
+    let deadline = Instant::now() + Duration::from_secs(tx.timeout);
+    loop {
+        let receipt = provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .with_context(|| format!("{tx_hash} is no longer mined"))?;
+        let mined = receipt
+            .block_number
+            .with_context(|| format!("the receipt of {tx_hash} has no block number"))?;
+        let head = provider
+            .get_block_number()
+            .await
+            .context("reading the block number")?;
+        if head >= mined + tx.confirmations {
+            return Ok(receipt);
+        }
+        ensure!(
+            Instant::now() < deadline,
+            "{tx_hash} did not get {} confirmations in {} seconds",
+            tx.confirmations,
+            tx.timeout
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Receipts saved to disk, so that a proof made on one machine can be checked
+//! and published from another.
+
+use std::{fs, path::Path};
+
+use anyhow::{ensure, Context, Result};
+use clap::ValueEnum;
+use methods::SPXVRFY_ID;
+use risc0_ethereum_contracts::encode_seal;
+use risc0_zkvm::{sha::Digest, InnerReceipt, ProverOpts, Receipt};
+use serde::{Deserialize, Serialize};
+
+use crate::verify::verify_receipt;
+
+/// Kind of receipt to prove.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReceiptKind {
+    /// One STARK per segment
+    Composite,
+    /// A single STARK over all segments
+    Succinct,
+    /// A succinct receipt wrapped in Groth16, the only kind with an on-chain seal
+    Groth16,
+}
+
+impl ReceiptKind {
+    /// Name of the kind as given to `--kind`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ReceiptKind::Composite => "composite",
+            ReceiptKind::Succinct => "succinct",
+            ReceiptKind::Groth16 => "groth16",
+        }
+    }
+
+    pub fn prover_opts(self) -> ProverOpts {
+        match self {
+            ReceiptKind::Composite => ProverOpts::composite(),
+            ReceiptKind::Succinct => ProverOpts::succinct(),
+            ReceiptKind::Groth16 => ProverOpts::groth16(),
+        }
+    }
+}
+
+/// A receipt together with the image ID it was proven for.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedReceipt {
+    pub image_id: Digest,
+    pub receipt: Receipt,
+}
+
+impl SavedReceipt {
+    /// Wraps a receipt of the spxVrfy guest of this build.
+    pub fn new(receipt: Receipt) -> Self {
+        Self {
+            image_id: SPXVRFY_ID.into(),
+            receipt,
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let bytes = bincode::serialize(self).context("serializing receipt")?;
+        fs::write(path, bytes).with_context(|| format!("writing {}", path.display()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        bincode::deserialize(&bytes).with_context(|| format!("decoding {}", path.display()))
+    }
+
+    /// Name of the receipt kind, `fake` for dev-mode receipts.
+    pub fn kind(&self) -> &'static str {
+        match self.receipt.inner {
+            InnerReceipt::Composite(_) => "composite",
+            InnerReceipt::Succinct(_) => "succinct",
+            InnerReceipt::Groth16(_) => "groth16",
+            InnerReceipt::Fake(_) => "fake",
+            _ => "unknown",
+        }
+    }
+
+    /// Checks that the receipt was saved for the spxVrfy guest of this build
+    /// and that it verifies against `SPXVRFY_ID`.
+    pub fn verify(&self) -> Result<()> {
+        let expected = Digest::from(SPXVRFY_ID);
+        ensure!(
+            self.image_id == expected,
+            "receipt was saved for image ID {}, but this build's spxVrfy image ID is {expected}",
+            self.image_id
+        );
+        verify_receipt(&self.receipt, expected)
+            .with_context(|| format!("{} receipt does not verify against SPXVRFY_ID", self.kind()))
+    }
+
+    /// The seal `ISpxVrfy.set` expects, prefixed with its verifier selector.
+    pub fn seal(&self) -> Result<Vec<u8>> {
+        encode_seal(&self.receipt).with_context(|| {
+            format!(
+                "a {} receipt has no on-chain seal, prove a groth16 receipt",
+                self.kind()
+            )
+        })
+    }
+
+    /// Writes `receipt.bin` and `journal.bin` to `out_dir`, and `seal.bin`
+    /// when the receipt has an on-chain seal. A `seal.bin` left over from an
+    /// earlier proof is removed otherwise, so it never pairs with the wrong
+    /// journal.
+    pub fn write_to(&self, out_dir: impl AsRef<Path>) -> Result<()> {
+        let out_dir = out_dir.as_ref();
+        fs::create_dir_all(out_dir)?;
+        self.save(out_dir.join("receipt.bin"))?;
+        // 合约 set(journal, seal) 需要同一份 journal
+        fs::write(out_dir.join("journal.bin"), &self.receipt.journal.bytes)?;
+        let seal_path = out_dir.join("seal.bin");
+        match self.seal() {
+            Ok(seal) => fs::write(seal_path, seal)?,
+            Err(_) if seal_path.exists() => fs::remove_file(seal_path)?,
+            Err(_) => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn fake_receipt(image_id: Digest) -> SavedReceipt {
+        SavedReceipt {
+            image_id,
+            receipt: test_utils::fake_receipt(image_id, b"journal", b"journal"),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = std::env::temp_dir().join(format!("spx-receipt-{}", std::process::id()));
+        let saved = fake_receipt(SPXVRFY_ID.into());
+        saved.write_to(&dir).unwrap();
+
+        let loaded = SavedReceipt::load(dir.join("receipt.bin")).unwrap();
+        assert_eq!(loaded.image_id, saved.image_id);
+        assert_eq!(loaded.kind(), "fake");
+        assert_eq!(fs::read(dir.join("journal.bin")).unwrap(), b"journal");
+        // Fake receipts get the all-ones selector
+        assert_eq!(fs::read(dir.join("seal.bin")).unwrap()[..4], [0xff; 4]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_other_image_id() {
+        let err = fake_receipt(Digest::from([0xffff_ffff; 8]))
+            .verify()
+            .unwrap_err();
+        assert!(err.to_string().contains("image ID"), "{err}");
+    }
+}
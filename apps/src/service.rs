@@ -0,0 +1,767 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The HTTP prover service of `spx-prover`.
+//!
+//! A signature file posted to `/prove` becomes a job, which waits in a queue
+//! until one of the workers proves it. `/jobs/{id}` reports the job's status
+//! as JSON, and once it is done `/receipt/{id}` serves its files. Every job
+//! writes to a directory of its own under the storage directory, so jobs
+//! proven at the same time never share a file.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path as FsPath, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use clap::ValueEnum;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use spx_host::{GuestInput, VerificationJournal};
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::{
+    input::InputArgs,
+    receipt::{ReceiptKind, SavedReceipt},
+};
+
+/// Proves a guest input with a receipt of the given kind: [`crate::prover::prove`]
+/// with the service's backend, or a stand-in in tests.
+pub type ProveFn = dyn Fn(&GuestInput, ReceiptKind) -> Result<Receipt> + Send + Sync;
+
+/// Files `/receipt/{id}/{file}` serves, as `prove` writes them.
+const RECEIPT_FILES: [&str; 3] = ["receipt.bin", "journal.bin", "seal.bin"];
+
+/// How the service runs its jobs.
+#[derive(Clone, Debug)]
+pub struct ServiceConfig {
+    /// Directory the job directories are created in
+    pub storage: PathBuf,
+    /// Number of jobs proven at the same time
+    pub workers: usize,
+    /// Number of jobs that can wait; `/prove` answers 503 beyond it
+    pub queue: usize,
+    /// Kind of receipt proven, the one the backend was checked for
+    pub kind: ReceiptKind,
+    /// How long a finished job is kept before it is deleted
+    pub ttl: Duration,
+    /// Number of finished jobs kept; the oldest are deleted beyond it
+    pub max_jobs: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl JobState {
+    fn is_finished(self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed)
+    }
+}
+
+/// Status of a job, as `/jobs/{id}` returns it and its `status.json` keeps
+/// it across restarts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub state: JobState,
+    pub param_set: String,
+    pub kind: String,
+    /// Why the job failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the guest verified the signature, once done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    /// Status the guest committed to the journal, once done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal_status: Option<String>,
+    /// Files `/receipt/{id}/{file}` serves, once done; `seal.bin` only for
+    /// receipts with an on-chain seal
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+struct Job {
+    id: String,
+    input: GuestInput,
+    kind: ReceiptKind,
+}
+
+#[derive(Default)]
+struct Jobs {
+    statuses: HashMap<String, JobStatus>,
+    /// Finished jobs, oldest first, with the time they finished
+    finished: VecDeque<(Instant, String)>,
+}
+
+/// The job queue and the status of every job kept.
+pub struct Service {
+    config: ServiceConfig,
+    jobs: Mutex<Jobs>,
+    queue: mpsc::Sender<Job>,
+    next_id: AtomicU64,
+}
+
+impl Service {
+    /// Creates the storage directory, restores the jobs of an earlier run
+    /// from it and starts the workers, which prove with `prove`. Must be
+    /// called within a Tokio runtime.
+    pub fn start(config: ServiceConfig, prove: Arc<ProveFn>) -> Result<Arc<Self>> {
+        fs::create_dir_all(&config.storage)
+            .with_context(|| format!("creating {}", config.storage.display()))?;
+        let jobs = restore(&config.storage)?;
+        let (queue, queued) = mpsc::channel(config.queue.max(1));
+        let service = Arc::new(Self {
+            config,
+            jobs: Mutex::new(jobs),
+            queue,
+            next_id: AtomicU64::new(0),
+        });
+        tokio::spawn(dispatch(service.clone(), queued, prove));
+        tokio::spawn(sweep(service.clone()));
+        Ok(service)
+    }
+
+    /// The status of job `id`.
+    pub fn status(&self, id: &str) -> Option<JobStatus> {
+        self.jobs.lock().unwrap().statuses.get(id).cloned()
+    }
+
+    /// Changes the status of job `id` and writes it to its `status.json`.
+    async fn update(&self, id: &str, update: impl FnOnce(&mut JobStatus)) {
+        let status = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(status) = jobs.statuses.get_mut(id) else {
+                return;
+            };
+            update(status);
+            let status = status.clone();
+            if status.state.is_finished() {
+                jobs.finished.push_back((Instant::now(), id.to_string()));
+            }
+            status
+        };
+        let path = self.config.storage.join(id).join("status.json");
+        if let Err(err) = tokio::fs::write(&path, status_json(&status)).await {
+            log::warn!("writing {}: {err}", path.display());
+        }
+    }
+
+    /// Forgets the finished jobs past the TTL or beyond `max_jobs`, and
+    /// deletes their directories.
+    async fn evict(&self) {
+        let now = Instant::now();
+        let expired: Vec<String> = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let mut expired = Vec::new();
+            while let Some((finished, _)) = jobs.finished.front() {
+                let keep = now.duration_since(*finished) < self.config.ttl
+                    && jobs.finished.len() <= self.config.max_jobs;
+                if keep {
+                    break;
+                }
+                let (_, id) = jobs.finished.pop_front().unwrap();
+                jobs.statuses.remove(&id);
+                expired.push(id);
+            }
+            expired
+        };
+        for id in expired {
+            let dir = self.config.storage.join(&id);
+            if let Err(err) = tokio::fs::remove_dir_all(&dir).await {
+                log::warn!("deleting {}: {err}", dir.display());
+            }
+        }
+    }
+
+    /// Creates a new job directory, named after the time and a counter so
+    /// that it is not one of an earlier run either.
+    fn create_job_dir(&self) -> Result<(String, PathBuf)> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis());
+        loop {
+            let id = format!(
+                "{millis:x}-{}",
+                self.next_id.fetch_add(1, Ordering::Relaxed)
+            );
+            let dir = self.config.storage.join(&id);
+            match fs::create_dir(&dir) {
+                Ok(()) => return Ok((id, dir)),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err).with_context(|| format!("creating {}", dir.display())),
+            }
+        }
+    }
+
+    /// Stores the signature file of a new job with its status, and loads
+    /// it. A file that does not load leaves no directory behind.
+    fn store(&self, body: &[u8], param_set: &str) -> Result<(JobStatus, GuestInput), ApiError> {
+        let (id, dir) = self.create_job_dir().map_err(ApiError::internal)?;
+        let path = dir.join("input.json");
+        let args = InputArgs {
+            sig: Some(path.to_string_lossy().into_owned()),
+            pk: None,
+            msg: None,
+            mlen: None,
+            param_set: param_set.to_string(),
+        };
+        let status = JobStatus {
+            id,
+            state: JobState::Queued,
+            param_set: param_set.to_string(),
+            kind: self.config.kind.name().to_string(),
+            error: None,
+            verified: None,
+            journal_status: None,
+            files: Vec::new(),
+        };
+        let stored = fs::write(&path, body)
+            .and_then(|()| fs::write(dir.join("status.json"), status_json(&status)))
+            .map_err(ApiError::internal)
+            .and_then(|()| {
+                args.load().map_err(|err| {
+                    // Without the server's storage path in the message
+                    let message =
+                        format!("{err:#}").replace(&*path.to_string_lossy(), "the signature file");
+                    ApiError::new(StatusCode::BAD_REQUEST, message)
+                })
+            });
+        match stored {
+            Ok(input) => Ok((status, input)),
+            Err(err) => {
+                let _ = fs::remove_dir_all(&dir);
+                Err(err)
+            }
+        }
+    }
+
+    /// Stores the signature file of a new job and queues the job. A file
+    /// that does not load, or a full queue, leaves no job behind.
+    async fn submit(
+        self: &Arc<Self>,
+        body: Bytes,
+        param_set: String,
+    ) -> Result<JobStatus, ApiError> {
+        let service = self.clone();
+        // Writing and parsing the file blocks
+        let (status, input) = tokio::task::spawn_blocking(move || service.store(&body, &param_set))
+            .await
+            .map_err(ApiError::internal)??;
+
+        let id = status.id.clone();
+        self.jobs
+            .lock()
+            .unwrap()
+            .statuses
+            .insert(id.clone(), status.clone());
+        let job = Job {
+            id: id.clone(),
+            input,
+            kind: self.config.kind,
+        };
+        if self.queue.try_send(job).is_err() {
+            self.jobs.lock().unwrap().statuses.remove(&id);
+            let _ = tokio::fs::remove_dir_all(self.config.storage.join(&id)).await;
+            return Err(ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "the job queue is full, try again later",
+            ));
+        }
+        Ok(status)
+    }
+}
+
+fn status_json(status: &JobStatus) -> Vec<u8> {
+    serde_json::to_vec_pretty(status).expect("a status serializes")
+}
+
+/// Reads the statuses of the jobs in `storage`. A job that was queued or
+/// running when the service stopped is failed, and a directory left without
+/// a status by a stop during `/prove` is deleted. Restored jobs count as
+/// finished now for the TTL.
+fn restore(storage: &FsPath) -> Result<Jobs> {
+    let mut jobs = Jobs::default();
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(storage).with_context(|| format!("reading {}", storage.display()))? {
+        let dir = entry?.path();
+        let Some(id) = dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let status = fs::read(dir.join("status.json"))
+            .ok()
+            .and_then(|json| serde_json::from_slice::<JobStatus>(&json).ok());
+        match status {
+            Some(mut status) if status.id == id => {
+                if !status.state.is_finished() {
+                    status.state = JobState::Failed;
+                    status.error = Some("the service stopped before the job finished".into());
+                    fs::write(dir.join("status.json"), status_json(&status))?;
+                }
+                ids.push(status.id.clone());
+                jobs.statuses.insert(status.id.clone(), status);
+            }
+            _ if dir.join("input.json").is_file() => {
+                fs::remove_dir_all(&dir).with_context(|| format!("deleting {}", dir.display()))?;
+            }
+            // Not a job directory
+            _ => {}
+        }
+    }
+    // Ids start with the creation time, so this keeps the oldest first
+    ids.sort();
+    let now = Instant::now();
+    jobs.finished = ids.into_iter().map(|id| (now, id)).collect();
+    Ok(jobs)
+}
+
+/// Deletes expired jobs every minute, or more often for short TTLs.
+async fn sweep(service: Arc<Service>) {
+    let period = service
+        .config
+        .ttl
+        .clamp(Duration::from_millis(10), Duration::from_secs(60));
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        service.evict().await;
+    }
+}
+
+/// Hands the queued jobs to at most `workers` proving tasks at a time.
+async fn dispatch(service: Arc<Service>, mut jobs: mpsc::Receiver<Job>, prove: Arc<ProveFn>) {
+    let workers = Arc::new(Semaphore::new(service.config.workers.max(1)));
+    while let Some(job) = jobs.recv().await {
+        let Ok(worker) = workers.clone().acquire_owned().await else {
+            return;
+        };
+        let (service, prove) = (service.clone(), prove.clone());
+        tokio::spawn(async move {
+            run(&service, job, prove).await;
+            drop(worker);
+        });
+    }
+}
+
+async fn run(service: &Service, job: Job, prove: Arc<ProveFn>) {
+    let id = job.id.clone();
+    let dir = service.config.storage.join(&id);
+    service
+        .update(&id, |status| status.state = JobState::Running)
+        .await;
+
+    // Proving blocks for minutes, so it runs off the async threads
+    let proven = tokio::task::spawn_blocking(move || -> Result<SavedReceipt> {
+        let saved = SavedReceipt::new(prove(&job.input, job.kind)?);
+        saved.write_to(&dir)?;
+        Ok(saved)
+    })
+    .await
+    .unwrap_or_else(|err| Err(anyhow::anyhow!("the prover panicked: {err}")));
+
+    service
+        .update(&id, |status| match proven {
+            Ok(saved) => {
+                status.state = JobState::Done;
+                if let Ok(journal) = VerificationJournal::decode(&saved.receipt.journal.bytes) {
+                    status.verified = Some(journal.verified);
+                    status.journal_status = Some(journal.status.to_string());
+                }
+                let files = if saved.seal().is_ok() { 3 } else { 2 };
+                status.files = RECEIPT_FILES[..files].iter().map(|&f| f.into()).collect();
+            }
+            Err(err) => {
+                log::warn!("job {id} failed: {err:#}");
+                status.state = JobState::Failed;
+                status.error = Some(format!("{err:#}"));
+            }
+        })
+        .await;
+    service.evict().await;
+}
+
+/// An error response: the status code, with `{"error": <message>}`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn internal(err: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+/// The routes of the service.
+pub fn router(service: Arc<Service>) -> Router {
+    Router::new()
+        .route("/prove", post(prove))
+        .route("/jobs/{id}", get(job))
+        .route("/receipt/{id}", get(receipt))
+        .route("/receipt/{id}/{file}", get(receipt_file))
+        .with_state(service)
+}
+
+#[derive(Debug, Deserialize)]
+struct ProveQuery {
+    param_set: Option<String>,
+    kind: Option<String>,
+}
+
+/// `POST /prove`: queues the signature file in the body, a sig.json or a
+/// `signature_result_*.json`, and answers 202 with the job's status.
+///
+/// `?kind=` may only name the kind the service proves, which its backend
+/// was checked for at startup.
+async fn prove(
+    State(service): State<Arc<Service>>,
+    Query(query): Query<ProveQuery>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<JobStatus>), ApiError> {
+    if let Some(kind) = &query.kind {
+        let configured = service.config.kind;
+        if ReceiptKind::from_str(kind, true) != Ok(configured) {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "this service proves {} receipts, not {kind}",
+                    configured.name()
+                ),
+            ));
+        }
+    }
+    let param_set = query
+        .param_set
+        .unwrap_or_else(|| "sm3-128s-simple".to_string());
+    let status = service.submit(body, param_set).await?;
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// `GET /jobs/{id}`: the job's status.
+async fn job(
+    State(service): State<Arc<Service>>,
+    Path(id): Path<String>,
+) -> Result<Json<JobStatus>, ApiError> {
+    service
+        .status(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("no job {id}")))
+}
+
+/// `GET /receipt/{id}`: the job's `receipt.bin`.
+async fn receipt(state: State<Arc<Service>>, Path(id): Path<String>) -> Result<Response, ApiError> {
+    receipt_file(state, Path((id, RECEIPT_FILES[0].to_string()))).await
+}
+
+/// `GET /receipt/{id}/{file}`: `receipt.bin`, `journal.bin` or `seal.bin` of
+/// a finished job.
+async fn receipt_file(
+    State(service): State<Arc<Service>>,
+    Path((id, file)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let status = service
+        .status(&id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("no job {id}")))?;
+    if status.state != JobState::Done {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("job {id} is {:?}, not done", status.state).to_lowercase(),
+        ));
+    }
+    let Some(file) = status.files.into_iter().find(|name| *name == file) else {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("job {id} has no {file}"),
+        ));
+    };
+    let bytes = tokio::fs::read(service.config.storage.join(&id).join(&file))
+        .await
+        .map_err(ApiError::internal)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file}\""),
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{body::Body, http::Request};
+    use methods::SPXVRFY_ID;
+    use spx_host::Status;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::test_utils::fake_receipt;
+
+    const SIG_JSON: &str = include_str!("../../sig.json");
+
+    /// Commits the journal the guest would, without running it.
+    fn fake_prove(input: &GuestInput, _kind: ReceiptKind) -> Result<Receipt> {
+        let journal = VerificationJournal {
+            public_key: input.bundle.public_key.clone(),
+            message_hash: input.bundle.message_hash(),
+            param_set: input.param_set.clone(),
+            verified: true,
+            status: Status::Verified,
+        }
+        .encode();
+        Ok(fake_receipt(SPXVRFY_ID, &journal, &journal))
+    }
+
+    fn config(name: &str) -> ServiceConfig {
+        let storage =
+            std::env::temp_dir().join(format!("spx-service-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&storage);
+        ServiceConfig {
+            storage,
+            workers: 2,
+            queue: 8,
+            kind: ReceiptKind::Succinct,
+            ttl: Duration::from_secs(3600),
+            max_jobs: 100,
+        }
+    }
+
+    fn start(name: &str, prove: Arc<ProveFn>) -> (Router, PathBuf) {
+        let config = config(name);
+        let storage = config.storage.clone();
+        (router(Service::start(config, prove).unwrap()), storage)
+    }
+
+    async fn request(app: &Router, method: &str, uri: &str, body: &str) -> (StatusCode, Bytes) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, body)
+    }
+
+    async fn submit(app: &Router, body: &str) -> serde_json::Value {
+        let (status, body) = request(app, "POST", "/prove", body).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{body:?}");
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Polls the job until it is no longer queued or running.
+    async fn wait(app: &Router, id: &str) -> serde_json::Value {
+        for _ in 0..500 {
+            let (status, body) = request(app, "GET", &format!("/jobs/{id}"), "").await;
+            assert_eq!(status, StatusCode::OK);
+            let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            if job["state"] != "queued" && job["state"] != "running" {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {id} did not finish");
+    }
+
+    #[tokio::test]
+    async fn proves_jobs_into_their_own_directories() {
+        let (app, storage) = start("prove", Arc::new(fake_prove));
+        let first = submit(&app, SIG_JSON).await;
+        let second = submit(&app, SIG_JSON).await;
+        assert_eq!(first["state"], "queued");
+        assert_eq!(first["kind"], "succinct");
+        assert_ne!(first["id"], second["id"]);
+
+        for job in [first, second] {
+            let id = job["id"].as_str().unwrap();
+            let job = wait(&app, id).await;
+            assert_eq!(job["state"], "done", "{job}");
+            assert_eq!(job["verified"], true);
+            assert_eq!(job["journal_status"], "verified");
+
+            let (status, journal) =
+                request(&app, "GET", &format!("/receipt/{id}/journal.bin"), "").await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(
+                &journal[..],
+                fs::read(storage.join(id).join("journal.bin")).unwrap()
+            );
+            let (status, receipt) = request(&app, "GET", &format!("/receipt/{id}"), "").await;
+            assert_eq!(status, StatusCode::OK);
+            let saved: SavedReceipt = bincode::deserialize(&receipt).unwrap();
+            assert_eq!(saved.receipt.journal.bytes, journal);
+            assert!(storage.join(id).join("input.json").exists());
+        }
+        fs::remove_dir_all(storage).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reports_bad_requests_and_failed_jobs() {
+        let failing =
+            |_: &GuestInput, _: ReceiptKind| -> Result<Receipt> { anyhow::bail!("no prover here") };
+        let (app, storage) = start("fail", Arc::new(failing));
+
+        let (status, body) = request(&app, "POST", "/prove", "{}").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body:?}");
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let error = error["error"].as_str().unwrap();
+        assert!(error.starts_with("parsing the signature file"), "{error}");
+        let (status, _) = request(&app, "POST", "/prove?kind=plonk", SIG_JSON).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        // Only the kind the backend was checked for
+        let (status, body) = request(&app, "POST", "/prove?kind=groth16", SIG_JSON).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body:?}");
+        let (status, _) = request(&app, "POST", "/prove?kind=succinct", "{}").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = request(&app, "GET", "/jobs/nope", "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        // Refused files leave no job directory behind
+        assert_eq!(fs::read_dir(&storage).unwrap().count(), 0);
+
+        let job = submit(&app, SIG_JSON).await;
+        let id = job["id"].as_str().unwrap();
+        let job = wait(&app, id).await;
+        assert_eq!(job["state"], "failed");
+        assert!(
+            job["error"].as_str().unwrap().contains("no prover here"),
+            "{job}"
+        );
+        let (status, _) = request(&app, "GET", &format!("/receipt/{id}"), "").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        fs::remove_dir_all(storage).unwrap();
+    }
+
+    #[tokio::test]
+    async fn evicts_the_oldest_finished_jobs() {
+        let config = ServiceConfig {
+            max_jobs: 1,
+            ..config("evict")
+        };
+        let storage = config.storage.clone();
+        let app = router(Service::start(config, Arc::new(fake_prove)).unwrap());
+
+        let first = submit(&app, SIG_JSON).await;
+        let first = first["id"].as_str().unwrap();
+        wait(&app, first).await;
+        let second = submit(&app, SIG_JSON).await;
+        let second = second["id"].as_str().unwrap();
+        wait(&app, second).await;
+
+        for _ in 0..500 {
+            if !storage.join(first).exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!storage.join(first).exists());
+        let (status, _) = request(&app, "GET", &format!("/jobs/{first}"), "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(wait(&app, second).await["state"], "done");
+        assert!(storage.join(second).join("receipt.bin").exists());
+        fs::remove_dir_all(storage).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restores_jobs_after_a_restart() {
+        let config = config("restore");
+        let storage = config.storage.clone();
+        let app = router(Service::start(config.clone(), Arc::new(fake_prove)).unwrap());
+        let done = submit(&app, SIG_JSON).await;
+        let done = done["id"].as_str().unwrap().to_string();
+        let done_status = wait(&app, &done).await;
+
+        // A job the service stopped while proving, a directory left by a
+        // stop during /prove, and a directory that is not a job's
+        let running = JobStatus {
+            id: "0-running".into(),
+            state: JobState::Running,
+            param_set: "sm3-128s-simple".into(),
+            kind: "succinct".into(),
+            error: None,
+            verified: None,
+            journal_status: None,
+            files: Vec::new(),
+        };
+        fs::create_dir(storage.join(&running.id)).unwrap();
+        fs::write(
+            storage.join(&running.id).join("status.json"),
+            status_json(&running),
+        )
+        .unwrap();
+        fs::create_dir(storage.join("0-orphan")).unwrap();
+        fs::write(storage.join("0-orphan").join("input.json"), SIG_JSON).unwrap();
+        fs::create_dir(storage.join("other")).unwrap();
+
+        let app = router(Service::start(config, Arc::new(fake_prove)).unwrap());
+        let (status, body) = request(&app, "GET", &format!("/jobs/{done}"), "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            done_status
+        );
+        let (status, _) = request(&app, "GET", &format!("/receipt/{done}"), "").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let interrupted = wait(&app, "0-running").await;
+        assert_eq!(interrupted["state"], "failed");
+        assert!(interrupted["error"].as_str().unwrap().contains("stopped"));
+        assert!(!storage.join("0-orphan").exists());
+        assert!(storage.join("other").exists());
+        fs::remove_dir_all(storage).unwrap();
+    }
+}
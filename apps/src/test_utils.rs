@@ -0,0 +1,31 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers shared by the unit tests.
+
+use risc0_zkvm::{sha::Digest, FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+
+/// A receipt of `image_id` whose claim commits `committed`, carrying
+/// `journal`: the same bytes, unless the test forges the journal.
+pub(crate) fn fake_receipt(
+    image_id: impl Into<Digest>,
+    committed: &[u8],
+    journal: &[u8],
+) -> Receipt {
+    let claim = ReceiptClaim::ok(image_id, committed.to_vec());
+    Receipt::new(
+        InnerReceipt::Fake(FakeReceipt::new(claim)),
+        journal.to_vec(),
+    )
+}
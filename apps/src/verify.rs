@@ -0,0 +1,194 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Offline verification of receipts and on-chain seals against the spxVrfy
+//! image ID, i.e. the checks `ISpxVrfy.set` makes, without an Ethereum node.
+
+use std::fmt;
+
+use risc0_zkvm::{
+    sha::{Digest, Digestible},
+    Groth16Receipt, Groth16ReceiptVerifierParameters, InnerReceipt, MaybePruned, Receipt,
+    ReceiptClaim,
+};
+
+/// Selector `encode_seal` gives dev-mode receipts.
+const FAKE_SELECTOR: [u8; 4] = [0xff; 4];
+
+/// Why a receipt or seal was rejected.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The receipt proves a different guest than the spxVrfy of this build
+    WrongImageId { expected: Digest, actual: Digest },
+    /// The journal next to the proof is not the one the proof commits to
+    JournalMismatch { committed: Digest, actual: Digest },
+    /// The seal is shorter than its 4-byte selector
+    SealTooShort(usize),
+    /// The seal was made by a dev-mode prover and proves nothing
+    FakeSeal,
+    /// The seal selector is not the Groth16 verifier of this risc0 version
+    UnsupportedSelector { expected: [u8; 4], actual: [u8; 4] },
+    /// The seal does not prove the spxVrfy image ID with this journal; for a
+    /// bare seal this is either a wrong image ID or a changed journal
+    SealMismatch(String),
+    /// The proof itself does not verify
+    Invalid(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::WrongImageId { expected, actual } => write!(
+                f,
+                "wrong image ID: the receipt proves image {actual}, but this build's spxVrfy image ID is {expected}"
+            ),
+            VerifyError::JournalMismatch { committed, actual } => write!(
+                f,
+                "journal mismatch: the proof commits to a journal with digest {committed}, but the journal given has digest {actual}"
+            ),
+            VerifyError::SealTooShort(len) => {
+                write!(f, "seal is {len} bytes, too short to hold a 4-byte selector")
+            }
+            VerifyError::FakeSeal => f.write_str(
+                "bad seal selector: 0xffffffff marks a dev-mode seal, which no verifier accepts unless RISC0_DEV_MODE is set",
+            ),
+            VerifyError::UnsupportedSelector { expected, actual } => write!(
+                f,
+                "bad seal selector: 0x{} is not the Groth16 verifier selector 0x{} of this build",
+                hex::encode(actual),
+                hex::encode(expected)
+            ),
+            VerifyError::SealMismatch(err) => write!(
+                f,
+                "seal does not prove the spxVrfy image ID with this journal (the journal was changed or the seal belongs to another image): {err}"
+            ),
+            VerifyError::Invalid(err) => write!(f, "proof does not verify: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Verifies `receipt` against `image_id`, naming the part that does not
+/// match before checking the proof itself.
+pub fn verify_receipt(receipt: &Receipt, image_id: impl Into<Digest>) -> Result<(), VerifyError> {
+    let expected = image_id.into();
+
+    // Pruned claims can't be compared field by field; the integrity check
+    // below still catches them.
+    if let Ok(MaybePruned::Value(claim)) = receipt.claim() {
+        let actual = claim.pre.digest();
+        if actual != expected {
+            return Err(VerifyError::WrongImageId { expected, actual });
+        }
+        if let MaybePruned::Value(Some(output)) = &claim.output {
+            let committed = output.journal.digest();
+            let actual = receipt.journal.digest();
+            if committed != actual {
+                return Err(VerifyError::JournalMismatch { committed, actual });
+            }
+        }
+    }
+
+    receipt
+        .verify(expected)
+        .map_err(|err| VerifyError::Invalid(err.to_string()))
+}
+
+/// Verifies a `seal.bin` written by `encode_seal` for `journal`, as the
+/// on-chain RiscZeroVerifierRouter would.
+pub fn verify_seal(
+    seal: &[u8],
+    journal: &[u8],
+    image_id: impl Into<Digest>,
+) -> Result<(), VerifyError> {
+    let Some((selector, proof)) = seal.split_first_chunk::<4>() else {
+        return Err(VerifyError::SealTooShort(seal.len()));
+    };
+    if *selector == FAKE_SELECTOR {
+        return Err(VerifyError::FakeSeal);
+    }
+    let verifier_parameters = Groth16ReceiptVerifierParameters::default().digest();
+    let expected: [u8; 4] = verifier_parameters.as_bytes()[..4].try_into().unwrap();
+    if *selector != expected {
+        return Err(VerifyError::UnsupportedSelector {
+            expected,
+            actual: *selector,
+        });
+    }
+
+    let image_id = image_id.into();
+    let claim = ReceiptClaim::ok(image_id, journal.to_vec());
+    let inner = InnerReceipt::Groth16(Groth16Receipt::new(
+        proof.to_vec(),
+        MaybePruned::Value(claim),
+        verifier_parameters,
+    ));
+    Receipt::new(inner, journal.to_vec())
+        .verify(image_id)
+        .map_err(|err| VerifyError::SealMismatch(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fake_receipt;
+
+    const IMAGE_ID: [u32; 8] = [7; 8];
+
+    #[test]
+    fn explains_receipt_failures() {
+        let receipt = fake_receipt([8; 8], b"journal", b"journal");
+        assert!(matches!(
+            verify_receipt(&receipt, IMAGE_ID),
+            Err(VerifyError::WrongImageId { .. })
+        ));
+
+        let receipt = fake_receipt(IMAGE_ID, b"journal", b"forged");
+        assert!(matches!(
+            verify_receipt(&receipt, IMAGE_ID),
+            Err(VerifyError::JournalMismatch { .. })
+        ));
+
+        // Fake receipts only verify in dev mode
+        let receipt = fake_receipt(IMAGE_ID, b"journal", b"journal");
+        assert!(matches!(
+            verify_receipt(&receipt, IMAGE_ID),
+            Err(VerifyError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn explains_seal_failures() {
+        assert!(matches!(
+            verify_seal(&[0x9f, 0x39], b"journal", IMAGE_ID),
+            Err(VerifyError::SealTooShort(2))
+        ));
+        assert!(matches!(
+            verify_seal(&[0xff; 36], b"journal", IMAGE_ID),
+            Err(VerifyError::FakeSeal)
+        ));
+        assert!(matches!(
+            verify_seal(&[0x12; 260], b"journal", IMAGE_ID),
+            Err(VerifyError::UnsupportedSelector { .. })
+        ));
+
+        let verifier_parameters = Groth16ReceiptVerifierParameters::default().digest();
+        let seal = [&verifier_parameters.as_bytes()[..4], &[0u8; 256]].concat();
+        assert!(matches!(
+            verify_seal(&seal, b"journal", IMAGE_ID),
+            Err(VerifyError::SealMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `ThresholdWallet` and `ThresholdWalletFactory` bindings, and the checks
+//! made before a wallet transaction is proven or executed.
+
+use alloy::{
+    contract::{CallBuilder, CallDecoder, Error as ContractError},
+    eips::BlockNumberOrTag,
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+};
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolValue;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use risc0_zkvm::sha::{Impl, Sha256};
+use spx_host::VerificationJournal;
+
+use crate::publish::{self, TxArgs};
+
+// `IThresholdWallet` and `IThresholdWalletFactory` interfaces automatically
+// generated via the alloy `sol!` macro.
+alloy::sol!(
+    #[sol(rpc, all_derives)]
+    "../contracts/IThresholdWallet.sol"
+);
+
+pub use IThresholdWallet::{IThresholdWalletErrors, Transaction, TransactionStatus};
+
+/// Describes a revert of a wallet call, naming the `ThresholdWallet` error
+/// when the revert data is one.
+pub fn explain(err: ContractError) -> anyhow::Error {
+    use IThresholdWalletErrors as E;
+
+    let Some(decoded) = err.as_decoded_interface_error::<IThresholdWalletErrors>() else {
+        return anyhow!(err);
+    };
+    anyhow!(match decoded {
+        E::ZKProofVerificationFailed(_) => {
+            "the proof was rejected, or its journal is not a verified signature by the wallet's TSS public key"
+        }
+        E::TransactionDoesNotExist(_) => "the transaction does not exist or is no longer pending",
+        E::TransactionAlreadyExecuted(_) => "the transaction was already executed",
+        E::TransactionTimedOut(_) => "the transaction timed out, submit it again",
+        E::TransactionFailed(_) => "the call made by the transaction failed",
+        E::InsufficientGasReserve(_) => {
+            "the wallet balance does not cover the value plus MIN_GAS_RESERVE"
+        }
+        E::InvalidGasEstimation(_) => "invalid gas estimation",
+        E::OnlyOwner(_) => "only the wallet owner can do this",
+        E::TssPublicKeyNotSet(_) => "the wallet has no TSS public key, set one first",
+        E::MessageMismatch(_) => {
+            "the signed message is not the transactionMessage() of this transaction"
+        }
+        E::JournalAlreadyUsed(_) => "the journal was already used to execute a transaction",
+    })
+}
+
+/// Sends a wallet call with [`publish::send_checked`], explaining its
+/// reverts with [`explain`].
+pub async fn send<P: Provider, Q: Provider + Clone, D: CallDecoder + Clone>(
+    provider: &P,
+    sender: Address,
+    call: CallBuilder<(), Q, D>,
+    tx: &TxArgs,
+) -> Result<Option<TransactionReceipt>> {
+    publish::send_checked(provider, sender, call, tx, explain).await
+}
+
+/// Returns the transaction of `wallet` at `nonce`, failing unless it is
+/// pending and has not timed out.
+pub async fn pending_transaction<P: Provider>(
+    provider: &P,
+    wallet: Address,
+    nonce: U256,
+) -> Result<Transaction> {
+    let contract = IThresholdWallet::new(wallet, provider);
+    let txn = contract.getTransaction(nonce).call().await?._0;
+    match txn.status {
+        TransactionStatus::Pending => {}
+        TransactionStatus::Nonexistent => bail!("wallet {wallet} has no transaction {nonce}"),
+        TransactionStatus::Executed => bail!("transaction {nonce} was already executed"),
+        TransactionStatus::Cancelled => bail!("transaction {nonce} was cancelled"),
+        _ => bail!("transaction {nonce} has an unknown status"),
+    }
+
+    let timeout = contract.TRANSACTION_TIMEOUT().call().await?._0;
+    let now = latest_timestamp(provider).await?;
+    ensure!(
+        now <= txn.timestamp + timeout,
+        "transaction {nonce} timed out at {}, submit it again",
+        txn.timestamp + timeout
+    );
+    Ok(txn)
+}
+
+/// The message the TSS key signs to execute `txn`, transaction `nonce` of
+/// `wallet` on chain `chain_id`: `transactionMessage(nonce)` of the
+/// contract, `abi.encode(chainid, wallet, nonce, to, value, data)`.
+pub fn transaction_message(
+    chain_id: u64,
+    wallet: Address,
+    nonce: U256,
+    txn: &Transaction,
+) -> Vec<u8> {
+    (
+        U256::from(chain_id),
+        wallet,
+        nonce,
+        txn.to,
+        txn.value,
+        txn.data.clone(),
+    )
+        .abi_encode_params()
+}
+
+/// [`transaction_message`] of the pending transaction `txn` on the chain of
+/// `provider`.
+pub async fn expected_message<P: Provider>(
+    provider: &P,
+    wallet: Address,
+    nonce: U256,
+    txn: &Transaction,
+) -> Result<Vec<u8>> {
+    let chain_id = provider.get_chain_id().await?;
+    Ok(transaction_message(chain_id, wallet, nonce, txn))
+}
+
+/// Checks that `journal` is a verified signature of `message` by the TSS
+/// public key of `wallet`, which `executeTransaction` requires.
+pub async fn check_journal<P: Provider>(
+    provider: &P,
+    wallet: Address,
+    journal: &VerificationJournal,
+    message: &[u8],
+) -> Result<()> {
+    let contract = IThresholdWallet::new(wallet, provider);
+    let tss_public_key = contract.tssPublicKey().call().await?._0;
+    ensure!(
+        !tss_public_key.is_empty(),
+        "wallet {wallet} has no TSS public key, set one first"
+    );
+    ensure!(
+        journal.public_key == tss_public_key[..],
+        "the signature is by public key 0x{}, but the wallet's TSS public key is {tss_public_key}",
+        hex::encode(&journal.public_key)
+    );
+    ensure!(
+        journal.verified,
+        "the journal does not prove a verified signature: {}",
+        journal.status
+    );
+    ensure!(
+        journal.message_hash[..] == *Impl::hash_bytes(message).as_bytes(),
+        "the signed message is not the transaction's, 0x{}",
+        hex::encode(message)
+    );
+    Ok(())
+}
+
+/// Timestamp of the latest block, what a transaction sent now is checked
+/// against.
+pub async fn latest_timestamp<P: Provider>(provider: &P) -> Result<U256> {
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .context("the node returned no latest block")?;
+    Ok(U256::from(block.header.timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_transaction_messages_like_abi_encode() {
+        let txn = Transaction {
+            to: Address::repeat_byte(0x11),
+            value: U256::from(5),
+            data: vec![0xab, 0xcd].into(),
+            gas: U256::from(21000),
+            timestamp: U256::from(1),
+            status: TransactionStatus::Pending,
+        };
+        let wallet = Address::repeat_byte(0x22);
+        let message = transaction_message(31337, wallet, U256::from(3), &txn);
+
+        // Six head words, the offset of data pointing past them, then its
+        // length and its padded bytes
+        let word = |i: usize| U256::from_be_slice(&message[32 * i..32 * (i + 1)]);
+        assert_eq!(message.len(), 32 * 8);
+        assert_eq!(word(0), U256::from(31337));
+        assert_eq!(message[32 + 12..64], wallet[..]);
+        assert_eq!(word(2), U256::from(3));
+        assert_eq!(message[3 * 32 + 12..4 * 32], txn.to[..]);
+        assert_eq!(word(4), U256::from(5));
+        assert_eq!(word(5), U256::from(6 * 32));
+        assert_eq!(word(6), U256::from(2));
+        assert_eq!(message[7 * 32..7 * 32 + 2], [0xab, 0xcd]);
+
+        // Each field is part of the message
+        let other = Transaction {
+            value: U256::from(6),
+            ..txn.clone()
+        };
+        assert_ne!(
+            transaction_message(31337, wallet, U256::from(3), &other),
+            message
+        );
+        assert_ne!(transaction_message(1, wallet, U256::from(3), &txn), message);
+    }
+}
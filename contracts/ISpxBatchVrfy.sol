@@ -0,0 +1,44 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+pragma solidity ^0.8.20;
+
+/// @notice One signature of the batch passed to the spxBatchVrfy guest.
+struct SpxBatchItem {
+    bytes publicKey;
+    bytes signature;
+    bytes message;
+}
+
+/// @notice Journal committed by the spxBatchVrfy guest.
+/// @dev Bit `i % 8` of `results[i / 8]` is set when item `i` verified.
+///      `root` is a SHA-256 Merkle root over one leaf per item,
+///      `sha256(abi.encodePacked(sha256(bytes(paramSet)), sha256(publicKey), sha256(message), verified))`,
+///      with parents `sha256(abi.encodePacked(left, right))`. A node left without a
+///      sibling at the end of a level moves up unchanged, and an empty batch has a zero root.
+///      `status` only reports whether the batch could be checked, not whether its signatures
+///      verified: it is `SPX_STATUS_VERIFIED` (0) of ISpxVrfy.sol once the batch decoded and every
+///      item was checked, even when `verifiedCount < count`. Which items verified is in `results`
+///      and `verifiedCount` alone. A batch that could not be checked has `SPX_STATUS_MALFORMED_INPUT`
+///      or `SPX_STATUS_UNKNOWN_PARAM_SET`, no items and a zero root.
+struct SpxBatchJournal {
+    string paramSet;
+    uint32 count;
+    uint32 verifiedCount;
+    bytes results;
+    bytes32 root;
+    uint8 status;
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/**
+ * @title 市场接口
+ * @notice Interfaces of `spxCoin`, `spxNFT` and `spxMarketplace`, also used by apps to generate their Rust bindings.
+ */
+interface ISpxCoin {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    function balanceOf(address account) external view returns (uint256);
+
+    function allowance(address owner, address spender) external view returns (uint256);
+
+    function approve(address spender, uint256 value) external returns (bool);
+
+    function transfer(address to, uint256 value) external returns (bool);
+}
+
+interface ISpxNFT {
+    event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+    event ApprovalForAll(address indexed owner, address indexed operator, bool approved);
+
+    function nextTokenId() external view returns (uint256);
+
+    function ownerOf(uint256 tokenId) external view returns (address);
+
+    function isApprovedForAll(address owner, address operator) external view returns (bool);
+
+    function setApprovalForAll(address operator, bool approved) external;
+
+    function mint(address to) external;
+}
+
+interface ISpxMarketplace {
+    event NFTBought(uint256 indexed nftId, address indexed buyer, uint256 price);
+
+    error OwnableUnauthorizedAccount(address account);
+
+    function owner() external view returns (address);
+
+    function coin() external view returns (address);
+
+    function nft() external view returns (address);
+
+    function tssPublicKey() external view returns (bytes memory);
+
+    function nftPrices(uint256 nftId) external view returns (uint256);
+
+    function buyNFT(uint256 nftId, bytes memory sig) external;
+
+    function setPrice(uint256 nftId, uint256 price) external;
+
+    function setTSSPublicKey(bytes memory publicKey) external;
+
+    function getSignatureResult(bytes memory sig) external view returns (bool);
+}
@@ -16,14 +16,63 @@
 
 pragma solidity ^0.8.20;
 
+/// @notice Version of `SpxInput` understood by the spxVrfy guest.
+uint8 constant SPX_INPUT_VERSION = 1;
+
+/// @notice Input of the spxVrfy guest, passed as `abi.encode(SpxInput)`.
+struct SpxInput {
+    uint8 version;
+    string paramSet;
+    bytes publicKey;
+    bytes signature;
+    bytes message;
+}
+
+/// @notice Values of `SpxJournal.status`. Only `SPX_STATUS_VERIFIED` comes with `verified == true`.
+uint8 constant SPX_STATUS_VERIFIED = 0;
+/// @notice The input was well formed but the signature does not verify.
+uint8 constant SPX_STATUS_INVALID_SIGNATURE = 1;
+/// @notice The guest input is not a valid `abi.encode(SpxInput)`.
+uint8 constant SPX_STATUS_MALFORMED_INPUT = 2;
+/// @notice `SpxInput.version` is not `SPX_INPUT_VERSION`.
+uint8 constant SPX_STATUS_UNSUPPORTED_VERSION = 3;
+/// @notice `SpxInput.paramSet` names no known parameter set.
+uint8 constant SPX_STATUS_UNKNOWN_PARAM_SET = 4;
+/// @notice The public key or signature length does not match the parameter set.
+uint8 constant SPX_STATUS_BAD_LENGTH = 5;
+
+/// @notice Journal committed by the spxVrfy guest.
+/// @dev `messageHash` is the SHA-256 of the signed message and `paramSet` names the
+///      SPHINCS+ instance used, e.g. `sm3-128s-simple`. `status` is one of the
+///      `SPX_STATUS_*` codes, so invalid input is proven instead of aborting the guest.
+struct SpxJournal {
+    bytes publicKey;
+    bytes32 messageHash;
+    string paramSet;
+    bool verified;
+    uint8 status;
+}
+
 /// @title A starter application using RISC Zero.
 /// @notice This basic application holds a number, guaranteed to be even.
 /// @dev This contract demonstrates one pattern for offloading the computation of an expensive
 ///      or difficult to implement function to a RISC Zero guest running on the zkVM.
 interface ISpxVrfy {
-    /// @notice Set the even number stored on the contract. Requires a RISC Zero proof that the number is even.
-    function set(bytes calldata seal) external;
+    /// @notice Record a verified signature. Requires a RISC Zero proof whose journal is `journal`.
+    function set(bytes calldata journal, bytes calldata seal) external;
 
     /// @notice Returns the number stored.
     function get() external view returns (bool);
+
+    /// @notice Returns 1 if the last `set` proved a verified signature, 2 if its proof or
+    ///         signature was rejected, and 0 before the first `set`.
+    /// @dev `set` does not revert on a seal the verifier rejects, so this is how a sender
+    ///      learns that its proof was not accepted.
+    function getCheck() external view returns (uint128);
+
+    /// @notice Image ID of the spxVrfy guest whose proofs `set` accepts.
+    function imageId() external view returns (bytes32);
+
+    /// @notice RISC Zero verifier `set` checks the seal with.
+    function verifier() external view returns (address);
 }
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+/**
+ * @title 钱包接口
+ * @notice Interface of `ThresholdWallet`, also used by apps to generate its Rust bindings.
+ */
+interface IThresholdWallet {
+    enum TransactionStatus {
+        Nonexistent,
+        Pending,
+        Executed,
+        Cancelled
+    }
+
+    struct Transaction {
+        address to;
+        uint256 value;
+        bytes data;
+        uint256 gas;
+        uint256 timestamp;
+        TransactionStatus status;
+    }
+
+    event TransactionSubmitted(uint256 indexed nonce, address indexed to, uint256 value);
+    event TransactionExecuted(uint256 indexed nonce, address indexed to, uint256 value, bool success);
+    event TransactionCancelled(uint256 indexed nonce);
+    event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+    event TssPublicKeySet(bytes publicKey);
+    event SignatureVerified(bytes32 indexed messageHash);
+
+    error ZKProofVerificationFailed();
+    error TransactionDoesNotExist();
+    error TransactionAlreadyExecuted();
+    error TransactionTimedOut();
+    error TransactionFailed();
+    error InsufficientGasReserve();
+    error InvalidGasEstimation();
+    error OnlyOwner();
+    error TssPublicKeyNotSet();
+    error MessageMismatch();
+    error JournalAlreadyUsed();
+
+    function owner() external view returns (address);
+
+    /// @notice Nonce the next submitted transaction gets.
+    function nonce() external view returns (uint256);
+
+    function tssPublicKey() external view returns (bytes memory);
+
+    function imageId() external view returns (bytes32);
+
+    /// @notice Journals already used to execute a transaction, by sha256.
+    function usedJournals(bytes32 journalHash) external view returns (bool);
+
+    /// @notice Message the TSS key signs to execute transaction `_nonce`:
+    /// `abi.encode(block.chainid, address(this), _nonce, to, value, data)`.
+    function transactionMessage(uint256 _nonce) external view returns (bytes memory);
+
+    /// @notice Whether the proof is valid and proves a verified signature by the TSS public key.
+    function verifySignature(bytes calldata _journal, bytes calldata _seal) external view returns (bool);
+
+    /// @notice Seconds after submission during which a transaction can be executed.
+    function TRANSACTION_TIMEOUT() external view returns (uint256);
+
+    /// @notice Balance the wallet keeps after executing a transaction.
+    function MIN_GAS_RESERVE() external view returns (uint256);
+
+    function submitTransaction(
+        address _to,
+        uint256 _value,
+        bytes calldata _data,
+        uint256 _gas
+    ) external returns (uint256);
+
+    function executeTransaction(
+        uint256 _nonce,
+        bytes calldata _journal,
+        bytes calldata _seal
+    ) external returns (bool);
+
+    function setTssPublicKey(bytes calldata _publicKey) external;
+
+    function estimateTransactionGas(
+        address _to,
+        uint256 _value,
+        bytes calldata _data
+    ) external view returns (uint256);
+
+    function cancelTransaction(uint256 _nonce) external;
+
+    function getTransaction(uint256 _nonce) external view returns (Transaction memory);
+
+    function transferOwnership(address _newOwner) external;
+}
+
+/**
+ * @title 钱包工厂接口
+ * @notice Interface of `ThresholdWalletFactory`.
+ */
+interface IThresholdWalletFactory {
+    event WalletCreated(address indexed wallet, address indexed owner);
+
+    /// @notice Creates a wallet owned by the caller.
+    function createWallet() external returns (address);
+
+    function getUserWallets(address _user) external view returns (address[] memory);
+}
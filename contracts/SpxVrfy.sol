@@ -18,6 +18,7 @@ pragma solidity ^0.8.20;
 
 import {IRiscZeroVerifier} from "risc0/IRiscZeroVerifier.sol";
 import {ImageID} from "./ImageID.sol"; // auto-generated contract after running `cargo build`.
+import {SpxJournal} from "./ISpxVrfy.sol";
 
 /// @title A starter application using RISC Zero.
 /// @notice This basic application holds a number, guaranteed to be even.
@@ -34,6 +35,9 @@ contract SpxVrfy {
     bytes32 public constant imageId = ImageID.SPXVRFY_ID;
     bool public isValidZKProof;
     uint128 public check;
+    /// @notice Public key and message hash taken from the last proven journal.
+    bytes public publicKey;
+    bytes32 public messageHash;
 
     /// @notice A number that is guaranteed, by the RISC Zero zkVM, to be even.
     ///         It can be set by calling the `set` function.
@@ -45,12 +49,14 @@ contract SpxVrfy {
         check = 0; // 初始化为 0
     }
 
-    function set(bytes calldata seal) public {
-        // journal 只需要匹配 guest 中 commit 的 bool 值（true/false）
-        bytes memory journal = abi.encode(true); // guest commit 的是 `true` 当且仅当验签成功
+    function set(bytes calldata journal, bytes calldata seal) public {
+        // journal 由 guest commit，包含公钥、消息哈希、参数集与验签结果
         try verifier.verify(seal, imageId, sha256(journal)) {
-            isValidZKProof = true;
-            check = 1;
+            SpxJournal memory output = abi.decode(journal, (SpxJournal));
+            isValidZKProof = output.verified;
+            check = output.verified ? 1 : 2;
+            publicKey = output.publicKey;
+            messageHash = output.messageHash;
         } catch {
             isValidZKProof = false;
             check = 2;
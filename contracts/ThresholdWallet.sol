@@ -3,7 +3,7 @@ pragma solidity ^0.8.20;
 
 import {IRiscZeroVerifier} from "risc0/IRiscZeroVerifier.sol";
 import {ImageID} from "./ImageID.sol";
-import {ISpxVrfy} from "./ISpxVrfy.sol";
+import {ISpxVrfy, SpxJournal} from "./ISpxVrfy.sol";
 import {SpxVrfy} from "./SpxVrfy.sol";
 import {ReentrancyGuard} from "@openzeppelin/contracts/utils/ReentrancyGuard.sol";
 
@@ -25,8 +25,8 @@ contract ThresholdWallet is ReentrancyGuard {
     /// @notice 当前交易nonce
     uint256 public nonce;
     
-    /// @notice 存储验证结果
-    bool public isVerified;
+    /// @notice 门限签名公钥，journal 中的公钥必须与之一致
+    bytes public tssPublicKey;
     
     /// @notice 交易超时时间（秒）
     uint256 public constant TRANSACTION_TIMEOUT = 1 hours;
@@ -54,6 +54,9 @@ contract ThresholdWallet is ReentrancyGuard {
     
     /// @notice 存储所有交易的映射 (nonce => Transaction)
     mapping(uint256 => Transaction) public transactions;
+
+    /// @notice 已用于执行交易的 journal（按 sha256），每个 journal 只能用一次
+    mapping(bytes32 => bool) public usedJournals;
     
 
     event WalletCreated(address indexed owner);
@@ -61,7 +64,8 @@ contract ThresholdWallet is ReentrancyGuard {
     event TransactionExecuted(uint256 indexed nonce, address indexed to, uint256 value, bool success);
     event TransactionCancelled(uint256 indexed nonce);
     event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
-    event VerificationResult(bool success);
+    event TssPublicKeySet(bytes publicKey);
+    event SignatureVerified(bytes32 indexed messageHash);
     
 
     error ZKProofVerificationFailed();
@@ -72,6 +76,9 @@ contract ThresholdWallet is ReentrancyGuard {
     error InsufficientGasReserve();
     error InvalidGasEstimation();
     error OnlyOwner();
+    error TssPublicKeyNotSet();
+    error MessageMismatch();
+    error JournalAlreadyUsed();
     
     modifier onlyOwner() {
         if (msg.sender != owner) revert OnlyOwner();
@@ -86,7 +93,6 @@ contract ThresholdWallet is ReentrancyGuard {
         verifier = _verifier;
         owner = msg.sender;
         nonce = 0;
-        isVerified = false;
         
         emit WalletCreated(owner);
     }
@@ -122,22 +128,39 @@ contract ThresholdWallet is ReentrancyGuard {
     }
     
     /**
-     * @notice 验证签名
+     * @notice 设置门限签名公钥
+     * @param _publicKey SPHINCS+ 公钥
+     */
+    function setTssPublicKey(bytes calldata _publicKey) external onlyOwner {
+        tssPublicKey = _publicKey;
+
+        emit TssPublicKeySet(_publicKey);
+    }
+
+    /**
+     * @notice 门限签名者为执行交易 `_nonce` 需要签名的消息
+     * @dev abi.encode(chainid, 钱包地址, nonce, to, value, data)，journal 中的 messageHash 必须是它的 sha256
+     * @param _nonce 交易nonce
+     * @return 待签名的消息
+     */
+    function transactionMessage(uint256 _nonce) public view returns (bytes memory) {
+        Transaction storage txn = transactions[_nonce];
+        return abi.encode(block.chainid, address(this), _nonce, txn.to, txn.value, txn.data);
+    }
+
+    /**
+     * @notice 验证签名，不改变状态
+     * @param _journal guest commit 的 journal
      * @param _seal ZK证明seal
-     * @return 验证是否成功
+     * @return 证明有效、验签成功且签名者为本钱包的门限公钥
      */
-    function verifySignature(bytes calldata _seal) public returns (bool) {
-        // 期望 journal 为 true，这表示签名验证成功
-        bytes memory journal = abi.encode(true); 
-        bytes32 journalHash = sha256(journal);
-        
-        try verifier.verify(_seal, imageId, journalHash) {
-            isVerified = true;
-            emit VerificationResult(true);
-            return true;
+    function verifySignature(bytes calldata _journal, bytes calldata _seal) public view returns (bool) {
+        if (tssPublicKey.length == 0) revert TssPublicKeyNotSet();
+
+        try verifier.verify(_seal, imageId, sha256(_journal)) {
+            SpxJournal memory output = abi.decode(_journal, (SpxJournal));
+            return output.verified && keccak256(output.publicKey) == keccak256(tssPublicKey);
         } catch {
-            isVerified = false;
-            emit VerificationResult(false);
             return false;
         }
     }
@@ -145,11 +168,13 @@ contract ThresholdWallet is ReentrancyGuard {
     /**
      * @notice 执行交易，需要通过ZK证明验证
      * @param _nonce 待执行交易的nonce
+     * @param _journal guest commit 的 journal
      * @param _seal ZK证明seal
      * @return success 交易执行是否成功
      */
     function executeTransaction(
         uint256 _nonce,
+        bytes calldata _journal,
         bytes calldata _seal
     ) external onlyOwner nonReentrant returns (bool success) {
         Transaction storage txn = transactions[_nonce];
@@ -163,9 +188,21 @@ contract ThresholdWallet is ReentrancyGuard {
         }
         
         // 验证ZK证明
-        if (!verifySignature(_seal)) {
+        if (!verifySignature(_journal, _seal)) {
             revert ZKProofVerificationFailed();
         }
+
+        // 签名的消息必须是这笔交易，且 journal 不能重放
+        SpxJournal memory output = abi.decode(_journal, (SpxJournal));
+        if (output.messageHash != sha256(transactionMessage(_nonce))) {
+            revert MessageMismatch();
+        }
+        bytes32 journalHash = sha256(_journal);
+        if (usedJournals[journalHash]) {
+            revert JournalAlreadyUsed();
+        }
+        usedJournals[journalHash] = true;
+        emit SignatureVerified(output.messageHash);
         
         // 确保钱包在交易后保留最小gas
         if (address(this).balance < txn.value + MIN_GAS_RESERVE) {
@@ -37,6 +37,8 @@ contract ThresholdWalletFactory {
      */
     function createWallet() external returns (address) {
         ThresholdWallet wallet = new ThresholdWallet(verifier);
+        // 构造函数把工厂设为 owner，需转给调用者，否则钱包无法提交交易
+        wallet.transferOwnership(msg.sender);
         
         userWallets[msg.sender].push(address(wallet));
         
@@ -54,48 +56,3 @@ contract ThresholdWalletFactory {
         return userWallets[_user];
     }
 }
-
-/**
- * @title 钱包接口
- */
-interface IThresholdWallet {
-    enum TransactionStatus {
-        Nonexistent,
-        Pending,
-        Executed,
-        Cancelled
-    }
-    
-    struct Transaction {
-        address to;
-        uint256 value;
-        bytes data;
-        uint256 gas;
-        uint256 timestamp;
-        TransactionStatus status;
-    }
-    
-    function submitTransaction(
-        address _to,
-        uint256 _value,
-        bytes calldata _data,
-        uint256 _gas
-    ) external returns (uint256);
-    
-    function executeTransaction(
-        uint256 _nonce,
-        bytes calldata _seal
-    ) external returns (bool);
-    
-    function estimateTransactionGas(
-        address _to,
-        uint256 _value,
-        bytes calldata _data
-    ) external view returns (uint256);
-    
-    function cancelTransaction(uint256 _nonce) external;
-    
-    function getTransaction(uint256 _nonce) external view returns (Transaction memory);
-    
-    function transferOwnership(address _newOwner) external;
-} 
\ No newline at end of file
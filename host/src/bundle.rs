@@ -0,0 +1,145 @@
+//! Signatures together with the key and message they sign.
+
+use std::{fs, path::Path};
+
+use alloy_primitives::hex;
+use anyhow::{bail, ensure, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::param_sizes;
+
+/// Signature file written by the SPX TSS signer, see [sig.json](../../sig.json).
+///
+/// The frontend backend writes the same shape to `signature_result_*.json`,
+/// with `pk` and `Sig` left empty when signing failed.
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct Sm3Signature {
+    pub mlen: u32,
+    pub pk: String,  // Base64 编码的公钥
+    pub Sig: String, // Base64 编码的签名
+}
+
+impl Sm3Signature {
+    /// Decodes the key and the signed message `Sig = sig ‖ msg`, either
+    /// base64 or `0x`-prefixed hex.
+    ///
+    /// With a `param_set`, `mlen` is checked against the size of its
+    /// signatures, so a wrong `mlen` is reported as such rather than as a
+    /// signature of the wrong size.
+    pub fn bundle(&self, param_set: Option<&str>) -> Result<SignatureBundle> {
+        ensure!(
+            !self.pk.is_empty() || !self.Sig.is_empty(),
+            "pk and Sig are empty, the signer failed to sign"
+        );
+        let pk = decode_field(&self.pk).context("decoding pk")?;
+        let sm = decode_field(&self.Sig).context("decoding Sig")?;
+        ensure!(!pk.is_empty(), "pk is empty");
+        let mlen = self.mlen as usize;
+        if let Some(param_set) = param_set {
+            check_mlen(sm.len(), mlen, param_set)?;
+        }
+        SignatureBundle::from_signed_message(pk, &sm, mlen)
+    }
+}
+
+impl From<&SignatureBundle> for Sm3Signature {
+    /// Encodes a bundle in the sig.json format, base64 with `Sig = sig ‖ msg`.
+    fn from(bundle: &SignatureBundle) -> Self {
+        let sm = [bundle.signature.as_slice(), &bundle.message].concat();
+        Self {
+            mlen: u32::try_from(bundle.message.len()).expect("message longer than 4 GiB"),
+            pk: general_purpose::STANDARD.encode(&bundle.public_key),
+            Sig: general_purpose::STANDARD.encode(sm),
+        }
+    }
+}
+
+fn decode_field(field: &str) -> Result<Vec<u8>> {
+    match field.strip_prefix("0x") {
+        Some(hex) => Ok(hex::decode(hex)?),
+        None => Ok(general_purpose::STANDARD.decode(field)?),
+    }
+}
+
+/// Checks that a signed message of `sm_len` bytes is a `param_set` signature
+/// followed by an `mlen`-byte message. Unknown parameter sets are left to
+/// [`GuestInput::validate`](crate::GuestInput::validate).
+pub fn check_mlen(sm_len: usize, mlen: usize, param_set: &str) -> Result<()> {
+    let Some(sizes) = param_sizes(param_set) else {
+        return Ok(());
+    };
+    if sm_len < sizes.signature {
+        bail!(
+            "the signed message is {sm_len} bytes, shorter than a {param_set} signature of {} bytes",
+            sizes.signature
+        );
+    }
+    ensure!(
+        sm_len - sizes.signature == mlen,
+        "mlen is {mlen}, but the {sm_len}-byte signed message holds a {param_set} signature of {} bytes and a {}-byte message",
+        sizes.signature,
+        sm_len - sizes.signature
+    );
+    Ok(())
+}
+
+/// A detached signature, the public key it verifies under and the signed
+/// message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureBundle {
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub message: Vec<u8>,
+}
+
+impl SignatureBundle {
+    /// Splits the signed message `sm = sig ‖ msg`, whose last `mlen` bytes
+    /// are the message.
+    pub fn from_signed_message(public_key: Vec<u8>, sm: &[u8], mlen: usize) -> Result<Self> {
+        ensure!(
+            mlen <= sm.len(),
+            "mlen {mlen} exceeds signed message length {}",
+            sm.len()
+        );
+        let (signature, message) = sm.split_at(sm.len() - mlen);
+        Ok(Self {
+            public_key,
+            signature: signature.to_vec(),
+            message: message.to_vec(),
+        })
+    }
+
+    /// Parses a signature file in the sig.json format.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let sig_: Sm3Signature = serde_json::from_str(json)?;
+        sig_.bundle(None)
+    }
+
+    /// Reads the signature file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read(path.as_ref(), None)
+    }
+
+    /// Reads the signature file at `path`, checking its `mlen` against the
+    /// signature size of `param_set`.
+    pub fn load_for(path: impl AsRef<Path>, param_set: &str) -> Result<Self> {
+        Self::read(path.as_ref(), Some(param_set))
+    }
+
+    fn read(path: &Path, param_set: Option<&str>) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str::<Sm3Signature>(&json)
+            .map_err(anyhow::Error::from)
+            .and_then(|sig_| sig_.bundle(param_set))
+            .with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// SHA-256 of the message, as committed to the journal.
+    pub fn message_hash(&self) -> [u8; 32] {
+        Sha256::digest(&self.message).into()
+    }
+}
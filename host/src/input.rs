@@ -0,0 +1,78 @@
+//! Input of the spxVrfy guest.
+
+use alloy_sol_types::SolType;
+use anyhow::{bail, ensure, Context, Result};
+
+use crate::{param_sizes, SignatureBundle, SpxInput, SPX_INPUT_VERSION};
+
+/// What the host writes to the spxVrfy guest: `abi.encode(SpxInput)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuestInput {
+    /// Layout version, [`SPX_INPUT_VERSION`] unless testing the guest's
+    /// handling of other versions
+    pub version: u8,
+    pub param_set: String,
+    pub bundle: SignatureBundle,
+}
+
+impl GuestInput {
+    pub fn new(param_set: impl Into<String>, bundle: SignatureBundle) -> Self {
+        Self {
+            version: SPX_INPUT_VERSION,
+            param_set: param_set.into(),
+            bundle,
+        }
+    }
+
+    /// Checks everything the guest checks before verifying, so a bad input is
+    /// reported before spending time on a proof of its rejection.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.version == SPX_INPUT_VERSION,
+            "input version {} is not the supported version {SPX_INPUT_VERSION}",
+            self.version
+        );
+        let Some(sizes) = param_sizes(&self.param_set) else {
+            bail!("unknown parameter set {}", self.param_set);
+        };
+        ensure!(
+            self.bundle.public_key.len() == sizes.public_key,
+            "public key is {} bytes, {} expects {}",
+            self.bundle.public_key.len(),
+            self.param_set,
+            sizes.public_key
+        );
+        ensure!(
+            self.bundle.signature.len() == sizes.signature,
+            "signature is {} bytes, {} expects {}",
+            self.bundle.signature.len(),
+            self.param_set,
+            sizes.signature
+        );
+        Ok(())
+    }
+
+    /// ABI-encodes the input, without validating it.
+    pub fn encode(&self) -> Vec<u8> {
+        SpxInput::abi_encode(&SpxInput {
+            version: self.version,
+            paramSet: self.param_set.clone(),
+            publicKey: self.bundle.public_key.clone().into(),
+            signature: self.bundle.signature.clone().into(),
+            message: self.bundle.message.clone().into(),
+        })
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let input = SpxInput::abi_decode(bytes, true).context("decoding SpxInput")?;
+        Ok(Self {
+            version: input.version,
+            param_set: input.paramSet,
+            bundle: SignatureBundle {
+                public_key: input.publicKey.into(),
+                signature: input.signature.into(),
+                message: input.message.into(),
+            },
+        })
+    }
+}
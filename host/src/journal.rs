@@ -0,0 +1,104 @@
+//! Journal committed by the spxVrfy guest.
+
+use std::fmt;
+
+use alloy_primitives::B256;
+use alloy_sol_types::SolType;
+use anyhow::{anyhow, ensure, Context, Result};
+
+use crate::{SignatureBundle, SpxJournal};
+
+/// `SpxJournal.status`, see the `SPX_STATUS_*` constants of
+/// contracts/ISpxVrfy.sol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Status {
+    Verified = 0,
+    InvalidSignature = 1,
+    MalformedInput = 2,
+    UnsupportedVersion = 3,
+    UnknownParamSet = 4,
+    BadLength = 5,
+}
+
+impl Status {
+    pub const ALL: [Status; 6] = [
+        Status::Verified,
+        Status::InvalidSignature,
+        Status::MalformedInput,
+        Status::UnsupportedVersion,
+        Status::UnknownParamSet,
+        Status::BadLength,
+    ];
+}
+
+impl TryFrom<u8> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        Self::ALL
+            .get(code as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown journal status {code}"))
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Verified => "verified",
+            Status::InvalidSignature => "invalid signature",
+            Status::MalformedInput => "malformed input",
+            Status::UnsupportedVersion => "unsupported input version",
+            Status::UnknownParamSet => "unknown parameter set",
+            Status::BadLength => "bad public key or signature length",
+        })
+    }
+}
+
+/// What the spxVrfy guest commits: `abi.encode(SpxJournal)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationJournal {
+    pub public_key: Vec<u8>,
+    /// SHA-256 of the message
+    pub message_hash: [u8; 32],
+    pub param_set: String,
+    pub verified: bool,
+    pub status: Status,
+}
+
+impl VerificationJournal {
+    pub fn encode(&self) -> Vec<u8> {
+        SpxJournal::abi_encode(&SpxJournal {
+            publicKey: self.public_key.clone().into(),
+            messageHash: B256::from(self.message_hash),
+            paramSet: self.param_set.clone(),
+            verified: self.verified,
+            status: self.status as u8,
+        })
+    }
+
+    /// Decodes a journal, failing on an unknown status or on a `verified`
+    /// flag that contradicts the status.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let journal = SpxJournal::abi_decode(bytes, true).context("decoding SpxJournal")?;
+        let status = Status::try_from(journal.status)?;
+        ensure!(
+            journal.verified == (status == Status::Verified),
+            "journal says verified = {} with status {status}",
+            journal.verified
+        );
+        Ok(Self {
+            public_key: journal.publicKey.into(),
+            message_hash: journal.messageHash.0,
+            param_set: journal.paramSet,
+            verified: journal.verified,
+            status,
+        })
+    }
+
+    /// Whether the journal is about `bundle`'s public key and message.
+    pub fn matches(&self, bundle: &SignatureBundle) -> bool {
+        self.public_key == bundle.public_key && self.message_hash == bundle.message_hash()
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host-side wire format of the spxVrfy guest.
+//!
+//! Every host tool and test goes through these types instead of packing the
+//! ABI structs by hand:
+//!
+//! ```no_run
+//! use spx_host::{GuestInput, SignatureBundle, VerificationJournal};
+//!
+//! let bundle = SignatureBundle::load("sig.json")?;
+//! let input = GuestInput::new("sm3-128s-simple", bundle.clone());
+//! input.validate()?;
+//! // The bytes to hand the guest, e.g. with `ExecutorEnv::builder().write_slice`
+//! let guest_input: Vec<u8> = input.encode();
+//!
+//! // The journal `spx-zk prove` wrote for that input
+//! let journal = VerificationJournal::decode(&std::fs::read("ZKbin/journal.bin")?)?;
+//! assert!(journal.verified && journal.matches(&bundle));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+mod bundle;
+mod input;
+mod journal;
+mod params;
+
+pub use bundle::{check_mlen, SignatureBundle, Sm3Signature};
+pub use input::GuestInput;
+pub use journal::{Status, VerificationJournal};
+pub use params::{param_sizes, ParamSizes};
+
+alloy_sol_types::sol!(
+    #[sol(all_derives)]
+    "../contracts/ISpxVrfy.sol"
+);
+
+/// `SPX_INPUT_VERSION` of contracts/ISpxVrfy.sol, which `sol!` does not export.
+pub const SPX_INPUT_VERSION: u8 = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_sol_types::SolType;
+
+    const SIG_JSON: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../sig.json");
+
+    #[test]
+    fn loads_sig_json() {
+        let bundle = SignatureBundle::load(SIG_JSON).unwrap();
+        assert_eq!(bundle.message, b"helloworld\0");
+
+        let input = GuestInput::new("sm3-128s-simple", bundle);
+        input.validate().unwrap();
+        assert_eq!(GuestInput::decode(&input.encode()).unwrap(), input);
+    }
+
+    #[test]
+    fn rejects_bad_inputs() {
+        let bundle = SignatureBundle::load(SIG_JSON).unwrap();
+
+        let mut input = GuestInput::new("sm3-128s-fast", bundle.clone());
+        assert!(input.validate().is_err());
+        input.param_set = "sm3-128f-simple".into();
+        assert!(input.validate().is_err(), "128s signature under 128f");
+
+        let mut input = GuestInput::new("sm3-128s-simple", bundle);
+        input.bundle.public_key.pop();
+        assert!(input.validate().is_err());
+
+        assert!(GuestInput::decode(&input.encode()[1..]).is_err());
+        assert!(SignatureBundle::from_signed_message(vec![], &[0; 4], 5).is_err());
+    }
+
+    #[test]
+    fn checks_sig_json_fields() {
+        let json = std::fs::read_to_string(SIG_JSON).unwrap();
+        let sig: Sm3Signature = serde_json::from_str(&json).unwrap();
+        let bundle = sig.bundle(Some("sm3-128s-simple")).unwrap();
+        assert_eq!(bundle, SignatureBundle::from_json(&json).unwrap());
+        let (sig_mlen, sig_pk) = (sig.mlen, sig.pk.clone());
+
+        // mlen must leave a whole signature in front of the message
+        let wrong = Sm3Signature { mlen: 10, ..sig };
+        let err = wrong.bundle(Some("sm3-128s-simple")).unwrap_err();
+        assert!(err.to_string().contains("mlen is 10"), "{err}");
+        assert!(
+            wrong.bundle(None).is_ok(),
+            "no parameter set to check against"
+        );
+
+        // Hex fields decode to the same bundle
+        let sm = [bundle.signature.as_slice(), &bundle.message].concat();
+        let hex = Sm3Signature {
+            mlen: 11,
+            pk: format!("0x{}", alloy_primitives::hex::encode(&bundle.public_key)),
+            Sig: format!("0x{}", alloy_primitives::hex::encode(sm)),
+        };
+        assert_eq!(hex.bundle(Some("sm3-128s-simple")).unwrap(), bundle);
+
+        // Writing a bundle gives back the sig.json fields
+        let written = Sm3Signature::from(&bundle);
+        assert_eq!((written.mlen, &written.pk), (sig_mlen, &sig_pk));
+        assert_eq!(written.bundle(Some("sm3-128s-simple")).unwrap(), bundle);
+
+        // What the frontend backend writes when signing fails
+        let failed = r#"{ "mlen": 8, "pk": "", "Sig": "" }"#;
+        let err = SignatureBundle::from_json(failed).unwrap_err();
+        assert!(err.to_string().contains("failed to sign"), "{err}");
+    }
+
+    #[test]
+    fn journal_round_trips() {
+        let bundle = SignatureBundle::load(SIG_JSON).unwrap();
+        let journal = VerificationJournal {
+            public_key: bundle.public_key.clone(),
+            message_hash: bundle.message_hash(),
+            param_set: "sm3-128s-simple".into(),
+            verified: true,
+            status: Status::Verified,
+        };
+        let decoded = VerificationJournal::decode(&journal.encode()).unwrap();
+        assert_eq!(decoded, journal);
+        assert!(decoded.matches(&bundle));
+
+        // verified must agree with the status
+        let mut forged = SpxJournal::abi_decode(&journal.encode(), true).unwrap();
+        forged.status = Status::InvalidSignature as u8;
+        assert!(VerificationJournal::decode(&SpxJournal::abi_encode(&forged)).is_err());
+        forged.status = 9;
+        assert!(VerificationJournal::decode(&SpxJournal::abi_encode(&forged)).is_err());
+    }
+}
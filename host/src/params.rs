@@ -0,0 +1,44 @@
+//! Key and signature sizes of the parameter sets the spxVrfy guest accepts.
+//!
+//! The sizes come from `spx_sm3::ParamSet` with the hash functions the guest
+//! enables, so they cannot drift from what the guest checks.
+
+use spx_sm3::ParamSet;
+
+/// Public key and signature sizes of a parameter set, in bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParamSizes {
+    pub public_key: usize,
+    pub signature: usize,
+}
+
+/// Returns the sizes of the parameter set called `name`, e.g.
+/// `sm3-128s-simple` or `slh-dsa-shake-256f`, or `None` if the guest does not
+/// know the name.
+pub fn param_sizes(name: &str) -> Option<ParamSizes> {
+    let params = ParamSet::from_name(name)?;
+    Some(ParamSizes {
+        public_key: params.public_key_bytes(),
+        signature: params.signature_bytes(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knows_guest_param_sets() {
+        let sizes = param_sizes("sm3-128s-simple").unwrap();
+        assert_eq!(sizes.public_key, 32);
+        assert_eq!(sizes.signature, 7_856);
+
+        assert_eq!(param_sizes("slh-dsa-shake-256f").unwrap().public_key, 64);
+        assert_eq!(param_sizes("sha2-192f-robust").unwrap().signature, 35_664);
+
+        assert_eq!(param_sizes("sm3-128s-fast"), None);
+        assert_eq!(param_sizes("slh-dsa-sm3-128s"), None);
+        assert_eq!(param_sizes("md5-128s-simple"), None);
+        assert_eq!(param_sizes("sm3-128x-simple"), None);
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Guest cycle counts for verifying one signature of the 128s and 128f
+//! parameter sets.
+//!
+//! Each set verifies the first vector of its known-answer file in
+//! `guest/src/lib/spx_module/tests/KAT` with the executor, so no proof is
+//! generated.
+//!
+//! ```text
+//! cargo bench -p methods --bench cycles
+//! ```
+//!
+//! To compare two versions of the guest, save the counts of the first one and
+//! point the second run at the same file:
+//!
+//! ```text
+//! SPX_CYCLES_SAVE=1 SPX_CYCLES_BASELINE=/tmp/spx-cycles.txt cargo bench -p methods --bench cycles
+//! # check out the other version
+//! SPX_CYCLES_BASELINE=/tmp/spx-cycles.txt cargo bench -p methods --bench cycles
+//! ```
+//!
+//! The saved counts record the `SPX_INPUT_VERSION` they were taken with, and
+//! a baseline of another input layout, or one without that header, is ignored
+//! with a warning: the guest cycles include decoding the input.
+
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+use methods::SPXVRFY_ELF;
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use spx_host::{GuestInput, SignatureBundle, SPX_INPUT_VERSION};
+
+const PARAM_SETS: [&str; 4] = [
+    "sm3-128s-simple",
+    "sm3-128f-simple",
+    "sha2-128s-simple",
+    "sha2-128f-simple",
+];
+
+const KAT_DIR: &str = "guest/src/lib/spx_module/tests/KAT";
+
+struct Count {
+    cycles: u64,
+    segments: usize,
+}
+
+/// Returns the guest input for the first record in the `.rsp` file of
+/// `param_set`, splitting `sm` into the signature and the trailing `mlen`
+/// message bytes.
+fn first_vector(param_set: &str) -> GuestInput {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join(KAT_DIR)
+        .join(format!("sphincs-{param_set}.rsp"));
+    let rsp = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+
+    let value = |key: &str| {
+        rsp.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(" = "))
+            .unwrap_or_else(|| panic!("{}: missing {key}", path.display()))
+    };
+    let field = |key: &str| hex::decode(value(key)).unwrap();
+
+    let mlen: usize = value("mlen").parse().unwrap();
+    let bundle = SignatureBundle::from_signed_message(field("pk"), &field("sm"), mlen).unwrap();
+    GuestInput::new(param_set, bundle)
+}
+
+fn execute(param_set: &str) -> Count {
+    let input = first_vector(param_set).encode();
+    let env = ExecutorEnv::builder().write_slice(&input).build().unwrap();
+    let session_info = default_executor().execute(env, SPXVRFY_ELF).unwrap();
+
+    Count {
+        cycles: session_info.cycles(),
+        segments: session_info.segments.len(),
+    }
+}
+
+/// Header of a saved baseline, naming the input layout it was taken with.
+fn baseline_header() -> String {
+    format!("input-version {SPX_INPUT_VERSION}")
+}
+
+/// Reads the `<param set> <cycles>` lines written by an earlier run with the
+/// same input layout.
+fn load_baseline(path: &str) -> BTreeMap<String, u64> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("warning: ignoring baseline {path}: {e}");
+            return BTreeMap::new();
+        }
+    };
+    let mut lines = text.lines();
+    let expected = baseline_header();
+    match lines.next() {
+        Some(header) if header == expected => {}
+        Some(header) if header.starts_with("input-version ") => {
+            eprintln!(
+                "warning: ignoring baseline {path}: it was saved with `{header}`, \
+                 this guest reads `{expected}`"
+            );
+            return BTreeMap::new();
+        }
+        _ => {
+            eprintln!(
+                "warning: ignoring baseline {path}: it has no `input-version` header, \
+                 so the input layout it was taken with is unknown; save it again with \
+                 SPX_CYCLES_SAVE=1"
+            );
+            return BTreeMap::new();
+        }
+    }
+    lines
+        .filter_map(|line| {
+            let (name, cycles) = line.split_once(' ')?;
+            Some((name.to_string(), cycles.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+fn main() {
+    let baseline_path = env::var("SPX_CYCLES_BASELINE").ok();
+    let save = env::var_os("SPX_CYCLES_SAVE").is_some();
+    let baseline = match &baseline_path {
+        Some(path) if !save => load_baseline(path),
+        _ => BTreeMap::new(),
+    };
+
+    println!(
+        "{:<18} {:>12} {:>9} {:>12} {:>8}",
+        "param set", "cycles", "segments", "baseline", "change"
+    );
+
+    let mut saved = baseline_header() + "\n";
+    for param_set in PARAM_SETS {
+        let count = execute(param_set);
+        saved.push_str(&format!("{param_set} {}\n", count.cycles));
+
+        let (before, change) = match baseline.get(param_set) {
+            Some(&before) => (
+                before.to_string(),
+                format!(
+                    "{:+.1}%",
+                    (count.cycles as f64 / before as f64 - 1.0) * 100.0
+                ),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<18} {:>12} {:>9} {:>12} {:>8}",
+            param_set, count.cycles, count.segments, before, change
+        );
+    }
+
+    if let (true, Some(path)) = (save, &baseline_path) {
+        fs::write(path, saved).unwrap();
+        println!("saved cycle counts to {path}");
+    }
+}
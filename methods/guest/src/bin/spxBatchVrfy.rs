@@ -0,0 +1,130 @@
+use alloy_primitives::B256;
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::{Impl, Sha256};
+use spx_sm3::*;
+use std::io::Read;
+use alloy_sol_types::{SolType, SolValue, sol};
+
+sol! {
+    /// 与 contracts/ISpxBatchVrfy.sol 中的定义保持一致
+    struct SpxBatchItem {
+        bytes publicKey;
+        bytes signature;
+        bytes message;
+    }
+
+    struct SpxBatchJournal {
+        string paramSet;
+        uint32 count;
+        uint32 verifiedCount;
+        bytes results;
+        bytes32 root;
+        uint8 status;
+    }
+}
+
+// journal 的 status 取值，与 contracts/ISpxVrfy.sol 中的 SPX_STATUS_* 一致。
+// status 只说明整批是否解码并逐条验过，VERIFIED 不代表每条签名都通过，
+// 逐条结果只看 results 与 verifiedCount
+const SPX_STATUS_VERIFIED: u8 = 0;
+const SPX_STATUS_MALFORMED_INPUT: u8 = 2;
+const SPX_STATUS_UNKNOWN_PARAM_SET: u8 = 4;
+
+/// 叶子 = SHA-256(SHA-256(paramSet) ‖ SHA-256(pk) ‖ SHA-256(msg) ‖ verified)，
+/// 叶子里带上参数集，同一对公钥与消息在不同参数集下的结果不会混淆
+fn leaf(param_set: &[u8; 32], item: &SpxBatchItem, verified: bool) -> [u8; 32] {
+    let mut preimage = [0u8; 97];
+    preimage[..32].copy_from_slice(param_set);
+    preimage[32..64].copy_from_slice(Impl::hash_bytes(&item.publicKey).as_bytes());
+    preimage[64..96].copy_from_slice(Impl::hash_bytes(&item.message).as_bytes());
+    preimage[96] = verified as u8;
+    Impl::hash_bytes(&preimage).as_bytes().try_into().unwrap()
+}
+
+/// 整批无法验证时的 journal：没有条目，根为全零，status 说明原因
+fn failed(param_set: String, status: u8) -> SpxBatchJournal {
+    SpxBatchJournal {
+        paramSet: param_set,
+        count: 0,
+        verifiedCount: 0,
+        results: Default::default(),
+        root: B256::ZERO,
+        status,
+    }
+}
+
+/// 父节点 = SHA-256(左 ‖ 右)，每层末尾落单的节点原样上移；空批次的根为全零
+fn merkle_root(mut level: Vec<[u8; 32]>) -> [u8; 32] {
+    if level.is_empty() {
+        return [0u8; 32];
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut node = [0u8; 64];
+                    node[..32].copy_from_slice(left);
+                    node[32..].copy_from_slice(right);
+                    Impl::hash_bytes(&node).as_bytes().try_into().unwrap()
+                }
+                [odd] => *odd,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// 逐个验签并建 Merkle 树；输入错误以状态码写进 journal，而不是让 guest 中止
+fn verify_batch(input_bytes: &[u8]) -> SpxBatchJournal {
+    // 解析 abi.encode(string paramSet, SpxBatchItem[] items) 编码的内容
+    let Ok((param_set, items)) =
+        <sol! { (string, SpxBatchItem[]) }>::abi_decode_params(input_bytes, true)
+    else {
+        return failed(String::new(), SPX_STATUS_MALFORMED_INPUT);
+    };
+    let Some(params) = ParamSet::from_name(&param_set) else {
+        return failed(param_set, SPX_STATUS_UNKNOWN_PARAM_SET);
+    };
+    let param_set_hash: [u8; 32] = Impl::hash_bytes(params.name().as_bytes())
+        .as_bytes()
+        .try_into()
+        .unwrap();
+
+    // 逐个验签，第 i 个签名的结果记在 results 第 i / 8 字节的第 i % 8 位
+    let mut results = vec![0u8; items.len().div_ceil(8)];
+    let mut leaves = Vec::with_capacity(items.len());
+    let mut verified_count = 0u32;
+    for (i, item) in items.iter().enumerate() {
+        // 长度不对的条目验签直接返回错误，记为失败，不会中断整批证明
+        let verified = if params.is_slh_dsa() {
+            slh_verify(params, &item.signature, &item.message, &[], &item.publicKey).is_ok()
+        } else {
+            vrfy(params, &item.signature, &item.message, &item.publicKey).is_ok()
+        };
+        if verified {
+            results[i / 8] |= 1 << (i % 8);
+            verified_count += 1;
+        }
+        leaves.push(leaf(&param_set_hash, item, verified));
+    }
+
+    // 链上只需保存 root，再用 Merkle 证明逐条确认是谁签了什么
+    SpxBatchJournal {
+        paramSet: params.name().into(),
+        count: items.len() as u32,
+        verifiedCount: verified_count,
+        results: results.into(),
+        root: B256::from(merkle_root(leaves)),
+        status: SPX_STATUS_VERIFIED,
+    }
+}
+
+fn main() {
+    let mut input_bytes = Vec::<u8>::new();
+    env::stdin().read_to_end(&mut input_bytes).unwrap();
+
+    let journal = verify_batch(&input_bytes);
+    env::commit_slice(journal.abi_encode().as_slice());
+}
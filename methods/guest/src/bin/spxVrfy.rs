@@ -1,37 +1,105 @@
-use alloy_primitives::FixedBytes;
+use alloy_primitives::B256;
 use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::{Impl, Sha256};
 use spx_sm3::*;
+use std::fmt::Display;
 use std::io::Read;
-use alloy_sol_types::{SolType,sol};
+use alloy_sol_types::{SolValue, sol};
 
-type BoolSol = sol! { bool };
+sol! {
+    /// 与 contracts/ISpxVrfy.sol 中的 SpxInput 保持一致
+    struct SpxInput {
+        uint8 version;
+        string paramSet;
+        bytes publicKey;
+        bytes signature;
+        bytes message;
+    }
 
-fn main() {
-    let mut input_bytes = Vec::<u8>::new();
-    env::stdin().read_to_end(&mut input_bytes).unwrap();
+    /// 与 contracts/ISpxVrfy.sol 中的 SpxJournal 保持一致
+    struct SpxJournal {
+        bytes publicKey;
+        bytes32 messageHash;
+        string paramSet;
+        bool verified;
+        uint8 status;
+    }
+}
 
-    // 解析 abi.encode(bytes) 编码的内容
-    let received = <sol! { bytes }>::abi_decode(&input_bytes, true).unwrap();
+const SPX_INPUT_VERSION: u8 = 1;
 
-    let pk_bytes: Vec<u8> = received[..CRYPTO_PUBLICKEYBYTES].to_vec();
-    let sm_bytes: Vec<u8> = received[CRYPTO_PUBLICKEYBYTES..].to_vec();
+// journal 的 status 取值，与 contracts/ISpxVrfy.sol 中的 SPX_STATUS_* 一致
+const SPX_STATUS_VERIFIED: u8 = 0;
+const SPX_STATUS_INVALID_SIGNATURE: u8 = 1;
+const SPX_STATUS_MALFORMED_INPUT: u8 = 2;
+const SPX_STATUS_UNSUPPORTED_VERSION: u8 = 3;
+const SPX_STATUS_UNKNOWN_PARAM_SET: u8 = 4;
+const SPX_STATUS_BAD_LENGTH: u8 = 5;
 
-    let sig: FixedBytes<CRYPTO_BYTES> = sm_bytes[..CRYPTO_BYTES]
-        .try_into()
-        .expect("Signature length mismatch");
+/// 设置了 SPX_PROFILE 时，在每个阶段开始处向 stderr 写 `spx-phase <阶段> <已用周期>`，
+/// 供 `spx-zk execute --profile` 统计各阶段的周期数；journal 不受影响
+fn mark(profile: bool, phase: impl Display) {
+    if profile {
+        eprintln!("spx-phase {phase} {}", env::cycle_count());
+    }
+}
 
-    let msg = &sm_bytes[CRYPTO_BYTES..];
-    
-    let public: FixedBytes<CRYPTO_PUBLICKEYBYTES> = pk_bytes[..]
-        .try_into()
-        .expect("Public key length mismatch");
+/// 校验输入并验签；任何输入错误都以状态码写进 journal，而不是让 guest 中止
+fn check(input: &SpxInput, profile: bool) -> (String, u8) {
+    if input.version != SPX_INPUT_VERSION {
+        return (input.paramSet.clone(), SPX_STATUS_UNSUPPORTED_VERSION);
+    }
+    let Some(params) = ParamSet::from_name(&input.paramSet) else {
+        return (input.paramSet.clone(), SPX_STATUS_UNKNOWN_PARAM_SET);
+    };
+    let name = params.name().to_string();
 
-    let result = vrfy(sig.as_slice(), msg, public.as_slice());
+    // SLH-DSA 参数集按 FIPS 205 的纯签名接口验证（空上下文串）
+    let trace = &mut |phase: Phase| mark(profile, phase);
+    let result = if params.is_slh_dsa() {
+        slh_verify_traced(params, &input.signature, &input.message, &[], &input.publicKey, trace)
+    } else {
+        vrfy_traced(params, &input.signature, &input.message, &input.publicKey, trace)
+    };
+    // 公钥、签名长度不对时验签在哈希之前就返回
+    match result {
+        Ok(()) => (name, SPX_STATUS_VERIFIED),
+        Err(SigError::BadPublicKeyLength { .. } | SigError::BadSignatureLength { .. }) => {
+            (name, SPX_STATUS_BAD_LENGTH)
+        }
+        Err(_) => (name, SPX_STATUS_INVALID_SIGNATURE),
+    }
+}
 
-    let res = BoolSol::abi_encode(&result.is_ok());
-    env::commit_slice(res.as_slice());
+fn main() {
+    let profile = std::env::var_os("SPX_PROFILE").is_some();
+    mark(profile, "read_input");
+    let mut input_bytes = Vec::<u8>::new();
+    env::stdin().read_to_end(&mut input_bytes).unwrap();
 
-    // for dev test
-    // let res = BoolSol::abi_encode(&true);
-    // env::commit_slice(res.as_slice());
+    // 解析 abi.encode(SpxInput) 编码的内容
+    mark(profile, "decode_input");
+    let journal = match SpxInput::abi_decode(&input_bytes, true) {
+        Ok(input) => {
+            let (param_set, status) = check(&input, profile);
+            // 公开被验证的公钥与消息摘要，链上合约据此确认是谁签了什么
+            SpxJournal {
+                publicKey: input.publicKey.clone(),
+                messageHash: B256::from_slice(Impl::hash_bytes(&input.message).as_bytes()),
+                paramSet: param_set,
+                verified: status == SPX_STATUS_VERIFIED,
+                status,
+            }
+        }
+        Err(_) => SpxJournal {
+            publicKey: Default::default(),
+            messageHash: B256::ZERO,
+            paramSet: String::new(),
+            verified: false,
+            status: SPX_STATUS_MALFORMED_INPUT,
+        },
+    };
+    mark(profile, "commit_journal");
+    env::commit_slice(journal.abi_encode().as_slice());
+    mark(profile, "end");
 }
@@ -1,31 +1,425 @@
-use crate::params::{CRYPTO_PUBLICKEYBYTES, CRYPTO_SECRETKEYBYTES};
+use crate::params::{with_params, ParamSet, SpxParams};
 use crate::sign::*;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use rand_core::CryptoRngCore;
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Keypair {
-    pub public: [u8; CRYPTO_PUBLICKEYBYTES],
-    pub secret: [u8; CRYPTO_SECRETKEYBYTES],
+    pub params: ParamSet,
+    pub public: Vec<u8>,
+    pub secret: Vec<u8>,
 }
 
+/// Reason a key, signature or message was rejected
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SigError {
-    Input,
-    Verify,
+    /// The seed is not `seed_bytes()` long for the parameter set
+    BadSeedLength { expected: usize, actual: usize },
+    /// The public key is not `public_key_bytes()` long for the parameter set
+    BadPublicKeyLength { expected: usize, actual: usize },
+    /// The secret key is not `secret_key_bytes()` long for the parameter set
+    BadSecretKeyLength { expected: usize, actual: usize },
+    /// The signature is not `signature_bytes()` long for the parameter set
+    BadSignatureLength { expected: usize, actual: usize },
+    /// The SLH-DSA context string is longer than 255 bytes
+    BadContextLength { actual: usize },
+    /// The root recomputed from the signature differs from the one in the
+    /// public key, i.e. the signature does not match the message and key
+    RootMismatch,
+    /// The operation is not defined for this parameter set, e.g. SLH-DSA
+    /// signing with a SPHINCS+ key
+    UnsupportedParams(ParamSet),
+}
+
+impl fmt::Display for SigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigError::BadSeedLength { expected, actual } => {
+                write!(f, "seed is {actual} bytes, expected {expected}")
+            }
+            SigError::BadPublicKeyLength { expected, actual } => {
+                write!(f, "public key is {actual} bytes, expected {expected}")
+            }
+            SigError::BadSecretKeyLength { expected, actual } => {
+                write!(f, "secret key is {actual} bytes, expected {expected}")
+            }
+            SigError::BadSignatureLength { expected, actual } => {
+                write!(f, "signature is {actual} bytes, expected {expected}")
+            }
+            SigError::BadContextLength { actual } => {
+                write!(f, "context string is {actual} bytes, at most 255 allowed")
+            }
+            SigError::RootMismatch => {
+                f.write_str("root computed from the signature does not match the public key")
+            }
+            SigError::UnsupportedParams(params) => {
+                write!(
+                    f,
+                    "operation not supported by parameter set {}",
+                    params.name()
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for SigError {}
+
+/// Step of a verification, reported by [`vrfy_traced`] as it starts, e.g. to
+/// count the zkVM cycles each step takes
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Hashing the message into the FORS digest and the hypertree leaf index
+    HashMessage,
+    /// Recomputing the FORS public key from the FORS signature
+    Fors,
+    /// Recomputing the WOTS public key and the subtree root of hypertree
+    /// layer `i`, counted from the bottom
+    Layer(usize),
+    /// All hashing is done and the root is compared with the public key
+    Done,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::HashMessage => f.write_str("hash_message"),
+            Phase::Fors => f.write_str("fors"),
+            Phase::Layer(i) => write!(f, "layer_{i}"),
+            Phase::Done => f.write_str("done"),
+        }
+    }
+}
+
+/// Generates a keypair for `params` from a fresh seed drawn from `rng`
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// let keys = keypair(ParamSet::Sm3_128sSimple, &mut rand::thread_rng());
+/// ```
+pub fn keypair(params: ParamSet, rng: &mut dyn CryptoRngCore) -> Keypair {
+    let mut seed = vec![0u8; params.seed_bytes()];
+    rng.fill_bytes(&mut seed);
+    match seed_keypair(params, &seed) {
+        Ok(keys) => keys,
+        Err(_) => unreachable!("seed has the length the parameter set asks for"),
+    }
+}
+
+/// Deterministically derives a keypair from `SK.seed || SK.prf || PK.seed`
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// let params = ParamSet::Sm3_128sSimple;
+/// let keys = seed_keypair(params, &vec![0u8; params.seed_bytes()]);
+/// assert!(keys.is_ok());
+/// ```
+pub fn seed_keypair(params: ParamSet, seed: &[u8]) -> Result<Keypair, SigError> {
+    if seed.len() != params.seed_bytes() {
+        return Err(SigError::BadSeedLength {
+            expected: params.seed_bytes(),
+            actual: seed.len(),
+        });
+    }
+    let mut public = vec![0u8; params.public_key_bytes()];
+    let mut secret = vec![0u8; params.secret_key_bytes()];
+    with_params!(params, P => crypto_sign_seed_keypair::<P>(&mut public, &mut secret, seed));
+    Ok(Keypair {
+        params,
+        public,
+        secret,
+    })
+}
+
+/// Checks that the keys of `keypair` have the lengths of its parameter set
+fn check_keypair(keypair: &Keypair) -> Result<(), SigError> {
+    let params = keypair.params;
+    if keypair.secret.len() != params.secret_key_bytes() {
+        return Err(SigError::BadSecretKeyLength {
+            expected: params.secret_key_bytes(),
+            actual: keypair.secret.len(),
+        });
+    }
+    if keypair.public.len() != params.public_key_bytes() {
+        return Err(SigError::BadPublicKeyLength {
+            expected: params.public_key_bytes(),
+            actual: keypair.public.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Signs a message, using `PK.seed` as the randomizer so the same message
+/// always produces the same signature
+///
+/// Fails with [`SigError::BadSecretKeyLength`] or
+/// [`SigError::BadPublicKeyLength`] if a key of `keypair` does not have the
+/// length of its parameter set.
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// # let keys = keypair(ParamSet::Sm3_128sSimple, &mut rand::thread_rng());
+/// let msg = [0u8; 32];
+/// let sig = sign(&msg, &keys);
+/// assert!(sig.is_ok());
+/// ```
+pub fn sign(msg: &[u8], keypair: &Keypair) -> Result<Vec<u8>, SigError> {
+    check_keypair(keypair)?;
+    let n = keypair.params.public_key_bytes() / 2;
+    sign_with_optrand(msg, keypair, &keypair.public[..n])
+}
+
+/// Signs a message with a fresh randomizer drawn from `rng`
+///
+/// Fails like [`sign`] on keys of the wrong length.
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// # let keys = keypair(ParamSet::Sm3_128sSimple, &mut rand::thread_rng());
+/// let msg = [0u8; 32];
+/// let sig = sign_randomized(&msg, &keys, &mut rand::thread_rng());
+/// assert!(sig.is_ok());
+/// ```
+pub fn sign_randomized(
+    msg: &[u8],
+    keypair: &Keypair,
+    rng: &mut dyn CryptoRngCore,
+) -> Result<Vec<u8>, SigError> {
+    let mut optrand = vec![0u8; keypair.params.public_key_bytes() / 2];
+    rng.fill_bytes(&mut optrand);
+    sign_with_optrand(msg, keypair, &optrand)
+}
+
+pub(crate) fn sign_with_optrand(
+    msg: &[u8],
+    keypair: &Keypair,
+    optrand: &[u8],
+) -> Result<Vec<u8>, SigError> {
+    check_keypair(keypair)?;
+    let mut sig = vec![0u8; keypair.params.signature_bytes()];
+    with_params!(keypair.params, P => {
+        crypto_sign_signature::<P>(&mut sig, msg, &keypair.secret, optrand)
+    });
+    Ok(sig)
 }
 
 /// Verify signature using keypair
 ///
 /// Example:
 /// ```no_run
-/// # use pqc_sphincsplus::*;
-/// # let keys = keypair();
+/// # use spx_sm3::*;
+/// # let keys = keypair(ParamSet::Sm3_128sSimple, &mut rand::thread_rng());
 /// # let msg = [0u8; 32];
-/// # let sig = sign(&msg, &keys);
+/// # let sig = sign(&msg, &keys).ok().unwrap();
 /// let sig_verify = verify(&sig, &msg, &keys);
 /// assert!(sig_verify.is_ok());
 pub fn verify(sig: &[u8], msg: &[u8], keypair: &Keypair) -> Result<(), SigError> {
-    crypto_sign_verify(&sig, &msg, &keypair.public)
+    vrfy(keypair.params, sig, msg, &keypair.public)
+}
+
+/// Verify signature under the public key `pk` of parameter set `params`
+///
+/// Fails with [`SigError::BadPublicKeyLength`] or
+/// [`SigError::BadSignatureLength`] before doing any hashing if either input
+/// has the wrong size, and with [`SigError::RootMismatch`] if the signature
+/// is not valid.
+pub fn vrfy(params: ParamSet, sig: &[u8], msg: &[u8], pk: &[u8]) -> Result<(), SigError> {
+    with_params!(params, P => vrfy_with::<P>(sig, msg, pk))
+}
+
+/// Verify signature with the parameter set fixed at compile time
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// # let keys = keypair(ParamSet::Sm3_128sSimple, &mut rand::thread_rng());
+/// # let msg = [0u8; 32];
+/// # let sig = sign(&msg, &keys).ok().unwrap();
+/// let sig_verify = vrfy_with::<Sm3_128sSimple>(&sig, &msg, &keys.public);
+/// assert!(sig_verify.is_ok());
+pub fn vrfy_with<P: SpxParams>(sig: &[u8], msg: &[u8], pk: &[u8]) -> Result<(), SigError> {
+    crypto_sign_verify::<P>(sig, msg, pk)
+}
+
+/// Same as [`vrfy`], calling `mark` as each [`Phase`] starts
+///
+/// Length errors are returned before any phase starts.
+pub fn vrfy_traced(
+    params: ParamSet,
+    sig: &[u8],
+    msg: &[u8],
+    pk: &[u8],
+    mark: &mut dyn FnMut(Phase),
+) -> Result<(), SigError> {
+    with_params!(params, P => crypto_sign_verify_traced::<P>(sig, msg, pk, mark))
 }
 
-pub fn vrfy(sig: &[u8], msg: &[u8], pk: &[u8]) -> Result<(), SigError> {
-    crypto_sign_verify(&sig, &msg, &pk)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sm3")]
+    use crate::sm3::sm3;
+
+    const MSG: &[u8] = b"helloworld\0";
+
+    fn test_keys(params: ParamSet) -> Keypair {
+        let seed: Vec<u8> = (0..params.seed_bytes() as u8).collect();
+        seed_keypair(params, &seed).ok().unwrap()
+    }
+
+    #[test]
+    fn sign_then_verify() {
+        for &params in ParamSet::ALL {
+            let keys = test_keys(params);
+            let sig = sign(MSG, &keys).ok().unwrap();
+            assert!(verify(&sig, MSG, &keys).is_ok(), "{}", params.name());
+            assert!(vrfy(params, &sig, b"helloworld", &keys.public).is_err());
+
+            let sig = sign_randomized(MSG, &keys, &mut rand::thread_rng())
+                .ok()
+                .unwrap();
+            assert!(vrfy(params, &sig, MSG, &keys.public).is_ok());
+        }
+    }
+
+    #[test]
+    fn reports_why_verification_failed() {
+        let params = *ParamSet::ALL.iter().find(|p| !p.is_slh_dsa()).unwrap();
+        let keys = test_keys(params);
+        let sig = sign(MSG, &keys).ok().unwrap();
+        let (pk_len, sig_len) = (params.public_key_bytes(), params.signature_bytes());
+
+        assert_eq!(
+            vrfy(params, &sig, MSG, &keys.public[1..]),
+            Err(SigError::BadPublicKeyLength {
+                expected: pk_len,
+                actual: pk_len - 1
+            })
+        );
+        assert_eq!(
+            vrfy(params, &sig[1..], MSG, &keys.public),
+            Err(SigError::BadSignatureLength {
+                expected: sig_len,
+                actual: sig_len - 1
+            })
+        );
+        assert_eq!(
+            vrfy(params, &sig, b"helloworld", &keys.public),
+            Err(SigError::RootMismatch)
+        );
+        let mut short = keys.clone();
+        short.secret.pop();
+        assert_eq!(
+            sign(MSG, &short).err(),
+            Some(SigError::BadSecretKeyLength {
+                expected: params.secret_key_bytes(),
+                actual: params.secret_key_bytes() - 1
+            })
+        );
+        let mut short = keys.clone();
+        short.public.truncate(3);
+        assert_eq!(
+            sign_randomized(MSG, &short, &mut rand::thread_rng()).err(),
+            Some(SigError::BadPublicKeyLength {
+                expected: pk_len,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            seed_keypair(params, &[0u8; 3]).err(),
+            Some(SigError::BadSeedLength {
+                expected: params.seed_bytes(),
+                actual: 3
+            })
+        );
+        assert_eq!(
+            crate::slh_verify(params, &sig, MSG, &[], &keys.public),
+            Err(SigError::UnsupportedParams(params))
+        );
+    }
+
+    #[test]
+    fn traces_every_phase() {
+        let params = *ParamSet::ALL.iter().find(|p| !p.is_slh_dsa()).unwrap();
+        let keys = test_keys(params);
+        let sig = sign(MSG, &keys).ok().unwrap();
+        let mut phases = Vec::new();
+        assert!(vrfy_traced(params, &sig, MSG, &keys.public, &mut |p| phases.push(p)).is_ok());
+
+        assert_eq!(phases[..2], [Phase::HashMessage, Phase::Fors]);
+        assert_eq!(phases.last(), Some(&Phase::Done));
+        let layers = &phases[2..phases.len() - 1];
+        assert!(!layers.is_empty());
+        for (i, phase) in layers.iter().enumerate() {
+            assert_eq!(*phase, Phase::Layer(i));
+        }
+
+        phases.clear();
+        let result = vrfy_traced(params, &sig[1..], MSG, &keys.public, &mut |p| {
+            phases.push(p)
+        });
+        assert!(result.is_err());
+        assert!(phases.is_empty());
+    }
+
+    #[test]
+    fn keypair_is_consistent() {
+        let params = ParamSet::ALL[0];
+        let keys = keypair(params, &mut rand::thread_rng());
+        let n = params.public_key_bytes() / 2;
+        assert_eq!(keys.public[..], keys.secret[2 * n..]);
+    }
+
+    #[test]
+    fn param_set_names_round_trip() {
+        for &params in ParamSet::ALL {
+            assert_eq!(ParamSet::from_name(params.name()), Some(params));
+        }
+        assert_eq!(ParamSet::from_name("sm3-128s-fast"), None);
+    }
+
+    // Expected values come from the spxlibAPI C implementation with the
+    // randombytes() output fixed to the given optrand.
+    #[test]
+    #[cfg(feature = "sm3")]
+    fn matches_reference_implementation() {
+        let keys = test_keys(ParamSet::Sm3_128sSimple);
+        let mut digest = [0u8; 32];
+
+        let mut expected = [0u8; 32];
+        expected[..16].copy_from_slice(&keys.secret[32..48]);
+        expected[16..].copy_from_slice(&[
+            0x42, 0xdb, 0x6a, 0x8c, 0x05, 0x75, 0x67, 0x58, 0x4d, 0x8d, 0x39, 0xb8, 0xba, 0x52,
+            0xdb, 0x93,
+        ]);
+        assert_eq!(keys.public, expected);
+
+        let sig = sign(MSG, &keys).ok().unwrap();
+        sm3(&mut digest, &sig, sig.len());
+        assert_eq!(
+            digest,
+            [
+                0x98, 0xeb, 0x2a, 0x19, 0xde, 0xe5, 0xab, 0x56, 0xaa, 0x04, 0x5a, 0x8a, 0x08, 0x2f,
+                0xb3, 0xb9, 0x10, 0x61, 0x33, 0x40, 0x1e, 0x28, 0x66, 0x44, 0xdc, 0x3f, 0x02, 0x5d,
+                0xb6, 0x91, 0x9b, 0xe3,
+            ]
+        );
+
+        let sig = sign_with_optrand(MSG, &keys, &[0xa5; 16]).ok().unwrap();
+        sm3(&mut digest, &sig, sig.len());
+        assert_eq!(
+            digest,
+            [
+                0x6d, 0xb3, 0x9b, 0x80, 0x06, 0x9a, 0xe6, 0xe7, 0x9f, 0x6c, 0x9c, 0xfa, 0x7c, 0x09,
+                0x05, 0x29, 0x70, 0x31, 0xef, 0xf7, 0x5a, 0x4a, 0x9a, 0xb3, 0x57, 0xee, 0xf4, 0xb7,
+                0xe7, 0x39, 0xc8, 0xde,
+            ]
+        );
+    }
 }
@@ -1,20 +1,29 @@
-use crate::params::SPX_N;
+use crate::params::SPX_MAX_N;
 
 /// Sphincs context
+/// Seeds are stored at their largest size; only the first N bytes are used.
 pub struct SpxCtx {
-    pub pub_seed: [u8; SPX_N],
+    pub pub_seed: [u8; SPX_MAX_N],
+    pub sk_seed: [u8; SPX_MAX_N],
 
-    #[cfg(feature = "sm3")]
+    #[cfg(any(feature = "sm3", feature = "sha2"))]
     pub state_seeded: [u8; 40],
+
+    #[cfg(feature = "sha2")]
+    pub state_seeded_512: [u8; 72],
 }
 
 impl Default for SpxCtx {
     fn default() -> Self {
         Self {
-            pub_seed: [0u8; SPX_N],
+            pub_seed: [0u8; SPX_MAX_N],
+            sk_seed: [0u8; SPX_MAX_N],
 
-            #[cfg(feature = "sm3")]
+            #[cfg(any(feature = "sm3", feature = "sha2"))]
             state_seeded: [0u8; 40],
+
+            #[cfg(feature = "sha2")]
+            state_seeded_512: [0u8; 72],
         }
     }
 }
@@ -1,74 +1,167 @@
 use crate::address::*;
 use crate::context::SpxCtx;
+use crate::hash::*;
 use crate::params::*;
 use crate::thash::*;
 use crate::utils::*;
 
-pub fn fors_sk_to_leaf(leaf: &mut [u8], sk: &[u8], ctx: &SpxCtx, fors_leaf_addr: &mut [u32]) {
-    thash::<1>(leaf, Some(sk), ctx, fors_leaf_addr);
+pub fn fors_gen_sk<P: SpxParams>(sk: &mut [u8], ctx: &SpxCtx, fors_leaf_addr: &[u8]) {
+    prf_addr::<P>(sk, ctx, fors_leaf_addr);
 }
 
-/// Interprets m as SPX_FORS_HEIGHT-bit unsigned integers.
-/// Assumes m contains at least SPX_FORS_HEIGHT * SPX_FORS_TREES bits.
-/// Assumes indices has space for SPX_FORS_TREES integers.
-pub fn message_to_indices(indices: &mut [u32], m: &[u8]) {
+pub fn fors_sk_to_leaf<P: SpxParams>(
+    leaf: &mut [u8],
+    sk: &[u8],
+    ctx: &SpxCtx,
+    fors_leaf_addr: &mut [u8],
+) {
+    thash::<P>(leaf, Some(sk), 1, ctx, fors_leaf_addr);
+}
+
+pub fn fors_gen_leaf<P: SpxParams>(
+    leaf: &mut [u8],
+    ctx: &SpxCtx,
+    addr_idx: u32,
+    fors_leaf_addr: &mut [u8],
+) {
+    // Only set the parts that the caller doesn't set
+    set_tree_index::<P>(fors_leaf_addr, addr_idx);
+    set_type::<P>(fors_leaf_addr, SPX_ADDR_TYPE_FORSPRF);
+    fors_gen_sk::<P>(leaf, ctx, fors_leaf_addr);
+
+    set_type::<P>(fors_leaf_addr, SPX_ADDR_TYPE_FORSTREE);
+    thash::<P>(leaf, None, 1, ctx, fors_leaf_addr);
+}
+
+/// Interprets m as FORS_HEIGHT-bit unsigned integers.
+/// Assumes m contains at least FORS_HEIGHT * FORS_TREES bits.
+/// Assumes indices has space for FORS_TREES integers.
+pub fn message_to_indices<P: SpxParams>(indices: &mut [u32], m: &[u8]) {
     let mut offset = 0;
 
-    for i in 0..SPX_FORS_TREES {
+    // FIPS 205 reads the indices most significant bit first (base_2b),
+    // round 3.1 SPHINCS+ least significant bit first.
+    for i in 0..P::FORS_TREES {
         indices[i] = 0;
-        for j in 0..SPX_FORS_HEIGHT {
-            indices[i] ^= (((m[offset >> 3] >> (offset & 0x7)) & 0x1) as u32) << j;
+        for j in 0..P::FORS_HEIGHT {
+            if P::SLH_DSA {
+                indices[i] =
+                    (indices[i] << 1) | ((m[offset >> 3] >> (7 - (offset & 0x7))) & 0x1) as u32;
+            } else {
+                indices[i] ^= (((m[offset >> 3] >> (offset & 0x7)) & 0x1) as u32) << j;
+            }
             offset += 1;
         }
     }
 }
 
+/// Signs a message m, deriving the secret key from sk_seed and the FTS address.
+/// Assumes m contains at least FORS_HEIGHT * FORS_TREES bits.
+pub fn fors_sign<P: SpxParams>(
+    sig: &mut [u8],
+    pk: &mut [u8],
+    m: &[u8],
+    ctx: &SpxCtx,
+    fors_addr: &[u8],
+) {
+    let mut indices = [0u32; SPX_MAX_FORS_TREES];
+    let mut roots = [0u8; SPX_MAX_FORS_TREES * SPX_MAX_N];
+    let mut fors_tree_addr = [0u8; SPX_ADDR_BYTES];
+    let mut fors_leaf_addr = [0u8; SPX_ADDR_BYTES];
+    let mut fors_pk_addr = [0u8; SPX_ADDR_BYTES];
+    let mut idx_offset;
+
+    copy_keypair_addr::<P>(&mut fors_tree_addr, fors_addr);
+    copy_keypair_addr::<P>(&mut fors_leaf_addr, fors_addr);
+
+    copy_keypair_addr::<P>(&mut fors_pk_addr, fors_addr);
+    set_type::<P>(&mut fors_pk_addr, SPX_ADDR_TYPE_FORSPK);
+
+    message_to_indices::<P>(&mut indices, m);
+
+    let mut idx = 0usize;
+    for i in 0..P::FORS_TREES {
+        idx_offset = i as u32 * (1 << P::FORS_HEIGHT as u32);
+
+        set_tree_height::<P>(&mut fors_tree_addr, 0);
+        set_tree_index::<P>(&mut fors_tree_addr, indices[i] + idx_offset);
+        set_type::<P>(&mut fors_tree_addr, SPX_ADDR_TYPE_FORSPRF);
+
+        // Include the secret key part that produces the selected leaf node.
+        fors_gen_sk::<P>(&mut sig[idx..], ctx, &fors_tree_addr);
+        set_type::<P>(&mut fors_tree_addr, SPX_ADDR_TYPE_FORSTREE);
+        idx += P::N;
+
+        // Compute the authentication path for this leaf node.
+        treehash::<P, _>(
+            &mut roots[i * P::N..],
+            &mut sig[idx..],
+            ctx,
+            indices[i],
+            idx_offset,
+            P::FORS_HEIGHT as u32,
+            |leaf, ctx, addr_idx| fors_gen_leaf::<P>(leaf, ctx, addr_idx, &mut fors_leaf_addr),
+            &mut fors_tree_addr,
+        );
+        idx += P::N * P::FORS_HEIGHT;
+    }
+
+    // Hash horizontally across all tree roots to derive the public key.
+    thash::<P>(pk, Some(&roots), P::FORS_TREES, ctx, &fors_pk_addr);
+}
+
 /// Derives the FORS public key from a signature.
 /// This can be used for verification by comparing to a known public key, or to
 /// subsequently verify a signature on the derived public key. The latter is the
 /// typical use-case when used as an FTS below an OTS in a hypertree.
-/// Assumes m contains at least SPX_FORS_HEIGHT * SPX_FORS_TREES bits.
-pub fn fors_pk_from_sig(pk: &mut [u8], sig: &[u8], m: &[u8], ctx: &SpxCtx, fors_addr: &mut [u32]) {
-    let mut indices = [0u32; SPX_FORS_TREES];
-    let mut roots = [0u8; SPX_FORS_TREES * SPX_N];
-    let mut leaf = [0u8; SPX_N];
-    let mut fors_tree_addr = [0u32; 8];
-    let mut fors_pk_addr = [0u32; 8];
+/// Assumes m contains at least FORS_HEIGHT * FORS_TREES bits.
+pub fn fors_pk_from_sig<P: SpxParams>(
+    pk: &mut [u8],
+    sig: &[u8],
+    m: &[u8],
+    ctx: &SpxCtx,
+    fors_addr: &mut [u8],
+) {
+    let mut indices = [0u32; SPX_MAX_FORS_TREES];
+    let mut roots = [0u8; SPX_MAX_FORS_TREES * SPX_MAX_N];
+    let mut leaf = [0u8; SPX_MAX_N];
+    let mut fors_tree_addr = [0u8; SPX_ADDR_BYTES];
+    let mut fors_pk_addr = [0u8; SPX_ADDR_BYTES];
     let mut idx_offset;
 
-    copy_keypair_addr(&mut fors_tree_addr, fors_addr);
-    copy_keypair_addr(&mut fors_pk_addr, fors_addr);
+    copy_keypair_addr::<P>(&mut fors_tree_addr, fors_addr);
+    copy_keypair_addr::<P>(&mut fors_pk_addr, fors_addr);
 
-    set_type(&mut fors_tree_addr, SPX_ADDR_TYPE_FORSTREE);
-    set_type(&mut fors_pk_addr, SPX_ADDR_TYPE_FORSPK);
+    set_type::<P>(&mut fors_tree_addr, SPX_ADDR_TYPE_FORSTREE);
+    set_type::<P>(&mut fors_pk_addr, SPX_ADDR_TYPE_FORSPK);
 
-    message_to_indices(&mut indices, m);
+    message_to_indices::<P>(&mut indices, m);
 
     let mut idx = 0usize;
-    for i in 0..SPX_FORS_TREES {
-        idx_offset = i as u32 * (1 << SPX_FORS_HEIGHT as u32);
+    for i in 0..P::FORS_TREES {
+        idx_offset = i as u32 * (1 << P::FORS_HEIGHT as u32);
 
-        set_tree_height(&mut fors_tree_addr, 0);
-        set_tree_index(&mut fors_tree_addr, indices[i] + idx_offset);
+        set_tree_height::<P>(&mut fors_tree_addr, 0);
+        set_tree_index::<P>(&mut fors_tree_addr, indices[i] + idx_offset);
 
         // Derive the leaf from the included secret key part.
-        fors_sk_to_leaf(&mut leaf, &sig[idx..], ctx, &mut fors_tree_addr);
-        idx += SPX_N;
+        fors_sk_to_leaf::<P>(&mut leaf, &sig[idx..], ctx, &mut fors_tree_addr);
+        idx += P::N;
 
         // Derive the corresponding root node of this tree.
-        compute_root(
-            &mut roots[i * SPX_N..],
+        compute_root::<P>(
+            &mut roots[i * P::N..],
             &leaf,
             indices[i],
             idx_offset,
             &sig[idx..],
-            SPX_FORS_HEIGHT as u32,
+            P::FORS_HEIGHT as u32,
             ctx,
             &mut fors_tree_addr,
         );
-        idx += SPX_N * SPX_FORS_HEIGHT;
+        idx += P::N * P::FORS_HEIGHT;
     }
 
     // Hash horizontally across all tree roots to derive the public key.
-    thash::<SPX_FORS_TREES>(pk, Some(&roots), ctx, &fors_pk_addr);
+    thash::<P>(pk, Some(&roots), P::FORS_TREES, ctx, &fors_pk_addr);
 }
@@ -1,5 +1,63 @@
+#[cfg(feature = "sha2")]
+mod sha2;
+#[cfg(feature = "shake")]
+mod shake;
 #[cfg(feature = "sm3")]
 mod sm3;
 
-#[cfg(feature = "sm3")]
-pub use sm3::*;
+use crate::context::SpxCtx;
+use crate::params::{HashFunction, SpxParams};
+
+/// Calls the implementation of `$f` for the hash function family of `$P`.
+/// `P::HASH` is a constant, so the match folds away after monomorphisation.
+macro_rules! dispatch {
+    ($P:ident, $f:ident($($arg:expr),*)) => {
+        match $P::HASH {
+            #[cfg(feature = "sm3")]
+            HashFunction::Sm3 => sm3::$f::<$P>($($arg),*),
+            #[cfg(feature = "sha2")]
+            HashFunction::Sha2 => sha2::$f::<$P>($($arg),*),
+            #[cfg(feature = "shake")]
+            HashFunction::Shake => shake::$f::<$P>($($arg),*),
+        }
+    };
+}
+
+/// Prepares the context for the hash function, based on the public seed.
+pub fn initialize_hash_function<P: SpxParams>(ctx: &mut SpxCtx) {
+    dispatch!(P, initialize_hash_function(ctx))
+}
+
+/// Computes PRF(pk_seed, sk_seed, addr).
+pub fn prf_addr<P: SpxParams>(out: &mut [u8], ctx: &SpxCtx, addr: &[u8]) {
+    dispatch!(P, prf_addr(out, ctx, addr))
+}
+
+/// Computes the message-dependent randomness R, using a secret seed and an
+/// optional randomization value as well as the message.
+pub fn gen_message_random<P: SpxParams>(
+    r: &mut [u8],
+    sk_prf: &[u8],
+    optrand: &[u8],
+    m: &[u8],
+    mlen: usize,
+    ctx: &SpxCtx,
+) {
+    dispatch!(P, gen_message_random(r, sk_prf, optrand, m, mlen, ctx))
+}
+
+/// Computes the message hash using R, the public key, and the message.
+/// Outputs the message digest and the index of the leaf. The index is split in
+/// the tree index and the leaf index, for convenient copying to an address.
+pub fn hash_message<P: SpxParams>(
+    digest: &mut [u8],
+    tree: &mut u64,
+    leaf_idx: &mut u32,
+    r: &[u8],
+    pk: &[u8],
+    m: &[u8],
+    mlen: usize,
+    ctx: &SpxCtx,
+) {
+    dispatch!(P, hash_message(digest, tree, leaf_idx, r, pk, m, mlen, ctx))
+}
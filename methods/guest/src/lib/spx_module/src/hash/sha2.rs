@@ -0,0 +1,217 @@
+#![allow(non_snake_case)]
+use crate::context::SpxCtx;
+use crate::params::*;
+use crate::sha2::*;
+use crate::utils::*;
+
+// The n = 24 and n = 32 parameter sets use SHA-512 for H_msg and the HMAC,
+// the n = 16 ones SHA-256.
+const SPX_SHAX_MAX_BLOCK_BYTES: usize = SPX_SHA512_BLOCK_BYTES;
+const SPX_SHAX_MAX_OUTPUT_BYTES: usize = SPX_SHA512_OUTPUT_BYTES;
+
+fn shaX_block_bytes<P: SpxParams>() -> usize {
+    if P::N >= 24 {
+        SPX_SHA512_BLOCK_BYTES
+    } else {
+        SPX_SHA256_BLOCK_BYTES
+    }
+}
+
+fn shaX_output_bytes<P: SpxParams>() -> usize {
+    if P::N >= 24 {
+        SPX_SHA512_OUTPUT_BYTES
+    } else {
+        SPX_SHA256_OUTPUT_BYTES
+    }
+}
+
+fn shaX_inc_init<P: SpxParams>(state: &mut [u8]) {
+    if P::N >= 24 {
+        sha512_inc_init(state);
+    } else {
+        sha256_inc_init(state);
+    }
+}
+
+fn shaX_inc_blocks<P: SpxParams>(state: &mut [u8], input: &[u8], inblocks: usize) {
+    if P::N >= 24 {
+        sha512_inc_blocks(state, input, inblocks);
+    } else {
+        sha256_inc_blocks(state, input, inblocks);
+    }
+}
+
+fn shaX_inc_finalize<P: SpxParams>(out: &mut [u8], state: &mut [u8], input: &[u8], inlen: usize) {
+    if P::N >= 24 {
+        sha512_inc_finalize(out, state, input, inlen);
+    } else {
+        sha256_inc_finalize(out, state, input, inlen);
+    }
+}
+
+fn shaX<P: SpxParams>(out: &mut [u8], input: &[u8], inlen: usize) {
+    if P::N >= 24 {
+        sha512(out, input, inlen);
+    } else {
+        sha256(out, input, inlen);
+    }
+}
+
+fn mgf1_X<P: SpxParams>(out: &mut [u8], outlen: usize, input: &[u8], inlen: usize) {
+    if P::N >= 24 {
+        mgf1_512(out, outlen, input, inlen);
+    } else {
+        mgf1_256(out, outlen, input, inlen);
+    }
+}
+
+/// Absorbs pub_seed into the seeded SHA-2 states reused by every thash and
+/// prf_addr call.
+pub fn initialize_hash_function<P: SpxParams>(ctx: &mut SpxCtx) {
+    seed_state::<P>(ctx);
+}
+
+/// Computes PRF(pk_seed, sk_seed, addr).
+pub fn prf_addr<P: SpxParams>(out: &mut [u8], ctx: &SpxCtx, addr: &[u8]) {
+    let mut buf = [0u8; sha256_padded_len(SPX_SHA256_ADDR_BYTES + SPX_MAX_N)];
+    let mut outbuf = [0u8; SPX_SHA256_OUTPUT_BYTES];
+
+    // Retrieve precomputed state containing pub_seed
+    let mut sha2_state = ctx.state_seeded;
+
+    // Remainder: ADDR^c ‖ SK.seed
+    buf[..SPX_SHA256_ADDR_BYTES].copy_from_slice(&addr[..SPX_SHA256_ADDR_BYTES]);
+    buf[SPX_SHA256_ADDR_BYTES..SPX_SHA256_ADDR_BYTES + P::N].copy_from_slice(&ctx.sk_seed[..P::N]);
+
+    sha256_inc_finalize_in_place(
+        &mut outbuf,
+        &mut sha2_state,
+        &mut buf,
+        SPX_SHA256_ADDR_BYTES + P::N,
+    );
+
+    out[..P::N].copy_from_slice(&outbuf[..P::N]);
+}
+
+/// Computes the message-dependent randomness R, using a secret seed as a key
+/// for HMAC, and an optional randomization value prefixed to the message.
+pub fn gen_message_random<P: SpxParams>(
+    R: &mut [u8],
+    sk_prf: &[u8],
+    optrand: &[u8],
+    m: &[u8],
+    mut mlen: usize,
+    _ctx: &SpxCtx,
+) {
+    let block_bytes = shaX_block_bytes::<P>();
+    let output_bytes = shaX_output_bytes::<P>();
+    let mut buf = [0u8; SPX_SHAX_MAX_BLOCK_BYTES + SPX_SHAX_MAX_OUTPUT_BYTES];
+    let mut state = [0u8; 8 + SPX_SHAX_MAX_OUTPUT_BYTES];
+    let mut m_idx = 0;
+
+    // This implements HMAC-SHA
+    for i in 0..P::N {
+        buf[i] = 0x36 ^ sk_prf[i];
+    }
+    buf[P::N..block_bytes].fill(0x36);
+
+    shaX_inc_init::<P>(&mut state);
+    shaX_inc_blocks::<P>(&mut state, &buf, 1);
+
+    buf[..P::N].copy_from_slice(&optrand[..P::N]);
+
+    // If optrand + message cannot fill up an entire block
+    if P::N + mlen < block_bytes {
+        buf[P::N..P::N + mlen].copy_from_slice(&m[..mlen]);
+        let inner = buf;
+        shaX_inc_finalize::<P>(&mut buf[block_bytes..], &mut state, &inner, mlen + P::N);
+    }
+    // Otherwise first fill a block, so that finalize only uses the message
+    else {
+        let end = block_bytes - P::N;
+        buf[P::N..block_bytes].copy_from_slice(&m[..end]);
+        shaX_inc_blocks::<P>(&mut state, &buf, 1);
+
+        m_idx += end;
+        mlen -= end;
+        shaX_inc_finalize::<P>(&mut buf[block_bytes..], &mut state, &m[m_idx..], mlen);
+    }
+
+    for i in 0..P::N {
+        buf[i] = 0x5c ^ sk_prf[i];
+    }
+    buf[P::N..block_bytes].fill(0x5c);
+
+    let outer = buf;
+    shaX::<P>(&mut buf, &outer, block_bytes + output_bytes);
+    R[..P::N].copy_from_slice(&buf[..P::N]);
+}
+
+/// Computes the message hash using R, the public key, and the message.
+/// Outputs the message digest and the index of the leaf. The index is split in
+/// the tree index and the leaf index, for convenient copying to an address.
+pub fn hash_message<P: SpxParams>(
+    digest: &mut [u8],
+    tree: &mut u64,
+    leaf_idx: &mut u32,
+    R: &[u8],
+    pk: &[u8],
+    m: &[u8],
+    mut mlen: usize,
+    _ctx: &SpxCtx,
+) {
+    let block_bytes = shaX_block_bytes::<P>();
+    let output_bytes = shaX_output_bytes::<P>();
+    let mut seed = [0u8; 2 * SPX_MAX_N + SPX_SHAX_MAX_OUTPUT_BYTES];
+
+    // Round to nearest multiple of the SHA-X block size
+    let inblocks = (P::N + P::PK_BYTES).div_ceil(block_bytes);
+
+    let mut inbuf = [0u8; SPX_SHAX_MAX_BLOCK_BYTES];
+
+    let mut buf = [0u8; SPX_MAX_DGST_BYTES];
+    let mut state = [0u8; 8 + SPX_SHAX_MAX_OUTPUT_BYTES];
+    let mut buf_idx = 0;
+    let mut m_idx = 0;
+
+    shaX_inc_init::<P>(&mut state);
+
+    // seed: SHA-X(R ‖ PK.seed ‖ PK.root ‖ M)
+    inbuf[..P::N].copy_from_slice(&R[..P::N]);
+    inbuf[P::N..P::N + P::PK_BYTES].copy_from_slice(&pk[..P::PK_BYTES]);
+
+    // If R + pk + message cannot fill up an entire block
+    let start = P::N + P::PK_BYTES;
+    if start + mlen < inblocks * block_bytes {
+        inbuf[start..start + mlen].copy_from_slice(&m[..mlen]);
+        shaX_inc_finalize::<P>(&mut seed[2 * P::N..], &mut state, &inbuf, start + mlen);
+    }
+    // Otherwise first fill a block, so that finalize only uses the message
+    else {
+        let end = inblocks * block_bytes - start;
+        inbuf[start..start + end].copy_from_slice(&m[..end]);
+        shaX_inc_blocks::<P>(&mut state, &inbuf, inblocks);
+
+        m_idx += end;
+        mlen -= end;
+        shaX_inc_finalize::<P>(&mut seed[2 * P::N..], &mut state, &m[m_idx..], mlen);
+    }
+
+    // H_msg: MGF1-SHA-X(R ‖ PK.seed ‖ seed)
+    seed[..P::N].copy_from_slice(&R[..P::N]);
+    seed[P::N..P::N * 2].copy_from_slice(&pk[..P::N]);
+
+    // By doing this in two steps, we prevent hashing the message twice;
+    // otherwise each iteration in MGF1 would hash the message again.
+    mgf1_X::<P>(&mut buf, P::DGST_BYTES, &seed, 2 * P::N + output_bytes);
+
+    digest[..P::FORS_MSG_BYTES].copy_from_slice(&buf[..P::FORS_MSG_BYTES]);
+    buf_idx += P::FORS_MSG_BYTES;
+
+    *tree = bytes_to_ull(&buf[buf_idx..], P::TREE_BYTES);
+    *tree &= !0u64 >> (64 - P::TREE_BITS);
+    buf_idx += P::TREE_BYTES;
+
+    *leaf_idx = bytes_to_ull(&buf[buf_idx..], P::LEAF_BYTES) as u32;
+    *leaf_idx &= !0u32 >> (32 - P::LEAF_BITS);
+}
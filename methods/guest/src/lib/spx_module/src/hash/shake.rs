@@ -0,0 +1,68 @@
+use crate::context::SpxCtx;
+use crate::params::*;
+use crate::utils::*;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+
+/// For SHAKE256, there is no immediate reason to initialize at the start,
+/// so this function is an empty operation.
+pub fn initialize_hash_function<P: SpxParams>(_ctx: &mut SpxCtx) {}
+
+/// Computes PRF(pk_seed, sk_seed, addr).
+pub fn prf_addr<P: SpxParams>(out: &mut [u8], ctx: &SpxCtx, addr: &[u8]) {
+    let mut hasher = Shake256::default();
+    hasher.update(&ctx.pub_seed[..P::N]);
+    hasher.update(addr);
+    hasher.update(&ctx.sk_seed[..P::N]);
+    hasher.finalize_xof().read(&mut out[..P::N]);
+}
+
+/// Computes the message-dependent randomness R, using a secret seed and an
+/// optional randomization value as well as the message.
+pub fn gen_message_random<P: SpxParams>(
+    r: &mut [u8],
+    sk_prf: &[u8],
+    optrand: &[u8],
+    m: &[u8],
+    mlen: usize,
+    _ctx: &SpxCtx,
+) {
+    let mut hasher = Shake256::default();
+    hasher.update(&sk_prf[..P::N]);
+    hasher.update(&optrand[..P::N]);
+    hasher.update(&m[..mlen]);
+    hasher.finalize_xof().read(&mut r[..P::N]);
+}
+
+/// Computes the message hash using R, the public key, and the message.
+/// Outputs the message digest and the index of the leaf. The index is split in
+/// the tree index and the leaf index, for convenient copying to an address.
+pub fn hash_message<P: SpxParams>(
+    digest: &mut [u8],
+    tree: &mut u64,
+    leaf_idx: &mut u32,
+    r: &[u8],
+    pk: &[u8],
+    m: &[u8],
+    mlen: usize,
+    _ctx: &SpxCtx,
+) {
+    let mut buf = [0u8; SPX_MAX_DGST_BYTES];
+    let mut idx = 0;
+
+    let mut hasher = Shake256::default();
+    hasher.update(&r[..P::N]);
+    hasher.update(&pk[..P::PK_BYTES]);
+    hasher.update(&m[..mlen]);
+    hasher.finalize_xof().read(&mut buf[..P::DGST_BYTES]);
+
+    digest[..P::FORS_MSG_BYTES].copy_from_slice(&buf[..P::FORS_MSG_BYTES]);
+    idx += P::FORS_MSG_BYTES;
+
+    *tree = bytes_to_ull(&buf[idx..], P::TREE_BYTES);
+    *tree &= !0u64 >> (64 - P::TREE_BITS);
+    idx += P::TREE_BYTES;
+
+    *leaf_idx = bytes_to_ull(&buf[idx..], P::LEAF_BYTES) as u32;
+    *leaf_idx &= !0u32 >> (32 - P::LEAF_BITS);
+}
@@ -4,20 +4,104 @@ use crate::params::*;
 use crate::sm3::*;
 use crate::utils::*;
 
-pub fn mgf1_X(out: &mut [u8], outlen: usize, input: &[u8]) {
-    mgf1_256_2(out, outlen, input);
+pub fn mgf1_X(out: &mut [u8], outlen: usize, input: &[u8], inlen: usize) {
+    mgf1_256(out, outlen, input, inlen);
 }
 
-/// For SM3, there is no immediate reason to initialize at the start,
-/// so this function is an empty operation.
-pub fn initialize_hash_function(ctx: &mut SpxCtx) {
-    seed_state(ctx);
+/// Absorbs pub_seed into the seeded SM3 state reused by every thash and
+/// prf_addr call.
+pub fn initialize_hash_function<P: SpxParams>(ctx: &mut SpxCtx) {
+    seed_state::<P>(ctx);
+}
+
+/// Computes PRF(pk_seed, sk_seed, addr).
+pub fn prf_addr<P: SpxParams>(out: &mut [u8], ctx: &SpxCtx, addr: &[u8]) {
+    let mut buf = [0u8; sm3_padded_len(SPX_SM3_ADDR_BYTES + SPX_MAX_N)];
+    let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
+
+    // Retrieve precomputed state containing pub_seed
+    let mut sm3_state = ctx.state_seeded;
+
+    // Remainder: ADDR^c ‖ SK.seed
+    buf[..SPX_SM3_ADDR_BYTES].copy_from_slice(&addr[..SPX_SM3_ADDR_BYTES]);
+    buf[SPX_SM3_ADDR_BYTES..SPX_SM3_ADDR_BYTES + P::N].copy_from_slice(&ctx.sk_seed[..P::N]);
+
+    sm3_inc_finalize_in_place(
+        &mut outbuf,
+        &mut sm3_state,
+        &mut buf,
+        SPX_SM3_ADDR_BYTES + P::N,
+    );
+
+    out[..P::N].copy_from_slice(&outbuf[..P::N]);
+}
+
+/// Computes the message-dependent randomness R, using a secret seed as a key
+/// for HMAC, and an optional randomization value prefixed to the message.
+pub fn gen_message_random<P: SpxParams>(
+    R: &mut [u8],
+    sk_prf: &[u8],
+    optrand: &[u8],
+    m: &[u8],
+    mut mlen: usize,
+    _ctx: &SpxCtx,
+) {
+    let mut buf = [0u8; SPX_SM3_BLOCK_BYTES + SPX_SM3_OUTPUT_BYTES];
+    let mut state = [0u8; 8 + SPX_SM3_OUTPUT_BYTES];
+    let mut m_idx = 0;
+
+    // This implements HMAC-SM3
+    for i in 0..P::N {
+        buf[i] = 0x36 ^ sk_prf[i];
+    }
+    buf[P::N..SPX_SM3_BLOCK_BYTES].fill(0x36);
+
+    sm3_inc_init(&mut state);
+    sm3_inc_blocks(&mut state, &buf, 1);
+
+    buf[..P::N].copy_from_slice(&optrand[..P::N]);
+
+    // If optrand + message cannot fill up an entire block
+    if P::N + mlen < SPX_SM3_BLOCK_BYTES {
+        buf[P::N..P::N + mlen].copy_from_slice(&m[..mlen]);
+        let inner = buf;
+        sm3_inc_finalize(
+            &mut buf[SPX_SM3_BLOCK_BYTES..],
+            &mut state,
+            &inner,
+            mlen + P::N,
+        );
+    }
+    // Otherwise first fill a block, so that finalize only uses the message
+    else {
+        let end = SPX_SM3_BLOCK_BYTES - P::N;
+        buf[P::N..SPX_SM3_BLOCK_BYTES].copy_from_slice(&m[..end]);
+        sm3_inc_blocks(&mut state, &buf, 1);
+
+        m_idx += end;
+        mlen -= end;
+        sm3_inc_finalize(
+            &mut buf[SPX_SM3_BLOCK_BYTES..],
+            &mut state,
+            &m[m_idx..],
+            mlen,
+        );
+    }
+
+    for i in 0..P::N {
+        buf[i] = 0x5c ^ sk_prf[i];
+    }
+    buf[P::N..SPX_SM3_BLOCK_BYTES].fill(0x5c);
+
+    let outer = buf;
+    sm3(&mut buf, &outer, SPX_SM3_BLOCK_BYTES + SPX_SM3_OUTPUT_BYTES);
+    R[..P::N].copy_from_slice(&buf[..P::N]);
 }
 
 /// Computes the message hash using R, the public key, and the message.
 /// Outputs the message digest and the index of the leaf. The index is split in
 /// the tree index and the leaf index, for convenient copying to an address.
-pub fn hash_message(
+pub fn hash_message<P: SpxParams>(
     digest: &mut [u8],
     tree: &mut u64,
     leaf_idx: &mut u32,
@@ -27,17 +111,14 @@ pub fn hash_message(
     mut mlen: usize,
     _ctx: &SpxCtx,
 ) {
-    let mut seed = [0u8; 2 * SPX_N + SPX_SM3_OUTPUT_BYTES];
+    let mut seed = [0u8; 2 * SPX_MAX_N + SPX_SM3_OUTPUT_BYTES];
 
-    /// Round to nearest multiple of SPX_SM3_BLOCK_BYTES
-    // TODO: cleanup this monstrosity
-    const SPX_INBLOCKS: usize = (((SPX_N + SPX_PK_BYTES + SPX_SM3_BLOCK_BYTES - 1) as isize
-        & -(SPX_SM3_BLOCK_BYTES as isize))
-        / SPX_SM3_BLOCK_BYTES as isize) as usize;
+    // Round to nearest multiple of SPX_SM3_BLOCK_BYTES
+    let inblocks = (P::N + P::PK_BYTES).div_ceil(SPX_SM3_BLOCK_BYTES);
 
-    let mut inbuf = [0u8; SPX_INBLOCKS * SPX_SM3_BLOCK_BYTES];
+    let mut inbuf = [0u8; (3 * SPX_MAX_N).div_ceil(SPX_SM3_BLOCK_BYTES) * SPX_SM3_BLOCK_BYTES];
 
-    let mut buf = [0u8; SPX_DGST_BYTES];
+    let mut buf = [0u8; SPX_MAX_DGST_BYTES];
     let mut state = [0u8; 8 + SPX_SM3_OUTPUT_BYTES];
     let mut buf_idx = 0;
     let mut m_idx = 0;
@@ -45,47 +126,52 @@ pub fn hash_message(
     sm3_inc_init(&mut state);
 
     // seed: SM3(R ‖ PK.seed ‖ PK.root ‖ M)
-    inbuf[..SPX_N].copy_from_slice(&R[..SPX_N]);
-    inbuf[SPX_N..SPX_N + SPX_PK_BYTES].copy_from_slice(&pk[..SPX_PK_BYTES]);
+    inbuf[..P::N].copy_from_slice(&R[..P::N]);
+    inbuf[P::N..P::N + P::PK_BYTES].copy_from_slice(&pk[..P::PK_BYTES]);
 
     // If R + pk + message cannot fill up an entire block
-    const START: usize = SPX_N + SPX_PK_BYTES;
-    if START + mlen < SPX_INBLOCKS * SPX_SM3_BLOCK_BYTES {
-        inbuf[START..START + mlen].copy_from_slice(&m[..mlen]);
+    let start = P::N + P::PK_BYTES;
+    if start + mlen < inblocks * SPX_SM3_BLOCK_BYTES {
+        inbuf[start..start + mlen].copy_from_slice(&m[..mlen]);
         sm3_inc_finalize(
-            &mut seed[2 * SPX_N..],
+            &mut seed[2 * P::N..],
             &mut state,
             &inbuf,
-            SPX_N + SPX_PK_BYTES + mlen,
+            P::N + P::PK_BYTES + mlen,
         );
     }
     // Otherwise first fill a block, so that finalize only uses the message
     else {
-        const END: usize = SPX_INBLOCKS * SPX_SM3_BLOCK_BYTES - SPX_N - SPX_PK_BYTES;
-        inbuf[START..START + END].copy_from_slice(&m[..END]);
-        sm3_inc_blocks(&mut state, &inbuf, SPX_INBLOCKS);
+        let end = inblocks * SPX_SM3_BLOCK_BYTES - P::N - P::PK_BYTES;
+        inbuf[start..start + end].copy_from_slice(&m[..end]);
+        sm3_inc_blocks(&mut state, &inbuf, inblocks);
 
-        m_idx += END;
-        mlen -= END;
-        sm3_inc_finalize(&mut seed[2 * SPX_N..], &mut state, &m[m_idx..], mlen);
+        m_idx += end;
+        mlen -= end;
+        sm3_inc_finalize(&mut seed[2 * P::N..], &mut state, &m[m_idx..], mlen);
     }
 
     // H_msg: MGF1-SM3(R ‖ PK.seed ‖ seed)
-    seed[..SPX_N].copy_from_slice(&R[..SPX_N]);
-    seed[SPX_N..SPX_N * 2].copy_from_slice(&pk[..SPX_N]);
+    seed[..P::N].copy_from_slice(&R[..P::N]);
+    seed[P::N..P::N * 2].copy_from_slice(&pk[..P::N]);
 
     // By doing this in two steps, we prevent hashing the message twice;
     // otherwise each iteration in MGF1 would hash the message again.
 
-    mgf1_X(&mut buf, SPX_DGST_BYTES, &seed);
+    mgf1_X(
+        &mut buf,
+        P::DGST_BYTES,
+        &seed,
+        2 * P::N + SPX_SM3_OUTPUT_BYTES,
+    );
 
-    digest[..SPX_FORS_MSG_BYTES].copy_from_slice(&buf[..SPX_FORS_MSG_BYTES]);
-    buf_idx += SPX_FORS_MSG_BYTES;
+    digest[..P::FORS_MSG_BYTES].copy_from_slice(&buf[..P::FORS_MSG_BYTES]);
+    buf_idx += P::FORS_MSG_BYTES;
 
-    *tree = bytes_to_ull(&buf[buf_idx..], SPX_TREE_BYTES);
-    *tree &= !0u64 >> (64 - SPX_TREE_BITS);
-    buf_idx += SPX_TREE_BYTES;
+    *tree = bytes_to_ull(&buf[buf_idx..], P::TREE_BYTES);
+    *tree &= !0u64 >> (64 - P::TREE_BITS);
+    buf_idx += P::TREE_BYTES;
 
-    *leaf_idx = bytes_to_ull(&buf[buf_idx..], SPX_LEAF_BYTES) as u32;
-    *leaf_idx &= !0u32 >> (32 - SPX_LEAF_BITS);
+    *leaf_idx = bytes_to_ull(&buf[buf_idx..], P::LEAF_BYTES) as u32;
+    *leaf_idx &= !0u32 >> (32 - P::LEAF_BITS);
 }
@@ -0,0 +1,360 @@
+//! Known answer tests generated by `tests/KAT/gen_kat.c` from the spxlibAPI C
+//! implementation (sm3) and the PQClean sources (sha2 and shake simple), and by
+//! `tests/KAT/gen_kat.rs` for the sha2 and shake robust sets, covering every
+//! parameter set and tree hash variant.
+//!
+//! Besides the end-to-end vectors in the `.rsp` files, the `.steps` files hold
+//! the intermediate values of the C verifier so that each building block can be
+//! checked in isolation against the reference.
+//!
+//! The SLH-DSA sets are checked against `slh-dsa-acvp.rsp`, a subset of the
+//! NIST ACVP vectors for the internal functions, and against the pure and
+//! pre-hash vectors of `tests/KAT/gen_slh_dsa.rs` for the external interface.
+
+extern crate std;
+
+use crate::address::*;
+use crate::api::*;
+use crate::context::SpxCtx;
+use crate::fors::fors_pk_from_sig;
+use crate::hash::initialize_hash_function;
+use crate::params::*;
+use crate::sign::*;
+use crate::slh_dsa::*;
+use crate::thash::thash;
+use crate::utils::{bytes_to_ull, compute_root};
+use crate::wots::wots_pk_from_sig;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use pqc_core::{bufs, kats, Kat};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Intermediate values of one verification, in the order gen_kat.c writes them.
+struct Steps {
+    mhash: Vec<u8>,
+    tree: u64,
+    idx_leaf: u32,
+    fors_pk: Vec<u8>,
+    wots_pk: Vec<u8>,
+    roots: Vec<u8>,
+}
+
+fn load<P: SpxParams>() -> Vec<(Kat, Steps)> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let kats = kats(&mut root.clone(), &std::format!("sphincs-{}.rsp", P::NAME));
+    let steps = bufs(
+        &mut root.clone(),
+        &std::format!("sphincs-{}.steps", P::NAME),
+    );
+    assert!(!kats.is_empty(), "{}", P::NAME);
+    assert_eq!(steps.len(), 6 * kats.len(), "{}", P::NAME);
+
+    let steps = steps.chunks_exact(6).map(|s| Steps {
+        mhash: s[0].clone(),
+        tree: bytes_to_ull(&s[1], 8),
+        idx_leaf: bytes_to_ull(&s[2], 4) as u32,
+        fors_pk: s[3].clone(),
+        wots_pk: s[4].clone(),
+        roots: s[5].clone(),
+    });
+    kats.into_iter().zip(steps).collect()
+}
+
+fn context<P: SpxParams>(pk: &[u8]) -> SpxCtx {
+    let mut ctx = SpxCtx::default();
+    ctx.pub_seed[..P::N].copy_from_slice(&pk[..P::N]);
+    initialize_hash_function::<P>(&mut ctx);
+    ctx
+}
+
+fn check_sign_verify<P: SpxParams>() {
+    for (kat, _) in load::<P>() {
+        let (mut pk, mut sk) = (vec![0u8; P::PK_BYTES], vec![0u8; P::SK_BYTES]);
+        crypto_sign_seed_keypair::<P>(&mut pk, &mut sk, &kat.seed);
+        assert_eq!(pk, kat.pk, "{}", P::NAME);
+        assert_eq!(sk, kat.sk, "{}", P::NAME);
+
+        let (sig, msg) = kat.sm.split_at(P::BYTES);
+        assert_eq!(kat.smlen, kat.sm.len());
+        assert_eq!(msg, &kat.msg[..kat.mlen]);
+
+        let mut signed = vec![0u8; P::BYTES];
+        crypto_sign_signature::<P>(&mut signed, msg, &kat.sk, &kat.pk[..P::N]);
+        assert_eq!(signed, sig, "{}", P::NAME);
+
+        assert!(
+            crypto_sign_verify::<P>(sig, msg, &kat.pk).is_ok(),
+            "{}",
+            P::NAME
+        );
+
+        let mut forged = sig.to_vec();
+        forged[P::BYTES - 1] ^= 1;
+        assert!(crypto_sign_verify::<P>(&forged, msg, &kat.pk).is_err());
+        assert!(crypto_sign_verify::<P>(sig, &msg[1..], &kat.pk).is_err());
+    }
+}
+
+fn check_fors_pk_from_sig<P: SpxParams>() {
+    for (kat, steps) in load::<P>() {
+        let ctx = context::<P>(&kat.pk);
+        let mut addr = [0u8; SPX_ADDR_BYTES];
+        set_type::<P>(&mut addr, SPX_ADDR_TYPE_WOTS);
+        set_tree_addr::<P>(&mut addr, steps.tree);
+        set_keypair_addr::<P>(&mut addr, steps.idx_leaf);
+
+        let mut pk = [0u8; SPX_MAX_N];
+        fors_pk_from_sig::<P>(&mut pk, &kat.sm[P::N..], &steps.mhash, &ctx, &mut addr);
+        assert_eq!(pk[..P::N], steps.fors_pk[..], "{}", P::NAME);
+    }
+}
+
+fn check_wots_pk_from_sig<P: SpxParams>() {
+    for (kat, steps) in load::<P>() {
+        let ctx = context::<P>(&kat.pk);
+        let mut addr = [0u8; SPX_ADDR_BYTES];
+        set_type::<P>(&mut addr, SPX_ADDR_TYPE_WOTS);
+        set_tree_addr::<P>(&mut addr, steps.tree);
+        set_keypair_addr::<P>(&mut addr, steps.idx_leaf);
+
+        let mut pk = [0u8; SPX_MAX_WOTS_BYTES];
+        let sig = &kat.sm[P::N + P::FORS_BYTES..];
+        wots_pk_from_sig::<P>(&mut pk, sig, &steps.fors_pk, &ctx, &mut addr);
+        assert_eq!(pk[..P::WOTS_BYTES], steps.wots_pk[..], "{}", P::NAME);
+    }
+}
+
+/// Recomputes the root of every hypertree layer, starting each layer from the
+/// reference values of the layer below.
+fn check_compute_root<P: SpxParams>() {
+    for (kat, steps) in load::<P>() {
+        let ctx = context::<P>(&kat.pk);
+        let mut tree = steps.tree;
+        let mut idx_leaf = steps.idx_leaf;
+        let mut idx = P::N + P::FORS_BYTES;
+
+        for i in 0..P::D {
+            let (mut tree_addr, mut wots_addr, mut wots_pk_addr) = (
+                [0u8; SPX_ADDR_BYTES],
+                [0u8; SPX_ADDR_BYTES],
+                [0u8; SPX_ADDR_BYTES],
+            );
+            set_type::<P>(&mut tree_addr, SPX_ADDR_TYPE_HASHTREE);
+            set_type::<P>(&mut wots_addr, SPX_ADDR_TYPE_WOTS);
+            set_type::<P>(&mut wots_pk_addr, SPX_ADDR_TYPE_WOTSPK);
+            set_layer_addr::<P>(&mut tree_addr, i as u32);
+            set_tree_addr::<P>(&mut tree_addr, tree);
+            copy_subtree_addr::<P>(&mut wots_addr, &tree_addr);
+            set_keypair_addr::<P>(&mut wots_addr, idx_leaf);
+            copy_keypair_addr::<P>(&mut wots_pk_addr, &wots_addr);
+
+            let mut wots_pk = [0u8; SPX_MAX_WOTS_BYTES];
+            if i == 0 {
+                wots_pk[..P::WOTS_BYTES].copy_from_slice(&steps.wots_pk);
+            } else {
+                let below = &steps.roots[(i - 1) * P::N..i * P::N];
+                wots_pk_from_sig::<P>(&mut wots_pk, &kat.sm[idx..], below, &ctx, &mut wots_addr);
+            }
+            idx += P::WOTS_BYTES;
+
+            let mut leaf = [0u8; SPX_MAX_N];
+            thash::<P>(&mut leaf, Some(&wots_pk), P::WOTS_LEN, &ctx, &wots_pk_addr);
+
+            let mut root = [0u8; SPX_MAX_N];
+            compute_root::<P>(
+                &mut root,
+                &leaf,
+                idx_leaf,
+                0,
+                &kat.sm[idx..],
+                P::TREE_HEIGHT as u32,
+                &ctx,
+                &mut tree_addr,
+            );
+            assert_eq!(
+                root[..P::N],
+                steps.roots[i * P::N..(i + 1) * P::N],
+                "{} layer {}",
+                P::NAME,
+                i
+            );
+            idx += P::TREE_HEIGHT * P::N;
+
+            idx_leaf = (tree & ((1 << P::TREE_HEIGHT) - 1)) as u32;
+            tree >>= P::TREE_HEIGHT;
+        }
+        assert_eq!(steps.roots[(P::D - 1) * P::N..], kat.pk[P::N..]);
+    }
+}
+
+/// The round 3.1 sets, which have `.rsp` and `.steps` files of their own.
+fn sphincs_plus_sets() -> impl Iterator<Item = &'static ParamSet> {
+    ParamSet::ALL.iter().filter(|set| !set.is_slh_dsa())
+}
+
+#[test]
+fn crypto_sign_verify_kat() {
+    for &params in sphincs_plus_sets() {
+        with_params!(params, P => check_sign_verify::<P>())
+    }
+}
+
+#[test]
+fn fors_pk_from_sig_kat() {
+    for &params in sphincs_plus_sets() {
+        with_params!(params, P => check_fors_pk_from_sig::<P>())
+    }
+}
+
+#[test]
+fn wots_pk_from_sig_kat() {
+    for &params in sphincs_plus_sets() {
+        with_params!(params, P => check_wots_pk_from_sig::<P>())
+    }
+}
+
+#[test]
+fn compute_root_kat() {
+    for &params in sphincs_plus_sets() {
+        with_params!(params, P => check_compute_root::<P>())
+    }
+}
+
+/// Reads `key = value` records separated by blank lines, skipping comments.
+fn records(filename: &str) -> Vec<HashMap<String, String>> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.extend(["tests", "KAT", filename]);
+    let text = std::fs::read_to_string(&path).unwrap();
+    text.split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| line.split_once(" ="))
+                .map(|(key, value)| (key.to_string(), value.trim().to_string()))
+                .collect::<HashMap<_, _>>()
+        })
+        .filter(|record| !record.is_empty())
+        .collect()
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Hands out a fixed `addrnd` in place of fresh randomness.
+struct FixedRng(Vec<u8>);
+
+impl rand_core::RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.copy_from_slice(&self.0[..dest.len()]);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl rand_core::CryptoRng for FixedRng {}
+
+fn keys(params: ParamSet, sk: &[u8]) -> Keypair {
+    Keypair {
+        params,
+        public: sk[sk.len() / 2..].to_vec(),
+        secret: sk.to_vec(),
+    }
+}
+
+#[test]
+fn slh_dsa_acvp_kat() {
+    for record in records("slh-dsa-acvp.rsp") {
+        let Some(params) = ParamSet::from_name(&record["params"]) else {
+            continue;
+        };
+        let count = &record["count"];
+        let msg = record.get("msg").map(|m| unhex(m)).unwrap_or_default();
+
+        if let Some(seed) = record.get("seed") {
+            // keyGen
+            let keys = seed_keypair(params, &unhex(seed)).ok().unwrap();
+            assert_eq!(keys.public, unhex(&record["pk"]), "{}", count);
+            assert_eq!(keys.secret, unhex(&record["sk"]), "{}", count);
+        } else if let Some(sk) = record.get("sk") {
+            // sigGen
+            let keys = keys(params, &unhex(sk));
+            let addrnd = unhex(&record["addrnd"]);
+            let sig = match addrnd.is_empty() {
+                true => sign(&msg, &keys),
+                false => sign_randomized(&msg, &keys, &mut FixedRng(addrnd)),
+            }
+            .ok()
+            .unwrap();
+            assert_eq!(sig, unhex(&record["sig"]), "{}", count);
+            assert!(verify(&sig, &msg, &keys).is_ok(), "{}", count);
+        } else {
+            // sigVer
+            let (sig, pk) = (unhex(&record["sig"]), unhex(&record["pk"]));
+            let valid = vrfy(params, &sig, &msg, &pk).is_ok();
+            assert_eq!(
+                valid,
+                record["valid"] == "1",
+                "{} {}",
+                count,
+                record["reason"]
+            );
+        }
+    }
+}
+
+#[test]
+fn slh_dsa_external_kat() {
+    for &params in ParamSet::ALL.iter().filter(|set| set.is_slh_dsa()) {
+        for record in records(&std::format!("{}.rsp", params.name())) {
+            let keys = seed_keypair(params, &unhex(&record["seed"])).ok().unwrap();
+            assert_eq!(keys.public, unhex(&record["pk"]), "{}", params.name());
+            assert_eq!(keys.secret, unhex(&record["sk"]), "{}", params.name());
+
+            let (ctx, msg) = (unhex(&record["ctx"]), unhex(&record["msg"]));
+            let addrnd = unhex(&record["addrnd"]);
+            let expected = unhex(&record["sig"]);
+            assert_eq!(msg.len(), record["mlen"].parse::<usize>().unwrap());
+
+            match PreHash::from_name(&record["ph"]) {
+                None => {
+                    assert!(addrnd.is_empty());
+                    let sig = slh_sign(&msg, &ctx, &keys).ok().unwrap();
+                    assert_eq!(sig, expected, "{}", params.name());
+                    assert!(slh_verify(params, &sig, &msg, &ctx, &keys.public).is_ok());
+                    assert!(slh_verify(params, &sig, &msg, &ctx[1..], &keys.public).is_err());
+                    assert!(vrfy(params, &sig, &msg, &keys.public).is_err());
+                }
+                Some(ph) => {
+                    let mut rng = FixedRng(addrnd);
+                    let sig = hash_slh_sign_randomized(&msg, &ctx, ph, &keys, &mut rng);
+                    let sig = sig.ok().unwrap();
+                    assert_eq!(sig, expected, "{}", params.name());
+                    assert!(hash_slh_verify(params, &sig, &msg, &ctx, ph, &keys.public).is_ok());
+                    assert!(slh_verify(params, &sig, &msg, &ctx, &keys.public).is_err());
+                    for &other in PreHash::ALL.iter().filter(|&&other| other != ph) {
+                        let res = hash_slh_verify(params, &sig, &msg, &ctx, other, &keys.public);
+                        assert!(res.is_err());
+                    }
+                }
+            }
+        }
+    }
+}
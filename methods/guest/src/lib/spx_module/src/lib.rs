@@ -10,23 +10,29 @@
 //!
 //! ## Usage
 //!
-//! To compile this library needs one from each of the following categories to be
-//! enabled, using more than one from each group will result in a compile error.
+//! At least one of the `sm3`, `sha2` and `shake` hash features must be enabled;
+//! `sm3` is enabled by default.
+//! Every parameter set of the enabled hash functions is compiled in and picked
+//! at runtime through [`ParamSet`], or at compile time through the matching
+//! [`SpxParams`] type (e.g. [`Sm3_128sSimple`]).
 //!
 //! For example in Cargo.toml:
 //!
 //! ```toml
 //! [dependencies]
-//! pqc_sphincsplus = {version = "0.1.0", features = ["haraka", "f128", "simple"]}
+//! spx_sm3 = {path = "src/lib/spx_module", features = ["sm3", "sha2", "shake"]}
 //! ```
 //!
+//! Inside a RISC Zero guest, also enable the `risc0` feature so that SHA-256
+//! block compression runs on the zkVM accelerator instead of in software.
+//!
 //! To generate a keypair and sign a message with it:
 //!
 //! ```no_run
-//!  use pqc_sphincsplus::*;
-//!  let keys = keypair();
+//!  use spx_sm3::*;
+//!  let keys = keypair(ParamSet::Sm3_128sSimple, &mut rand::thread_rng());
 //!  let msg = [0u8; 32];
-//!  let sig = sign(&msg, &keys);
+//!  let sig = sign(&msg, &keys).ok().unwrap();
 //!  let sig_verify = verify(&sig, &msg, &keys);
 //!  assert!(sig_verify.is_ok());
 //! ```
@@ -43,22 +49,74 @@
 //! These instantiations achieve about a factor three speed-up compared to the robust
 //! counterparts. This comes at the cost of a purely heuristic security argument.
 //!
-//! * ### Hash
-//!   * `haraka`
-//!   * `sha2`
-//!   * `shake`
-//!   * `sm3`
-//!
-//! * ### Security Level
-//!   * `f128`
-//!   * `f192`
-//!   * `f256`
-//!   * `s128`
-//!   * `s192`
-//!   * `s256`
-//! * ### TreeHash
-//!   * `simple`
-//!   * `robust`
+//! The `sha2` and `shake` sets follow the round 3.1 SPHINCS+ specification, so
+//! their keys and signatures interoperate with the reference implementation.
+//!
+//! The `slh-dsa-*` sets are SLH-DSA as standardised in FIPS 205. On them,
+//! [`sign`] and [`vrfy`] are the internal algorithms, taking the already
+//! encoded message M'. Use [`slh_sign`] and [`slh_verify`] for the pure
+//! variant with a context string, and [`hash_slh_sign`] and
+//! [`hash_slh_verify`] for HashSLH-DSA over a [`PreHash`] digest:
+//!
+//! ```no_run
+//!  use spx_sm3::*;
+//! # #[cfg(feature = "shake")] {
+//!  let keys = keypair(ParamSet::SlhDsa_Shake_128f, &mut rand::thread_rng());
+//!  let sig = slh_sign(b"message", b"context", &keys).ok().unwrap();
+//!  assert!(slh_verify(keys.params, &sig, b"message", b"context", &keys.public).is_ok());
+//! # }
+//! ```
+//!
+//! | Name                | Type                   |
+//! | :------------------ | :--------------------- |
+//! | `sm3-128s-simple`   | `Sm3_128sSimple`       |
+//! | `sm3-128s-robust`   | `Sm3_128sRobust`       |
+//! | `sm3-128f-simple`   | `Sm3_128fSimple`       |
+//! | `sm3-128f-robust`   | `Sm3_128fRobust`       |
+//! | `sm3-192s-simple`   | `Sm3_192sSimple`       |
+//! | `sm3-192s-robust`   | `Sm3_192sRobust`       |
+//! | `sm3-192f-simple`   | `Sm3_192fSimple`       |
+//! | `sm3-192f-robust`   | `Sm3_192fRobust`       |
+//! | `sm3-256s-simple`   | `Sm3_256sSimple`       |
+//! | `sm3-256s-robust`   | `Sm3_256sRobust`       |
+//! | `sm3-256f-simple`   | `Sm3_256fSimple`       |
+//! | `sm3-256f-robust`   | `Sm3_256fRobust`       |
+//! | `sha2-128s-simple`  | `Sha2_128sSimple`      |
+//! | `sha2-128s-robust`  | `Sha2_128sRobust`      |
+//! | `sha2-128f-simple`  | `Sha2_128fSimple`      |
+//! | `sha2-128f-robust`  | `Sha2_128fRobust`      |
+//! | `sha2-192s-simple`  | `Sha2_192sSimple`      |
+//! | `sha2-192s-robust`  | `Sha2_192sRobust`      |
+//! | `sha2-192f-simple`  | `Sha2_192fSimple`      |
+//! | `sha2-192f-robust`  | `Sha2_192fRobust`      |
+//! | `sha2-256s-simple`  | `Sha2_256sSimple`      |
+//! | `sha2-256s-robust`  | `Sha2_256sRobust`      |
+//! | `sha2-256f-simple`  | `Sha2_256fSimple`      |
+//! | `sha2-256f-robust`  | `Sha2_256fRobust`      |
+//! | `shake-128s-simple` | `Shake_128sSimple`     |
+//! | `shake-128s-robust` | `Shake_128sRobust`     |
+//! | `shake-128f-simple` | `Shake_128fSimple`     |
+//! | `shake-128f-robust` | `Shake_128fRobust`     |
+//! | `shake-192s-simple` | `Shake_192sSimple`     |
+//! | `shake-192s-robust` | `Shake_192sRobust`     |
+//! | `shake-192f-simple` | `Shake_192fSimple`     |
+//! | `shake-192f-robust` | `Shake_192fRobust`     |
+//! | `shake-256s-simple` | `Shake_256sSimple`     |
+//! | `shake-256s-robust` | `Shake_256sRobust`     |
+//! | `shake-256f-simple` | `Shake_256fSimple`     |
+//! | `shake-256f-robust` | `Shake_256fRobust`     |
+//! | `slh-dsa-sha2-128s` | `SlhDsa_Sha2_128s`     |
+//! | `slh-dsa-sha2-128f` | `SlhDsa_Sha2_128f`     |
+//! | `slh-dsa-sha2-192s` | `SlhDsa_Sha2_192s`     |
+//! | `slh-dsa-sha2-192f` | `SlhDsa_Sha2_192f`     |
+//! | `slh-dsa-sha2-256s` | `SlhDsa_Sha2_256s`     |
+//! | `slh-dsa-sha2-256f` | `SlhDsa_Sha2_256f`     |
+//! | `slh-dsa-shake-128s` | `SlhDsa_Shake_128s`    |
+//! | `slh-dsa-shake-128f` | `SlhDsa_Shake_128f`    |
+//! | `slh-dsa-shake-192s` | `SlhDsa_Shake_192s`    |
+//! | `slh-dsa-shake-192f` | `SlhDsa_Shake_192f`    |
+//! | `slh-dsa-shake-256s` | `SlhDsa_Shake_256s`    |
+//! | `slh-dsa-shake-256f` | `SlhDsa_Shake_256f`    |
 //!
 //! A comparison of the different security levels is below.
 //!
@@ -73,54 +131,62 @@
 //! | SPHINCS+-256f | 32 | 68 | 17 |      9 | 35 |  16 |          255 |       64 |      128 |    49,856 |
 //!
 #![no_std]
-#![allow(incomplete_features)]
-#![feature(generic_const_exprs)] //rustup override set nightly
-// Require one from each category
-#![cfg(all(
-    any(feature = "sm3"),
-    any(
-        feature = "f128",
-        feature = "f192",
-        feature = "f256",
-        feature = "s128",
-        feature = "s192",
-        feature = "s256"
-    ),
-    any(feature = "robust", feature = "simple")
-))]
+// The modules follow the C reference implementation function for function,
+// keeping its argument lists and indexed loops.
+#![allow(
+    clippy::too_many_arguments,
+    clippy::needless_range_loop,
+    clippy::explicit_counter_loop
+)]
+#![cfg(any(feature = "sm3", feature = "sha2", feature = "shake"))]
 
-// Ensure only one from each category
-macro_rules! assert_unique_feature {
-  () => {};
-  ($first:tt $(,$rest:tt)*) => {
-    $(
-      #[cfg(all(feature = $first, feature = $rest))]
-      compile_error!(concat!("features \"", $first, "\" and \"", $rest, "\" cannot be used together"));
-    )*
-    assert_unique_feature!($($rest),*);
-  }
-}
-assert_unique_feature!("sm3");
-assert_unique_feature!("f128", "f192", "f256", "s128", "s192", "s256");
-assert_unique_feature!("robust", "simple");
+extern crate alloc;
 
 mod address;
 mod api;
 mod context;
 mod fors;
 mod hash;
+mod merkle;
 mod offsets;
 mod params;
 mod sign;
+mod slh_dsa;
 mod thash;
 mod utils;
 mod wots;
 
 pub use api::*;
+pub use slh_dsa::*;
 
+#[cfg(feature = "sha2")]
+mod sha2;
 #[cfg(feature = "sm3")]
 mod sm3;
 
+#[cfg(test)]
+mod kat;
+
+pub use params::{HashFunction, ParamSet, SpxParams};
+
+#[cfg(feature = "sm3")]
+pub use params::{
+    Sm3_128fRobust, Sm3_128fSimple, Sm3_128sRobust, Sm3_128sSimple, Sm3_192fRobust, Sm3_192fSimple,
+    Sm3_192sRobust, Sm3_192sSimple, Sm3_256fRobust, Sm3_256fSimple, Sm3_256sRobust, Sm3_256sSimple,
+};
+
+#[cfg(feature = "sha2")]
+pub use params::{
+    Sha2_128fRobust, Sha2_128fSimple, Sha2_128sRobust, Sha2_128sSimple, Sha2_192fRobust,
+    Sha2_192fSimple, Sha2_192sRobust, Sha2_192sSimple, Sha2_256fRobust, Sha2_256fSimple,
+    Sha2_256sRobust, Sha2_256sSimple, SlhDsa_Sha2_128f, SlhDsa_Sha2_128s, SlhDsa_Sha2_192f,
+    SlhDsa_Sha2_192s, SlhDsa_Sha2_256f, SlhDsa_Sha2_256s,
+};
+
+#[cfg(feature = "shake")]
 pub use params::{
-    CRYPTO_BYTES, CRYPTO_PUBLICKEYBYTES, CRYPTO_SECRETKEYBYTES, CRYPTO_SEEDBYTES, HASH, MODE, THASH,
+    Shake_128fRobust, Shake_128fSimple, Shake_128sRobust, Shake_128sSimple, Shake_192fRobust,
+    Shake_192fSimple, Shake_192sRobust, Shake_192sSimple, Shake_256fRobust, Shake_256fSimple,
+    Shake_256sRobust, Shake_256sSimple, SlhDsa_Shake_128f, SlhDsa_Shake_128s, SlhDsa_Shake_192f,
+    SlhDsa_Shake_192s, SlhDsa_Shake_256f, SlhDsa_Shake_256s,
 };
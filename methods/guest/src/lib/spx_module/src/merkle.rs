@@ -0,0 +1,68 @@
+use crate::address::*;
+use crate::context::SpxCtx;
+use crate::params::*;
+use crate::utils::*;
+use crate::wots::*;
+
+/// This generates a Merkle signature (WOTS signature followed by the Merkle
+/// authentication path). This is in this file because most of the complexity
+/// is involved with the WOTS signature; the Merkle authentication path logic
+/// is mostly hidden in treehash
+pub fn merkle_sign<P: SpxParams>(
+    sig: &mut [u8],
+    root: &mut [u8],
+    ctx: &SpxCtx,
+    wots_addr: &[u8],
+    tree_addr: &mut [u8; SPX_ADDR_BYTES],
+    idx_leaf: u32,
+) {
+    let (wots_sig, auth_path) = sig.split_at_mut(P::WOTS_BYTES);
+    let mut info = LeafInfo {
+        wots_sig,
+        wots_sign_leaf: idx_leaf,
+        wots_steps: [0u32; SPX_MAX_WOTS_LEN],
+        leaf_addr: [0u8; SPX_ADDR_BYTES],
+        pk_addr: [0u8; SPX_ADDR_BYTES],
+    };
+
+    chain_lengths::<P>(&mut info.wots_steps, root);
+
+    set_type::<P>(tree_addr, SPX_ADDR_TYPE_HASHTREE);
+    set_type::<P>(&mut info.pk_addr, SPX_ADDR_TYPE_WOTSPK);
+    copy_subtree_addr::<P>(&mut info.leaf_addr, wots_addr);
+    copy_subtree_addr::<P>(&mut info.pk_addr, wots_addr);
+
+    treehash::<P, _>(
+        root,
+        auth_path,
+        ctx,
+        idx_leaf,
+        0,
+        P::TREE_HEIGHT as u32,
+        |leaf, ctx, leaf_idx| wots_gen_leaf::<P>(leaf, ctx, leaf_idx, &mut info),
+        tree_addr,
+    );
+}
+
+/// Compute root node of the top-most subtree.
+pub fn merkle_gen_root<P: SpxParams>(root: &mut [u8], ctx: &SpxCtx) {
+    // We do not need the auth path in key generation, but it simplifies the
+    // code to have just one treehash routine that computes both root and path
+    // in one function.
+    let mut auth_path = [0u8; SPX_MAX_TREE_HEIGHT * SPX_MAX_N + SPX_MAX_WOTS_BYTES];
+    let mut top_tree_addr = [0u8; SPX_ADDR_BYTES];
+    let mut wots_addr = [0u8; SPX_ADDR_BYTES];
+
+    set_layer_addr::<P>(&mut top_tree_addr, P::D as u32 - 1);
+    set_layer_addr::<P>(&mut wots_addr, P::D as u32 - 1);
+
+    // !0 means "don't bother generating an auth path"
+    merkle_sign::<P>(
+        &mut auth_path,
+        root,
+        ctx,
+        &wots_addr,
+        &mut top_tree_addr,
+        !0u32,
+    );
+}
@@ -1,5 +1,35 @@
+#[cfg(feature = "sha2")]
+mod sha2;
+#[cfg(feature = "shake")]
+mod shake;
 #[cfg(feature = "sm3")]
 mod sm3;
 
+#[cfg(feature = "sha2")]
+pub use sha2::SHA2;
+#[cfg(feature = "shake")]
+pub use shake::SHAKE;
 #[cfg(feature = "sm3")]
-pub use sm3::*;
+pub use sm3::SM3;
+
+/// Byte offsets of the fields of the 32-byte address structure. They depend
+/// on the hash function since SHA-2 and SM3 only hash a compressed address.
+#[derive(Copy, Clone, Debug)]
+pub struct Offsets {
+    /// The byte used to specify the Merkle tree layer
+    pub layer: usize,
+    /// The start of the 8 byte field used to specify the tree
+    pub tree: usize,
+    /// The byte used to specify the hash type (reason)
+    pub type_: usize,
+    /// The start of the 4 byte field used to specify the key pair address
+    pub kp_addr: usize,
+    /// The byte used to specify the chain address (which Winternitz chain)
+    pub chain_addr: usize,
+    /// The byte used to specify the hash address (where in the Winternitz chain)
+    pub hash_addr: usize,
+    /// The byte used to specify the height of this node in the FORS or Merkle tree
+    pub tree_hgt: usize,
+    /// The start of the 4 byte field used to specify the node in the FORS or Merkle tree
+    pub tree_index: usize,
+}
@@ -0,0 +1,14 @@
+use super::Offsets;
+
+/// Offsets of various fields in the address structure when we use SHA2 as
+/// the Sphincs+ hash function
+pub const SHA2: Offsets = Offsets {
+    layer: 0,
+    tree: 1,
+    type_: 9,
+    kp_addr: 10,
+    chain_addr: 17,
+    hash_addr: 21,
+    tree_hgt: 17,
+    tree_index: 18,
+};
@@ -0,0 +1,14 @@
+use super::Offsets;
+
+/// Offsets of various fields in the address structure when we use SHAKE as
+/// the Sphincs+ hash function
+pub const SHAKE: Offsets = Offsets {
+    layer: 3,
+    tree: 8,
+    type_: 19,
+    kp_addr: 20,
+    chain_addr: 27,
+    hash_addr: 31,
+    tree_hgt: 27,
+    tree_index: 28,
+};
@@ -1,111 +1,355 @@
-#[cfg(feature = "f128")]
-mod f128;
-#[cfg(feature = "f192")]
-mod f192;
-#[cfg(feature = "f256")]
-mod f256;
-#[cfg(feature = "s128")]
-mod s128;
-#[cfg(feature = "s192")]
-mod s192;
-#[cfg(feature = "s256")]
-mod s256;
-
-#[cfg(feature = "f128")]
-pub use f128::*;
-#[cfg(feature = "f192")]
-pub use f192::*;
-#[cfg(feature = "f256")]
-pub use f256::*;
-#[cfg(feature = "s128")]
-pub use s128::*;
-#[cfg(feature = "s192")]
-pub use s192::*;
-#[cfg(feature = "s256")]
-pub use s256::*;
-
-pub const CRYPTO_SECRETKEYBYTES: usize = SPX_SK_BYTES;
-pub const CRYPTO_PUBLICKEYBYTES: usize = SPX_PK_BYTES;
-pub const CRYPTO_BYTES: usize = SPX_BYTES;
-pub const CRYPTO_SEEDBYTES: usize = 3 * SPX_N;
-
-/// Winternitz parameter,
-pub const SPX_WOTS_W: usize = 16;
-
-/// WOTS parameters.
-pub const SPX_WOTS_LOGW: usize = if SPX_WOTS_W == 256 {
-    8
-} else {
-    // if SPX_WOTS_W == 16
-    4
-};
-
-pub const SPX_WOTS_LEN: usize = SPX_WOTS_LEN1 + SPX_WOTS_LEN2;
-pub const SPX_WOTS_BYTES: usize = SPX_WOTS_LEN * SPX_N;
-// pub const SPX_WOTS_PK_BYTES: usize = SPX_WOTS_BYTES;
-
-/// Subtree size.
-pub const SPX_TREE_HEIGHT: usize = SPX_FULL_HEIGHT / SPX_D;
-
-/// FORS parameters.
-pub const SPX_FORS_MSG_BYTES: usize = (SPX_FORS_HEIGHT * SPX_FORS_TREES + 7) / 8;
-pub const SPX_FORS_BYTES: usize = (SPX_FORS_HEIGHT + 1) * SPX_FORS_TREES * SPX_N;
-// pub const SPX_FORS_PK_BYTES: usize = SPX_N;
-
-/// Resulting SPX sizes.
-pub const SPX_BYTES: usize =
-    SPX_N + SPX_FORS_BYTES + SPX_D * SPX_WOTS_BYTES + SPX_FULL_HEIGHT * SPX_N;
-pub const SPX_PK_BYTES: usize = 2 * SPX_N;
-pub const SPX_SK_BYTES: usize = 2 * SPX_N + SPX_PK_BYTES;
-
-// pub const WOTS_SIG_LEN: usize = SPX_TREE_HEIGHT * SPX_N + SPX_WOTS_BYTES;
-
-pub const SPX_TREE_BITS: usize = SPX_TREE_HEIGHT * (SPX_D - 1);
-pub const SPX_TREE_BYTES: usize = (SPX_TREE_BITS + 7) / 8;
-pub const SPX_LEAF_BITS: usize = SPX_TREE_HEIGHT;
-pub const SPX_LEAF_BYTES: usize = (SPX_LEAF_BITS + 7) / 8;
-pub const SPX_DGST_BYTES: usize = SPX_FORS_MSG_BYTES + SPX_TREE_BYTES + SPX_LEAF_BYTES;
-
-pub const SPX_WOTS_LEN1: usize = 8 * SPX_N / SPX_WOTS_LOGW;
-
-/// SPX_WOTS_LEN2 is floor(log(len_1 * (w - 1)) / log(w)) + 1; we precompute
-pub const SPX_WOTS_LEN2: usize = if SPX_WOTS_W == 256 {
-    if SPX_N <= 1 {
-        1
-    } else {
-        // if SPX_N <= 256
-        2
-    }
-} else {
-    //if SPX_WOTS_W == 16
-    if SPX_N <= 8 {
-        2
-    } else if SPX_N <= 136 {
-        3
+use crate::offsets::*;
+
+/// A SPHINCS+ instance. Each parameter set is a zero-sized type implementing
+/// this trait, so the signing and verification code is monomorphised per set
+/// and the constants below fold away exactly as the old per-feature `const`s did.
+pub trait SpxParams {
+    /// Name committed alongside results, e.g. `sm3-128s-simple`.
+    const NAME: &'static str;
+    /// Security level and variant, e.g. `128s`.
+    const MODE: &'static str;
+
+    /// Hash output length in bytes.
+    const N: usize;
+    /// Height of the hypertree.
+    const FULL_HEIGHT: usize;
+    /// Number of subtree layer.
+    const D: usize;
+    /// FORS tree dimensions.
+    const FORS_HEIGHT: usize;
+    const FORS_TREES: usize;
+    /// Use the robust (bitmasked) tweakable hash instead of the simple one.
+    const ROBUST: bool;
+    /// Instantiates SLH-DSA as standardised in FIPS 205 rather than round 3.1
+    /// SPHINCS+. The two only differ in the order FORS indices are read from
+    /// the message digest; the context string and pre-hash wrapping of FIPS 205
+    /// is applied by the `slh_*` functions on top.
+    const SLH_DSA: bool;
+    /// Hash function family the tweakable hash, PRF and H_msg are built on.
+    const HASH: HashFunction;
+
+    /// Winternitz parameter,
+    const WOTS_W: usize = 16;
+
+    /// WOTS parameters.
+    const WOTS_LOGW: usize = if Self::WOTS_W == 256 {
+        8
     } else {
-        // if SPX_N <= 256
+        // if WOTS_W == 16
         4
+    };
+
+    const WOTS_LEN1: usize = 8 * Self::N / Self::WOTS_LOGW;
+
+    /// WOTS_LEN2 is floor(log(len_1 * (w - 1)) / log(w)) + 1; we precompute
+    const WOTS_LEN2: usize = if Self::WOTS_W == 256 {
+        if Self::N <= 1 {
+            1
+        } else {
+            // if N <= 256
+            2
+        }
+    } else {
+        //if WOTS_W == 16
+        if Self::N <= 8 {
+            2
+        } else if Self::N <= 136 {
+            3
+        } else {
+            // if N <= 256
+            4
+        }
+    };
+
+    const WOTS_LEN: usize = Self::WOTS_LEN1 + Self::WOTS_LEN2;
+    const WOTS_BYTES: usize = Self::WOTS_LEN * Self::N;
+
+    /// Subtree size.
+    const TREE_HEIGHT: usize = Self::FULL_HEIGHT / Self::D;
+
+    /// FORS parameters.
+    const FORS_MSG_BYTES: usize = (Self::FORS_HEIGHT * Self::FORS_TREES + 7) / 8;
+    const FORS_BYTES: usize = (Self::FORS_HEIGHT + 1) * Self::FORS_TREES * Self::N;
+
+    /// Resulting SPX sizes.
+    const BYTES: usize =
+        Self::N + Self::FORS_BYTES + Self::D * Self::WOTS_BYTES + Self::FULL_HEIGHT * Self::N;
+    const PK_BYTES: usize = 2 * Self::N;
+    const SK_BYTES: usize = 2 * Self::N + Self::PK_BYTES;
+    const SEED_BYTES: usize = 3 * Self::N;
+
+    const TREE_BITS: usize = Self::TREE_HEIGHT * (Self::D - 1);
+    const TREE_BYTES: usize = (Self::TREE_BITS + 7) / 8;
+    const LEAF_BITS: usize = Self::TREE_HEIGHT;
+    const LEAF_BYTES: usize = (Self::LEAF_BITS + 7) / 8;
+    const DGST_BYTES: usize = Self::FORS_MSG_BYTES + Self::TREE_BYTES + Self::LEAF_BYTES;
+}
+
+/// Upper bounds over every parameter set below, used to size stack buffers.
+pub const SPX_MAX_N: usize = 32;
+pub const SPX_MAX_WOTS_LEN: usize = 67;
+pub const SPX_MAX_WOTS_BYTES: usize = SPX_MAX_WOTS_LEN * SPX_MAX_N;
+pub const SPX_MAX_FORS_TREES: usize = 35;
+pub const SPX_MAX_FORS_MSG_BYTES: usize = 40;
+pub const SPX_MAX_TREE_HEIGHT: usize = 14;
+pub const SPX_MAX_DGST_BYTES: usize = 49;
+
+/// Hash function family of a parameter set. Each family is behind the cargo
+/// feature of the same name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HashFunction {
+    #[cfg(feature = "sm3")]
+    Sm3,
+    #[cfg(feature = "sha2")]
+    Sha2,
+    #[cfg(feature = "shake")]
+    Shake,
+}
+
+impl HashFunction {
+    /// Name used as the prefix of parameter set names, e.g. `sha2`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "sm3")]
+            HashFunction::Sm3 => "sm3",
+            #[cfg(feature = "sha2")]
+            HashFunction::Sha2 => "sha2",
+            #[cfg(feature = "shake")]
+            HashFunction::Shake => "shake",
+        }
+    }
+
+    pub(crate) const fn offsets(self) -> Offsets {
+        match self {
+            #[cfg(feature = "sm3")]
+            HashFunction::Sm3 => SM3,
+            #[cfg(feature = "sha2")]
+            HashFunction::Sha2 => SHA2,
+            #[cfg(feature = "shake")]
+            HashFunction::Shake => SHAKE,
+        }
     }
-};
-
-pub const HASH: &str = "sm3";
-
-pub const MODE: &str = if cfg!(feature = "s128") {
-    "128s"
-} else if cfg!(feature = "f128") {
-    "128f"
-} else if cfg!(feature = "s192") {
-    "192s"
-} else if cfg!(feature = "f192") {
-    "192f"
-} else if cfg!(feature = "s256") {
-    "256s"
-} else {
-    "256f"
-};
-
-pub const THASH: &str = if cfg!(feature = "simple") {
-    "simple"
-} else {
-    "robust"
-};
+}
+
+/// Name of a parameter set; the SLH-DSA sets follow the FIPS 205 naming.
+macro_rules! param_name {
+    ($feature:literal, $mode:literal, "slh-dsa") => {
+        concat!("slh-dsa-", $feature, "-", $mode)
+    };
+    ($feature:literal, $mode:literal, $thash:literal) => {
+        concat!($feature, "-", $mode, "-", $thash)
+    };
+}
+
+macro_rules! param_doc {
+    ($feature:literal, $mode:literal, "slh-dsa") => {
+        concat!(
+            "SLH-DSA `",
+            param_name!($feature, $mode, "slh-dsa"),
+            "` (FIPS 205)"
+        )
+    };
+    ($feature:literal, $mode:literal, $thash:literal) => {
+        concat!("SPHINCS+ `", param_name!($feature, $mode, $thash), "`")
+    };
+}
+
+macro_rules! param_sets {
+    ($dollar:tt $($ty:ident => $hash:ident, $feature:literal, $mode:literal, $thash:tt, $n:literal, $h:literal, $d:literal, $fors_h:literal, $fors_t:literal;)*) => {
+        $(
+            #[cfg(feature = $feature)]
+            #[doc = param_doc!($feature, $mode, $thash)]
+            #[allow(non_camel_case_types)]
+            #[derive(Copy, Clone, Debug)]
+            pub struct $ty;
+
+            #[cfg(feature = $feature)]
+            impl SpxParams for $ty {
+                const NAME: &'static str = param_name!($feature, $mode, $thash);
+                const MODE: &'static str = $mode;
+                const N: usize = $n;
+                const FULL_HEIGHT: usize = $h;
+                const D: usize = $d;
+                const FORS_HEIGHT: usize = $fors_h;
+                const FORS_TREES: usize = $fors_t;
+                const ROBUST: bool = matches!($thash.as_bytes(), b"robust");
+                const SLH_DSA: bool = matches!($thash.as_bytes(), b"slh-dsa");
+                const HASH: HashFunction = HashFunction::$hash;
+            }
+
+            #[cfg(feature = $feature)]
+            const _: () = {
+                assert!(<$ty as SpxParams>::N <= SPX_MAX_N);
+                assert!(<$ty as SpxParams>::WOTS_LEN <= SPX_MAX_WOTS_LEN);
+                assert!(<$ty as SpxParams>::FORS_TREES <= SPX_MAX_FORS_TREES);
+                assert!(<$ty as SpxParams>::FORS_MSG_BYTES <= SPX_MAX_FORS_MSG_BYTES);
+                assert!(<$ty as SpxParams>::FORS_HEIGHT <= SPX_MAX_TREE_HEIGHT);
+                assert!(<$ty as SpxParams>::TREE_HEIGHT <= SPX_MAX_TREE_HEIGHT);
+                assert!(<$ty as SpxParams>::DGST_BYTES <= SPX_MAX_DGST_BYTES);
+            };
+        )*
+
+        /// Runtime tag naming one of the supported parameter sets.
+        #[allow(non_camel_case_types)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum ParamSet {
+            $(
+                #[cfg(feature = $feature)]
+                $ty,
+            )*
+        }
+
+        impl ParamSet {
+            /// Every parameter set enabled by the crate features.
+            pub const ALL: &'static [ParamSet] = &[$(
+                #[cfg(feature = $feature)]
+                ParamSet::$ty,
+            )*];
+
+            /// Name of the parameter set, e.g. `sm3-128s-simple`.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::NAME,
+                    )*
+                }
+            }
+
+            pub const fn hash(self) -> HashFunction {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::HASH,
+                    )*
+                }
+            }
+
+            /// Whether the set is one of the FIPS 205 SLH-DSA instances.
+            pub const fn is_slh_dsa(self) -> bool {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::SLH_DSA,
+                    )*
+                }
+            }
+
+            pub const fn public_key_bytes(self) -> usize {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::PK_BYTES,
+                    )*
+                }
+            }
+
+            pub const fn secret_key_bytes(self) -> usize {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::SK_BYTES,
+                    )*
+                }
+            }
+
+            pub const fn signature_bytes(self) -> usize {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::BYTES,
+                    )*
+                }
+            }
+
+            pub const fn seed_bytes(self) -> usize {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ParamSet::$ty => <$ty as SpxParams>::SEED_BYTES,
+                    )*
+                }
+            }
+        }
+
+        /// Runs `body` with `P` bound to the parameter set type selected by `set`.
+        macro_rules! with_params {
+            ($dollar set:expr, $dollar P:ident => $dollar body:expr) => {
+                match $dollar set {
+                    $(
+                        #[cfg(feature = $feature)]
+                        $crate::params::ParamSet::$ty => {
+                            type $dollar P = $crate::params::$ty;
+                            $dollar body
+                        }
+                    )*
+                }
+            };
+        }
+    };
+}
+
+//                       hash   feature  mode    thash      n   h   d log(t) k
+param_sets! {
+    $
+    Sm3_128sSimple    => Sm3,   "sm3",   "128s", "simple",  16, 63,  7, 12, 14;
+    Sm3_128sRobust    => Sm3,   "sm3",   "128s", "robust",  16, 63,  7, 12, 14;
+    Sm3_128fSimple    => Sm3,   "sm3",   "128f", "simple",  16, 66, 22,  6, 33;
+    Sm3_128fRobust    => Sm3,   "sm3",   "128f", "robust",  16, 66, 22,  6, 33;
+    Sm3_192sSimple    => Sm3,   "sm3",   "192s", "simple",  24, 63,  7, 14, 17;
+    Sm3_192sRobust    => Sm3,   "sm3",   "192s", "robust",  24, 63,  7, 14, 17;
+    Sm3_192fSimple    => Sm3,   "sm3",   "192f", "simple",  24, 66, 22,  8, 33;
+    Sm3_192fRobust    => Sm3,   "sm3",   "192f", "robust",  24, 66, 22,  8, 33;
+    Sm3_256sSimple    => Sm3,   "sm3",   "256s", "simple",  32, 64,  8, 14, 22;
+    Sm3_256sRobust    => Sm3,   "sm3",   "256s", "robust",  32, 64,  8, 14, 22;
+    Sm3_256fSimple    => Sm3,   "sm3",   "256f", "simple",  32, 68, 17,  9, 35;
+    Sm3_256fRobust    => Sm3,   "sm3",   "256f", "robust",  32, 68, 17,  9, 35;
+    Sha2_128sSimple   => Sha2,  "sha2",  "128s", "simple",  16, 63,  7, 12, 14;
+    Sha2_128sRobust   => Sha2,  "sha2",  "128s", "robust",  16, 63,  7, 12, 14;
+    Sha2_128fSimple   => Sha2,  "sha2",  "128f", "simple",  16, 66, 22,  6, 33;
+    Sha2_128fRobust   => Sha2,  "sha2",  "128f", "robust",  16, 66, 22,  6, 33;
+    Sha2_192sSimple   => Sha2,  "sha2",  "192s", "simple",  24, 63,  7, 14, 17;
+    Sha2_192sRobust   => Sha2,  "sha2",  "192s", "robust",  24, 63,  7, 14, 17;
+    Sha2_192fSimple   => Sha2,  "sha2",  "192f", "simple",  24, 66, 22,  8, 33;
+    Sha2_192fRobust   => Sha2,  "sha2",  "192f", "robust",  24, 66, 22,  8, 33;
+    Sha2_256sSimple   => Sha2,  "sha2",  "256s", "simple",  32, 64,  8, 14, 22;
+    Sha2_256sRobust   => Sha2,  "sha2",  "256s", "robust",  32, 64,  8, 14, 22;
+    Sha2_256fSimple   => Sha2,  "sha2",  "256f", "simple",  32, 68, 17,  9, 35;
+    Sha2_256fRobust   => Sha2,  "sha2",  "256f", "robust",  32, 68, 17,  9, 35;
+    Shake_128sSimple  => Shake, "shake", "128s", "simple",  16, 63,  7, 12, 14;
+    Shake_128sRobust  => Shake, "shake", "128s", "robust",  16, 63,  7, 12, 14;
+    Shake_128fSimple  => Shake, "shake", "128f", "simple",  16, 66, 22,  6, 33;
+    Shake_128fRobust  => Shake, "shake", "128f", "robust",  16, 66, 22,  6, 33;
+    Shake_192sSimple  => Shake, "shake", "192s", "simple",  24, 63,  7, 14, 17;
+    Shake_192sRobust  => Shake, "shake", "192s", "robust",  24, 63,  7, 14, 17;
+    Shake_192fSimple  => Shake, "shake", "192f", "simple",  24, 66, 22,  8, 33;
+    Shake_192fRobust  => Shake, "shake", "192f", "robust",  24, 66, 22,  8, 33;
+    Shake_256sSimple  => Shake, "shake", "256s", "simple",  32, 64,  8, 14, 22;
+    Shake_256sRobust  => Shake, "shake", "256s", "robust",  32, 64,  8, 14, 22;
+    Shake_256fSimple  => Shake, "shake", "256f", "simple",  32, 68, 17,  9, 35;
+    Shake_256fRobust  => Shake, "shake", "256f", "robust",  32, 68, 17,  9, 35;
+    SlhDsa_Sha2_128s  => Sha2,  "sha2",  "128s", "slh-dsa", 16, 63,  7, 12, 14;
+    SlhDsa_Sha2_128f  => Sha2,  "sha2",  "128f", "slh-dsa", 16, 66, 22,  6, 33;
+    SlhDsa_Sha2_192s  => Sha2,  "sha2",  "192s", "slh-dsa", 24, 63,  7, 14, 17;
+    SlhDsa_Sha2_192f  => Sha2,  "sha2",  "192f", "slh-dsa", 24, 66, 22,  8, 33;
+    SlhDsa_Sha2_256s  => Sha2,  "sha2",  "256s", "slh-dsa", 32, 64,  8, 14, 22;
+    SlhDsa_Sha2_256f  => Sha2,  "sha2",  "256f", "slh-dsa", 32, 68, 17,  9, 35;
+    SlhDsa_Shake_128s => Shake, "shake", "128s", "slh-dsa", 16, 63,  7, 12, 14;
+    SlhDsa_Shake_128f => Shake, "shake", "128f", "slh-dsa", 16, 66, 22,  6, 33;
+    SlhDsa_Shake_192s => Shake, "shake", "192s", "slh-dsa", 24, 63,  7, 14, 17;
+    SlhDsa_Shake_192f => Shake, "shake", "192f", "slh-dsa", 24, 66, 22,  8, 33;
+    SlhDsa_Shake_256s => Shake, "shake", "256s", "slh-dsa", 32, 64,  8, 14, 22;
+    SlhDsa_Shake_256f => Shake, "shake", "256f", "slh-dsa", 32, 68, 17,  9, 35;
+}
+
+impl ParamSet {
+    /// Looks a parameter set up by its name, e.g. `sm3-128s-simple` or
+    /// `slh-dsa-shake-128f`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|set| set.name() == name)
+    }
+}
+
+// Exports the macro by path; clippy mistakes this for a redundant import
+#[allow(clippy::single_component_path_imports)]
+pub(crate) use with_params;
@@ -1,9 +0,0 @@
-/// Hash output length in bytes.
-pub const SPX_N: usize = 24;
-/// Height of the hypertree.
-pub const SPX_FULL_HEIGHT: usize = 63;
-/// Number of subtree layer.
-pub const SPX_D: usize = 7;
-/// FORS tree dimensions.
-pub const SPX_FORS_HEIGHT: usize = 14;
-pub const SPX_FORS_TREES: usize = 17;
@@ -1,9 +0,0 @@
-/// Hash output length in bytes.
-pub const SPX_N: usize = 32;
-/// Height of the hypertree.
-pub const SPX_FULL_HEIGHT: usize = 64;
-/// Number of subtree layer.
-pub const SPX_D: usize = 8;
-/// FORS tree dimensions.
-pub const SPX_FORS_HEIGHT: usize = 14;
-pub const SPX_FORS_TREES: usize = 22;
@@ -0,0 +1,331 @@
+use crate::context::SpxCtx;
+use crate::params::*;
+use crate::utils::*;
+use sha2::digest::generic_array::GenericArray;
+
+pub const SPX_SHA256_BLOCK_BYTES: usize = 64;
+pub const SPX_SHA256_OUTPUT_BYTES: usize = 32; /* This does not necessarily equal SPX_N */
+
+pub const SPX_SHA512_BLOCK_BYTES: usize = 128;
+pub const SPX_SHA512_OUTPUT_BYTES: usize = 64;
+
+pub const SPX_SHA256_ADDR_BYTES: usize = 22;
+
+const IV_256: [u8; 32] = [
+    0x6a, 0x09, 0xe6, 0x67, 0xbb, 0x67, 0xae, 0x85, 0x3c, 0x6e, 0xf3, 0x72, 0xa5, 0x4f, 0xf5, 0x3a,
+    0x51, 0x0e, 0x52, 0x7f, 0x9b, 0x05, 0x68, 0x8c, 0x1f, 0x83, 0xd9, 0xab, 0x5b, 0xe0, 0xcd, 0x19,
+];
+
+const IV_512: [u8; 64] = [
+    0x6a, 0x09, 0xe6, 0x67, 0xf3, 0xbc, 0xc9, 0x08, 0xbb, 0x67, 0xae, 0x85, 0x84, 0xca, 0xa7, 0x3b,
+    0x3c, 0x6e, 0xf3, 0x72, 0xfe, 0x94, 0xf8, 0x2b, 0xa5, 0x4f, 0xf5, 0x3a, 0x5f, 0x1d, 0x36, 0xf1,
+    0x51, 0x0e, 0x52, 0x7f, 0xad, 0xe6, 0x82, 0xd1, 0x9b, 0x05, 0x68, 0x8c, 0x2b, 0x3e, 0x6c, 0x1f,
+    0x1f, 0x83, 0xd9, 0xab, 0xfb, 0x41, 0xbd, 0x6b, 0x5b, 0xe0, 0xcd, 0x19, 0x13, 0x7e, 0x21, 0x79,
+];
+
+/// Runs the SHA-256 compression function over the whole blocks of `input`.
+/// With the `risc0` feature this goes through the zkVM SHA-256 accelerator.
+fn crypto_hashblocks_sha256(statebytes: &mut [u8], input: &[u8]) {
+    #[cfg(feature = "risc0")]
+    hashblocks_sha256_risc0(statebytes, input);
+    #[cfg(not(feature = "risc0"))]
+    hashblocks_sha256_soft(statebytes, input);
+}
+
+#[cfg_attr(all(feature = "risc0", not(test)), allow(dead_code))]
+fn hashblocks_sha256_soft(statebytes: &mut [u8], input: &[u8]) {
+    let mut state = [0u32; 8];
+    for i in 0..8 {
+        state[i] = load_bigendian_32(&statebytes[i * 4..]);
+    }
+
+    for block in input.chunks_exact(SPX_SHA256_BLOCK_BYTES) {
+        sha2::compress256(&mut state, &[*GenericArray::from_slice(block)]);
+    }
+
+    for i in 0..8 {
+        store_bigendian_32(&mut statebytes[i * 4..], state[i]);
+    }
+}
+
+/// The zkVM digest words hold the chaining value in big-endian byte order,
+/// which is exactly the layout of `statebytes`, so no conversion is needed.
+/// Blocks are copied into word-aligned buffers a few at a time to keep the
+/// number of accelerator calls low.
+#[cfg(feature = "risc0")]
+fn hashblocks_sha256_risc0(statebytes: &mut [u8], input: &[u8]) {
+    use risc0_zkp::core::digest::{Digest, DIGEST_BYTES};
+    use risc0_zkp::core::hash::sha::{Block, Impl, Sha256};
+
+    const BATCH: usize = 8;
+    let mut blocks = [Block::default(); BATCH];
+    let mut state = Digest::from_bytes(statebytes[..DIGEST_BYTES].try_into().unwrap());
+
+    for chunk in input.chunks(BATCH * SPX_SHA256_BLOCK_BYTES) {
+        let n = chunk.len() / SPX_SHA256_BLOCK_BYTES;
+        for (block, bytes) in blocks
+            .iter_mut()
+            .zip(chunk.chunks_exact(SPX_SHA256_BLOCK_BYTES))
+        {
+            block.as_mut_bytes().copy_from_slice(bytes);
+        }
+        state = *Impl::compress_slice(&state, &blocks[..n]);
+    }
+
+    statebytes[..DIGEST_BYTES].copy_from_slice(state.as_bytes());
+}
+
+fn crypto_hashblocks_sha512(statebytes: &mut [u8], input: &[u8]) {
+    let mut state = [0u64; 8];
+    for i in 0..8 {
+        state[i] = load_bigendian_64(&statebytes[i * 8..]);
+    }
+
+    for block in input.chunks_exact(SPX_SHA512_BLOCK_BYTES) {
+        sha2::compress512(&mut state, &[*GenericArray::from_slice(block)]);
+    }
+
+    for i in 0..8 {
+        store_bigendian_64(&mut statebytes[i * 8..], state[i]);
+    }
+}
+
+pub fn sha256_inc_init(state: &mut [u8]) {
+    // The first 32 bytes hold the chaining value, the last 8 the number of
+    // bytes processed so far.
+    state[..32].copy_from_slice(&IV_256);
+    state[32..40].fill(0);
+}
+
+pub fn sha512_inc_init(state: &mut [u8]) {
+    state[..64].copy_from_slice(&IV_512);
+    state[64..72].fill(0);
+}
+
+pub fn sha256_inc_blocks(state: &mut [u8], input: &[u8], inblocks: usize) {
+    let mut bytes = load_bigendian_64(&state[32..40]);
+
+    crypto_hashblocks_sha256(state, &input[..SPX_SHA256_BLOCK_BYTES * inblocks]);
+    bytes += (SPX_SHA256_BLOCK_BYTES * inblocks) as u64;
+
+    store_bigendian_64(&mut state[32..40], bytes);
+}
+
+pub fn sha512_inc_blocks(state: &mut [u8], input: &[u8], inblocks: usize) {
+    let mut bytes = load_bigendian_64(&state[64..72]);
+
+    crypto_hashblocks_sha512(state, &input[..SPX_SHA512_BLOCK_BYTES * inblocks]);
+    bytes += (SPX_SHA512_BLOCK_BYTES * inblocks) as u64;
+
+    store_bigendian_64(&mut state[64..72], bytes);
+}
+
+pub fn sha256_inc_finalize(out: &mut [u8], state: &mut [u8], input: &[u8], inlen: usize) {
+    let mut padded = [0u8; 128];
+    let bytes = load_bigendian_64(&state[32..40]) + inlen as u64;
+
+    crypto_hashblocks_sha256(state, &input[..inlen & !63]);
+
+    let rem_len = inlen & 63;
+    padded[..rem_len].copy_from_slice(&input[inlen - rem_len..inlen]);
+    padded[rem_len] = 0x80;
+
+    if rem_len < 56 {
+        store_bigendian_64(&mut padded[56..64], bytes << 3);
+        crypto_hashblocks_sha256(state, &padded[..64]);
+    } else {
+        store_bigendian_64(&mut padded[120..128], bytes << 3);
+        crypto_hashblocks_sha256(state, &padded);
+    }
+
+    out[..32].copy_from_slice(&state[..32]);
+}
+
+/// Length of `inlen` bytes once SHA-256 padding has been appended.
+pub const fn sha256_padded_len(inlen: usize) -> usize {
+    (inlen + 9).div_ceil(SPX_SHA256_BLOCK_BYTES) * SPX_SHA256_BLOCK_BYTES
+}
+
+/// Like `sha256_inc_finalize`, but writes the padding directly behind the
+/// input in `buf`, so the tail is not copied into a separate padding block.
+/// `buf` must hold at least `sha256_padded_len(inlen)` bytes.
+pub fn sha256_inc_finalize_in_place(
+    out: &mut [u8],
+    state: &mut [u8],
+    buf: &mut [u8],
+    inlen: usize,
+) {
+    let bytes = load_bigendian_64(&state[32..40]) + inlen as u64;
+    let padded_len = sha256_padded_len(inlen);
+
+    buf[inlen] = 0x80;
+    buf[inlen + 1..padded_len - 8].fill(0);
+    store_bigendian_64(&mut buf[padded_len - 8..padded_len], bytes << 3);
+    crypto_hashblocks_sha256(state, &buf[..padded_len]);
+
+    out[..32].copy_from_slice(&state[..32]);
+}
+
+pub fn sha512_inc_finalize(out: &mut [u8], state: &mut [u8], input: &[u8], inlen: usize) {
+    let mut padded = [0u8; 256];
+    let bytes = load_bigendian_64(&state[64..72]) + inlen as u64;
+
+    crypto_hashblocks_sha512(state, &input[..inlen & !127]);
+
+    let rem_len = inlen & 127;
+    padded[..rem_len].copy_from_slice(&input[inlen - rem_len..inlen]);
+    padded[rem_len] = 0x80;
+
+    // The 128-bit length field never needs its upper half here.
+    if rem_len < 112 {
+        padded[119] = (bytes >> 61) as u8;
+        store_bigendian_64(&mut padded[120..128], bytes << 3);
+        crypto_hashblocks_sha512(state, &padded[..128]);
+    } else {
+        padded[247] = (bytes >> 61) as u8;
+        store_bigendian_64(&mut padded[248..256], bytes << 3);
+        crypto_hashblocks_sha512(state, &padded);
+    }
+
+    out[..64].copy_from_slice(&state[..64]);
+}
+
+pub fn sha256(out: &mut [u8], input: &[u8], inlen: usize) {
+    let mut state = [0u8; 40];
+    sha256_inc_init(&mut state);
+    sha256_inc_finalize(out, &mut state, input, inlen);
+}
+
+pub fn sha512(out: &mut [u8], input: &[u8], inlen: usize) {
+    let mut state = [0u8; 72];
+    sha512_inc_init(&mut state);
+    sha512_inc_finalize(out, &mut state, input, inlen);
+}
+
+/// mgf1 function based on the SHA-256 hash function
+/// Note that inlen should be sufficiently small that it still allows for
+/// an array to be allocated on the stack. Typically 'input' is merely a seed.
+/// Outputs outlen number of bytes
+pub fn mgf1_256(out: &mut [u8], outlen: usize, input: &[u8], inlen: usize) {
+    mgf1::<SPX_SHA256_OUTPUT_BYTES>(out, outlen, input, inlen, sha256);
+}
+
+/// mgf1 function based on the SHA-512 hash function
+pub fn mgf1_512(out: &mut [u8], outlen: usize, input: &[u8], inlen: usize) {
+    mgf1::<SPX_SHA512_OUTPUT_BYTES>(out, outlen, input, inlen, sha512);
+}
+
+fn mgf1<const OUTPUT_BYTES: usize>(
+    out: &mut [u8],
+    outlen: usize,
+    input: &[u8],
+    inlen: usize,
+    hash: fn(&mut [u8], &[u8], usize),
+) {
+    const MAX_INLEN: usize = 2 * SPX_MAX_N + SPX_SHA512_OUTPUT_BYTES;
+    let mut inbuf = [0u8; MAX_INLEN + 4];
+    let mut outbuf = [0u8; SPX_SHA512_OUTPUT_BYTES];
+
+    inbuf[..inlen].copy_from_slice(&input[..inlen]);
+
+    // While we can fit in at least another full block of SHA output..
+    let mut i = 0;
+    let mut idx = 0;
+    while (i + 1) * OUTPUT_BYTES <= outlen {
+        u32_to_bytes(&mut inbuf[inlen..], i as u32);
+        hash(&mut outbuf, &inbuf, inlen + 4);
+        out[idx..idx + OUTPUT_BYTES].copy_from_slice(&outbuf[..OUTPUT_BYTES]);
+        idx += OUTPUT_BYTES;
+        i += 1;
+    }
+    // Until we cannot anymore, and we fill the remainder.
+    if outlen > i * OUTPUT_BYTES {
+        u32_to_bytes(&mut inbuf[inlen..], i as u32);
+        hash(&mut outbuf, &inbuf, inlen + 4);
+        let end = outlen - i * OUTPUT_BYTES;
+        out[idx..idx + end].copy_from_slice(&outbuf[..end]);
+    }
+}
+
+/// Absorb the constant pub_seed using one round of the compression function
+/// This initializes state_seeded and, for n > 16, state_seeded_512, which can
+/// then be reused in thash
+pub fn seed_state<P: SpxParams>(ctx: &mut SpxCtx) {
+    let mut block = [0u8; SPX_SHA512_BLOCK_BYTES];
+
+    block[..P::N].copy_from_slice(&ctx.pub_seed[..P::N]);
+
+    // block has been properly initialized for both SHA-256 and SHA-512
+    sha256_inc_init(&mut ctx.state_seeded);
+    sha256_inc_blocks(&mut ctx.state_seeded, &block, 1);
+
+    if P::N >= 24 {
+        sha512_inc_init(&mut ctx.state_seeded_512);
+        sha512_inc_blocks(&mut ctx.state_seeded_512, &block, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const SPX_N: usize = 16;
+
+    #[test]
+    fn sha256_finalize() {
+        let buf = [
+            0, 46, 130, 247, 82, 182, 99, 36, 30, 6, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 4, 124, 153,
+            53, 160, 176, 118, 148, 170, 12, 109, 16, 228, 219, 107, 26, 221,
+        ];
+        let mut sha2_state = [
+            20, 22, 52, 101, 4, 22, 68, 118, 244, 194, 114, 161, 208, 242, 205, 126, 223, 57, 106,
+            139, 71, 255, 239, 55, 65, 254, 4, 118, 170, 37, 3, 106, 0, 0, 0, 0, 0, 0, 0, 64,
+        ];
+        let mut outbuf = [0u8; SPX_SHA256_OUTPUT_BYTES];
+        let expected = [
+            151, 41, 244, 77, 28, 0, 51, 80, 20, 166, 116, 190, 217, 139, 37, 105, 21, 55, 45, 28,
+            40, 232, 167, 118, 61, 28, 222, 215, 214, 154, 24, 82,
+        ];
+        sha256_inc_finalize(
+            &mut outbuf,
+            &mut sha2_state,
+            &buf,
+            SPX_SHA256_ADDR_BYTES + SPX_N,
+        );
+        assert_eq!(outbuf, expected);
+    }
+
+    #[cfg(feature = "risc0")]
+    #[test]
+    fn risc0_compress_matches_software() {
+        let input: [u8; 11 * SPX_SHA256_BLOCK_BYTES] = core::array::from_fn(|i| i as u8);
+        for inblocks in [0, 1, 2, 8, 9, 11] {
+            let mut soft = [0u8; 40];
+            let mut risc0 = [0u8; 40];
+            sha256_inc_init(&mut soft);
+            sha256_inc_init(&mut risc0);
+            hashblocks_sha256_soft(&mut soft, &input[..inblocks * SPX_SHA256_BLOCK_BYTES]);
+            hashblocks_sha256_risc0(&mut risc0, &input[..inblocks * SPX_SHA256_BLOCK_BYTES]);
+            assert_eq!(soft, risc0, "{inblocks} blocks");
+        }
+    }
+
+    #[test]
+    fn sha512_abc() {
+        let mut out = [0u8; SPX_SHA512_OUTPUT_BYTES];
+        sha512(&mut out, b"abc", 3);
+        assert_eq!(
+            out[..16],
+            [
+                0xdd, 0xaf, 0x35, 0xa1, 0x93, 0x61, 0x7a, 0xba, 0xcc, 0x41, 0x73, 0x49, 0xae, 0x20,
+                0x41, 0x31,
+            ]
+        );
+        assert_eq!(
+            out[48..],
+            [
+                0x45, 0x4d, 0x44, 0x23, 0x64, 0x3c, 0xe8, 0x0e, 0x2a, 0x9a, 0xc9, 0x4f, 0xa5, 0x4c,
+                0xa4, 0x9f,
+            ]
+        );
+    }
+}
@@ -1,100 +1,227 @@
 use crate::address::*;
-use crate::api::SigError;
+use crate::api::{Phase, SigError};
 use crate::context::SpxCtx;
 use crate::fors::*;
 use crate::hash::*;
+use crate::merkle::*;
 use crate::params::*;
 use crate::thash::*;
 use crate::utils::*;
 use crate::wots::*;
 
+/// Generates an SPX key pair given a seed of length SEED_BYTES
+/// Format sk: [SK_SEED || SK_PRF || PUB_SEED || root]
+/// Format pk: [PUB_SEED || root]
+pub fn crypto_sign_seed_keypair<P: SpxParams>(pk: &mut [u8], sk: &mut [u8], seed: &[u8]) {
+    let mut ctx = SpxCtx::default();
+
+    // Initialize SK_SEED, SK_PRF and PUB_SEED from seed.
+    sk[..P::SEED_BYTES].copy_from_slice(&seed[..P::SEED_BYTES]);
+
+    pk[..P::N].copy_from_slice(&sk[2 * P::N..3 * P::N]);
+
+    ctx.pub_seed[..P::N].copy_from_slice(&pk[..P::N]);
+    ctx.sk_seed[..P::N].copy_from_slice(&sk[..P::N]);
+
+    // This hook allows the hash function instantiation to do whatever
+    // preparation or computation it needs, based on the public seed.
+    initialize_hash_function::<P>(&mut ctx);
+
+    // Compute root node of the top-most subtree.
+    merkle_gen_root::<P>(&mut sk[3 * P::N..], &ctx);
+
+    pk[P::N..P::PK_BYTES].copy_from_slice(&sk[3 * P::N..4 * P::N]);
+}
+
+/// Returns an array containing a detached signature.
+/// optrand is the N-byte randomization value mixed into R; signing is
+/// deterministic when it is set to PUB_SEED.
+pub fn crypto_sign_signature<P: SpxParams>(sig: &mut [u8], m: &[u8], sk: &[u8], optrand: &[u8]) {
+    let mut ctx = SpxCtx::default();
+
+    let sk_prf = &sk[P::N..2 * P::N];
+    let pk = &sk[2 * P::N..4 * P::N];
+
+    let mut mhash = [0u8; SPX_MAX_FORS_MSG_BYTES];
+    let mut root = [0u8; SPX_MAX_N];
+    let mut r = [0u8; SPX_MAX_N];
+    let mut tree = 0u64;
+    let mut idx_leaf = 0u32;
+    let (mut wots_addr, mut tree_addr) = ([0u8; SPX_ADDR_BYTES], [0u8; SPX_ADDR_BYTES]);
+    let mut idx = 0usize;
+
+    ctx.sk_seed[..P::N].copy_from_slice(&sk[..P::N]);
+    ctx.pub_seed[..P::N].copy_from_slice(&pk[..P::N]);
+
+    // This hook allows the hash function instantiation to do whatever
+    // preparation or computation it needs, based on the public seed.
+    initialize_hash_function::<P>(&mut ctx);
+
+    set_type::<P>(&mut wots_addr, SPX_ADDR_TYPE_WOTS);
+    set_type::<P>(&mut tree_addr, SPX_ADDR_TYPE_HASHTREE);
+
+    // Compute the digest randomization value.
+    gen_message_random::<P>(&mut r, sk_prf, optrand, m, m.len(), &ctx);
+    sig[..P::N].copy_from_slice(&r[..P::N]);
+
+    // Derive the message digest and leaf index from R, PK and M.
+    hash_message::<P>(
+        &mut mhash,
+        &mut tree,
+        &mut idx_leaf,
+        &r,
+        pk,
+        m,
+        m.len(),
+        &ctx,
+    );
+    idx += P::N;
+
+    set_tree_addr::<P>(&mut wots_addr, tree);
+    set_keypair_addr::<P>(&mut wots_addr, idx_leaf);
+
+    // Sign the message hash using FORS.
+    fors_sign::<P>(&mut sig[idx..], &mut root, &mhash, &ctx, &wots_addr);
+    idx += P::FORS_BYTES;
+
+    for i in 0..P::D {
+        set_layer_addr::<P>(&mut tree_addr, i as u32);
+        set_tree_addr::<P>(&mut tree_addr, tree);
+
+        copy_subtree_addr::<P>(&mut wots_addr, &tree_addr);
+        set_keypair_addr::<P>(&mut wots_addr, idx_leaf);
+
+        merkle_sign::<P>(
+            &mut sig[idx..],
+            &mut root,
+            &ctx,
+            &wots_addr,
+            &mut tree_addr,
+            idx_leaf,
+        );
+        idx += P::WOTS_BYTES + P::TREE_HEIGHT * P::N;
+
+        // Update the indices for the next layer.
+        idx_leaf = (tree & ((1 << P::TREE_HEIGHT) - 1)) as u32;
+        tree >>= P::TREE_HEIGHT;
+    }
+}
+
 /// Verifies a detached signature and message under a given public key.
-pub fn crypto_sign_verify(sig: &[u8], msg: &[u8], pk: &[u8]) -> Result<(), SigError> {
-    if sig.len() != SPX_BYTES {
-        return Err(SigError::Input);
+pub fn crypto_sign_verify<P: SpxParams>(sig: &[u8], msg: &[u8], pk: &[u8]) -> Result<(), SigError> {
+    crypto_sign_verify_traced::<P>(sig, msg, pk, &mut |_| {})
+}
+
+/// Same as [`crypto_sign_verify`], calling `mark` as each [`Phase`] of the
+/// verification starts.
+pub fn crypto_sign_verify_traced<P: SpxParams>(
+    sig: &[u8],
+    msg: &[u8],
+    pk: &[u8],
+    mark: &mut dyn FnMut(Phase),
+) -> Result<(), SigError> {
+    if pk.len() != P::PK_BYTES {
+        return Err(SigError::BadPublicKeyLength {
+            expected: P::PK_BYTES,
+            actual: pk.len(),
+        });
+    }
+    if sig.len() != P::BYTES {
+        return Err(SigError::BadSignatureLength {
+            expected: P::BYTES,
+            actual: sig.len(),
+        });
     }
 
     let mut ctx = SpxCtx::default();
-    let pub_root: &[u8] = &pk[SPX_N..];
-    let mut mhash = [0u8; SPX_FORS_MSG_BYTES];
-    let mut wots_pk = [0u8; SPX_WOTS_BYTES];
-    let (mut root, mut leaf) = ([0u8; SPX_N], [0u8; SPX_N]);
+    let pub_root: &[u8] = &pk[P::N..P::PK_BYTES];
+    let mut mhash = [0u8; SPX_MAX_FORS_MSG_BYTES];
+    let mut wots_pk = [0u8; SPX_MAX_WOTS_BYTES];
+    let (mut root, mut leaf) = ([0u8; SPX_MAX_N], [0u8; SPX_MAX_N]);
     let mut tree = 0u64;
     let mut idx_leaf = 0u32;
-    let (mut wots_addr, mut tree_addr, mut wots_pk_addr) = ([0u32; 8], [0u32; 8], [0u32; 8]);
+    let (mut wots_addr, mut tree_addr, mut wots_pk_addr) = (
+        [0u8; SPX_ADDR_BYTES],
+        [0u8; SPX_ADDR_BYTES],
+        [0u8; SPX_ADDR_BYTES],
+    );
     let mut idx = 0usize;
 
-    ctx.pub_seed[..].copy_from_slice(&pk[..SPX_N]);
+    ctx.pub_seed[..P::N].copy_from_slice(&pk[..P::N]);
 
     // This hook allows the hash function instantiation to do whatever
     // preparation or computation it needs, based on the public seed.
-    initialize_hash_function(&mut ctx);
+    initialize_hash_function::<P>(&mut ctx);
 
-    set_type(&mut wots_addr, SPX_ADDR_TYPE_WOTS);
-    set_type(&mut tree_addr, SPX_ADDR_TYPE_HASHTREE);
-    set_type(&mut wots_pk_addr, SPX_ADDR_TYPE_WOTSPK);
+    set_type::<P>(&mut wots_addr, SPX_ADDR_TYPE_WOTS);
+    set_type::<P>(&mut tree_addr, SPX_ADDR_TYPE_HASHTREE);
+    set_type::<P>(&mut wots_pk_addr, SPX_ADDR_TYPE_WOTSPK);
 
     // Derive the message digest and leaf index from R || PK || M.
-    // The additional SPX_N is a result of the hash domain separator.
-    hash_message(
+    // The additional N is a result of the hash domain separator.
+    mark(Phase::HashMessage);
+    hash_message::<P>(
         &mut mhash,
         &mut tree,
         &mut idx_leaf,
         sig,
         pk,
-        &msg,
+        msg,
         msg.len(),
         &ctx,
     );
-    idx += SPX_N;
+    idx += P::N;
 
     // Layer correctly defaults to 0, so no need to set_layer_addr
-    set_tree_addr(&mut wots_addr, tree);
-    set_keypair_addr(&mut wots_addr, idx_leaf);
+    set_tree_addr::<P>(&mut wots_addr, tree);
+    set_keypair_addr::<P>(&mut wots_addr, idx_leaf);
 
-    fors_pk_from_sig(&mut root, &sig[idx..], &mhash, &ctx, &mut wots_addr);
-    idx += SPX_FORS_BYTES;
+    mark(Phase::Fors);
+    fors_pk_from_sig::<P>(&mut root, &sig[idx..], &mhash, &ctx, &mut wots_addr);
+    idx += P::FORS_BYTES;
 
     // For each subtree..
-    for i in 0..SPX_D {
-        set_layer_addr(&mut tree_addr, i as u32);
-        set_tree_addr(&mut tree_addr, tree);
-        copy_subtree_addr(&mut wots_addr, &mut tree_addr);
-        set_keypair_addr(&mut wots_addr, idx_leaf);
+    for i in 0..P::D {
+        mark(Phase::Layer(i));
+        set_layer_addr::<P>(&mut tree_addr, i as u32);
+        set_tree_addr::<P>(&mut tree_addr, tree);
+        copy_subtree_addr::<P>(&mut wots_addr, &tree_addr);
+        set_keypair_addr::<P>(&mut wots_addr, idx_leaf);
 
-        copy_keypair_addr(&mut wots_pk_addr, &mut wots_addr);
+        copy_keypair_addr::<P>(&mut wots_pk_addr, &wots_addr);
 
         // The WOTS public key is only correct if the signature was correct.
         // Initially, root is the FORS pk, but on subsequent iterations it is
         // the root of the subtree below the currently processed subtree.
-        wots_pk_from_sig(&mut wots_pk, &sig[idx..], &root, &ctx, &mut wots_addr);
-        idx += SPX_WOTS_BYTES;
+        wots_pk_from_sig::<P>(&mut wots_pk, &sig[idx..], &root, &ctx, &mut wots_addr);
+        idx += P::WOTS_BYTES;
 
         // Compute the leaf node using the WOTS public key.
-        thash::<SPX_WOTS_LEN>(&mut leaf, Some(&wots_pk), &ctx, &wots_pk_addr);
+        thash::<P>(&mut leaf, Some(&wots_pk), P::WOTS_LEN, &ctx, &wots_pk_addr);
 
         // Compute the root node of this subtree.
-        compute_root(
+        compute_root::<P>(
             &mut root,
             &leaf,
             idx_leaf,
             0,
             &sig[idx..],
-            SPX_TREE_HEIGHT as u32,
+            P::TREE_HEIGHT as u32,
             &ctx,
             &mut tree_addr,
         );
-        idx += SPX_TREE_HEIGHT * SPX_N;
+        idx += P::TREE_HEIGHT * P::N;
 
         // Update the indices for the next layer.
-        idx_leaf = (tree & ((1 << SPX_TREE_HEIGHT) - 1)) as u32;
-        tree = tree >> SPX_TREE_HEIGHT;
+        idx_leaf = (tree & ((1 << P::TREE_HEIGHT) - 1)) as u32;
+        tree >>= P::TREE_HEIGHT;
     }
 
     // Check if the root node equals the root node in the public key.
-    if root != pub_root {
-        return Err(SigError::Verify);
+    mark(Phase::Done);
+    if root[..P::N] != *pub_root {
+        return Err(SigError::RootMismatch);
     }
 
-    return Ok(());
+    Ok(())
 }
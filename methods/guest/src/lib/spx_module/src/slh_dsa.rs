@@ -0,0 +1,262 @@
+//! The external interface of SLH-DSA (FIPS 205 section 10).
+//!
+//! `slh_sign` and `slh_verify` prefix the message with a domain separator and
+//! a context string of at most 255 bytes; `hash_slh_sign` and
+//! `hash_slh_verify` (HashSLH-DSA) sign the OID and digest of a pre-hashed
+//! message instead. Both then run the internal algorithms, which is what `sign`
+//! and `vrfy` compute for the `slh-dsa-*` parameter sets.
+
+use crate::api::{sign_with_optrand, vrfy, vrfy_traced, Keypair, Phase, SigError};
+use crate::params::ParamSet;
+use alloc::vec::Vec;
+use rand_core::CryptoRngCore;
+
+/// Hash function a message is digested with before HashSLH-DSA signs it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PreHash {
+    #[cfg(feature = "sha2")]
+    Sha256,
+    #[cfg(feature = "sha2")]
+    Sha512,
+    #[cfg(feature = "shake")]
+    Shake128,
+    #[cfg(feature = "shake")]
+    Shake256,
+}
+
+impl PreHash {
+    /// Every pre-hash function enabled by the crate features.
+    pub const ALL: &'static [PreHash] = &[
+        #[cfg(feature = "sha2")]
+        PreHash::Sha256,
+        #[cfg(feature = "sha2")]
+        PreHash::Sha512,
+        #[cfg(feature = "shake")]
+        PreHash::Shake128,
+        #[cfg(feature = "shake")]
+        PreHash::Shake256,
+    ];
+
+    /// Name of the function, e.g. `sha256`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "sha2")]
+            PreHash::Sha256 => "sha256",
+            #[cfg(feature = "sha2")]
+            PreHash::Sha512 => "sha512",
+            #[cfg(feature = "shake")]
+            PreHash::Shake128 => "shake128",
+            #[cfg(feature = "shake")]
+            PreHash::Shake256 => "shake256",
+        }
+    }
+
+    /// Looks a pre-hash function up by its name, e.g. `sha256`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ph| ph.name() == name)
+    }
+
+    /// Last byte of the DER encoded OID; every supported function lives under
+    /// 2.16.840.1.101.3.4.2.
+    const fn oid_suffix(self) -> u8 {
+        match self {
+            #[cfg(feature = "sha2")]
+            PreHash::Sha256 => 0x01,
+            #[cfg(feature = "sha2")]
+            PreHash::Sha512 => 0x03,
+            #[cfg(feature = "shake")]
+            PreHash::Shake128 => 0x0b,
+            #[cfg(feature = "shake")]
+            PreHash::Shake256 => 0x0c,
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "sha2", feature = "shake")), allow(unused_variables))]
+    fn digest(self, msg: &[u8], out: &mut Vec<u8>) {
+        match self {
+            #[cfg(feature = "sha2")]
+            PreHash::Sha256 => {
+                let mut digest = [0u8; crate::sha2::SPX_SHA256_OUTPUT_BYTES];
+                crate::sha2::sha256(&mut digest, msg, msg.len());
+                out.extend_from_slice(&digest);
+            }
+            #[cfg(feature = "sha2")]
+            PreHash::Sha512 => {
+                let mut digest = [0u8; crate::sha2::SPX_SHA512_OUTPUT_BYTES];
+                crate::sha2::sha512(&mut digest, msg, msg.len());
+                out.extend_from_slice(&digest);
+            }
+            #[cfg(feature = "shake")]
+            PreHash::Shake128 => xof::<sha3::Shake128>(msg, 32, out),
+            #[cfg(feature = "shake")]
+            PreHash::Shake256 => xof::<sha3::Shake256>(msg, 64, out),
+        }
+    }
+}
+
+#[cfg(feature = "shake")]
+fn xof<X>(msg: &[u8], len: usize, out: &mut Vec<u8>)
+where
+    X: Default + sha3::digest::Update + sha3::digest::ExtendableOutput,
+{
+    use sha3::digest::XofReader;
+
+    let mut hasher = X::default();
+    hasher.update(msg);
+    let start = out.len();
+    out.resize(start + len, 0);
+    hasher.finalize_xof().read(&mut out[start..]);
+}
+
+/// Builds M' = 0 || |ctx| || ctx || M
+fn pure_message(msg: &[u8], ctx: &[u8]) -> Result<Vec<u8>, SigError> {
+    let ctx_len =
+        u8::try_from(ctx.len()).map_err(|_| SigError::BadContextLength { actual: ctx.len() })?;
+    let mut m_prime = Vec::with_capacity(2 + ctx.len() + msg.len());
+    m_prime.extend_from_slice(&[0, ctx_len]);
+    m_prime.extend_from_slice(ctx);
+    m_prime.extend_from_slice(msg);
+    Ok(m_prime)
+}
+
+/// Builds M' = 1 || |ctx| || ctx || OID || PH(M)
+fn pre_hash_message(msg: &[u8], ctx: &[u8], ph: PreHash) -> Result<Vec<u8>, SigError> {
+    let ctx_len =
+        u8::try_from(ctx.len()).map_err(|_| SigError::BadContextLength { actual: ctx.len() })?;
+    let mut m_prime = Vec::with_capacity(2 + ctx.len() + 11 + 64);
+    m_prime.extend_from_slice(&[1, ctx_len]);
+    m_prime.extend_from_slice(ctx);
+    m_prime.extend_from_slice(&[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02]);
+    m_prime.push(ph.oid_suffix());
+    ph.digest(msg, &mut m_prime);
+    Ok(m_prime)
+}
+
+fn slh_dsa_only(params: ParamSet) -> Result<(), SigError> {
+    match params.is_slh_dsa() {
+        true => Ok(()),
+        false => Err(SigError::UnsupportedParams(params)),
+    }
+}
+
+fn sign_message(
+    m_prime: &[u8],
+    keypair: &Keypair,
+    rng: Option<&mut dyn CryptoRngCore>,
+) -> Result<Vec<u8>, SigError> {
+    let n = keypair.params.public_key_bytes() / 2;
+    match rng {
+        Some(rng) => {
+            let mut addrnd = alloc::vec![0u8; n];
+            rng.fill_bytes(&mut addrnd);
+            sign_with_optrand(m_prime, keypair, &addrnd)
+        }
+        // The deterministic variant uses PK.seed as opt_rand
+        None => sign_with_optrand(m_prime, keypair, &keypair.public[..n]),
+    }
+}
+
+/// Signs `msg` under the context string `ctx` with deterministic SLH-DSA
+///
+/// Fails if `keypair` is not an `slh-dsa-*` key or `ctx` exceeds 255 bytes.
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// # #[cfg(feature = "sha2")] {
+/// let keys = keypair(ParamSet::SlhDsa_Sha2_128s, &mut rand::thread_rng());
+/// let sig = slh_sign(b"message", b"context", &keys);
+/// assert!(sig.is_ok());
+/// # }
+/// ```
+pub fn slh_sign(msg: &[u8], ctx: &[u8], keypair: &Keypair) -> Result<Vec<u8>, SigError> {
+    slh_dsa_only(keypair.params)?;
+    sign_message(&pure_message(msg, ctx)?, keypair, None)
+}
+
+/// Signs `msg` under the context string `ctx` with hedged SLH-DSA, drawing
+/// the additional randomness from `rng`
+pub fn slh_sign_randomized(
+    msg: &[u8],
+    ctx: &[u8],
+    keypair: &Keypair,
+    rng: &mut dyn CryptoRngCore,
+) -> Result<Vec<u8>, SigError> {
+    slh_dsa_only(keypair.params)?;
+    sign_message(&pure_message(msg, ctx)?, keypair, Some(rng))
+}
+
+/// Signs the `ph` digest of `msg` under the context string `ctx` with
+/// deterministic HashSLH-DSA
+pub fn hash_slh_sign(
+    msg: &[u8],
+    ctx: &[u8],
+    ph: PreHash,
+    keypair: &Keypair,
+) -> Result<Vec<u8>, SigError> {
+    slh_dsa_only(keypair.params)?;
+    sign_message(&pre_hash_message(msg, ctx, ph)?, keypair, None)
+}
+
+/// Signs the `ph` digest of `msg` under the context string `ctx` with hedged
+/// HashSLH-DSA, drawing the additional randomness from `rng`
+pub fn hash_slh_sign_randomized(
+    msg: &[u8],
+    ctx: &[u8],
+    ph: PreHash,
+    keypair: &Keypair,
+    rng: &mut dyn CryptoRngCore,
+) -> Result<Vec<u8>, SigError> {
+    slh_dsa_only(keypair.params)?;
+    sign_message(&pre_hash_message(msg, ctx, ph)?, keypair, Some(rng))
+}
+
+/// Verifies an SLH-DSA signature on `msg` under the context string `ctx`
+///
+/// Example:
+/// ```no_run
+/// # use spx_sm3::*;
+/// # #[cfg(feature = "sha2")] {
+/// # let keys = keypair(ParamSet::SlhDsa_Sha2_128s, &mut rand::thread_rng());
+/// # let sig = slh_sign(b"message", b"context", &keys).ok().unwrap();
+/// let sig_verify = slh_verify(keys.params, &sig, b"message", b"context", &keys.public);
+/// assert!(sig_verify.is_ok());
+/// # }
+/// ```
+pub fn slh_verify(
+    params: ParamSet,
+    sig: &[u8],
+    msg: &[u8],
+    ctx: &[u8],
+    pk: &[u8],
+) -> Result<(), SigError> {
+    slh_dsa_only(params)?;
+    vrfy(params, sig, &pure_message(msg, ctx)?, pk)
+}
+
+/// Same as [`slh_verify`], calling `mark` as each [`Phase`] starts
+pub fn slh_verify_traced(
+    params: ParamSet,
+    sig: &[u8],
+    msg: &[u8],
+    ctx: &[u8],
+    pk: &[u8],
+    mark: &mut dyn FnMut(Phase),
+) -> Result<(), SigError> {
+    slh_dsa_only(params)?;
+    vrfy_traced(params, sig, &pure_message(msg, ctx)?, pk, mark)
+}
+
+/// Verifies a HashSLH-DSA signature on the `ph` digest of `msg` under the
+/// context string `ctx`
+pub fn hash_slh_verify(
+    params: ParamSet,
+    sig: &[u8],
+    msg: &[u8],
+    ctx: &[u8],
+    ph: PreHash,
+    pk: &[u8],
+) -> Result<(), SigError> {
+    slh_dsa_only(params)?;
+    vrfy(params, sig, &pre_hash_message(msg, ctx, ph)?, pk)
+}
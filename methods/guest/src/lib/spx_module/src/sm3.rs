@@ -1,7 +1,6 @@
 use crate::context::SpxCtx;
 use crate::params::*;
 use crate::utils::*;
-use core::convert::TryInto;
 
 pub const SPX_SM3_BLOCK_BYTES: usize = 64;
 pub const SPX_SM3_OUTPUT_BYTES: usize = 32; /* This does not necessarily equal SPX_N */
@@ -19,172 +18,114 @@ const IV_256: [u8; 32] = [
     0xB0, 0xFB, 0x0E, 0x4E, //H
 ];
 
-pub fn load_bigendian_32(x: &[u8]) -> u32 {
-    u32::from_be_bytes(x[..4].try_into().unwrap())
-}
-
-pub fn load_bigendian_64(x: &[u8]) -> u64 {
-    u64::from_be_bytes(x[..8].try_into().unwrap())
-}
-
-pub fn store_bigendian_32(x: &mut [u8], u: u32) {
-    x[..4].copy_from_slice(&u.to_be_bytes());
-}
-
-pub fn store_bigendian_64(x: &mut [u8], u: u64) {
-    x[..8].copy_from_slice(&u.to_be_bytes());
-}
-
-#[inline]
-fn rotl(x: u32, n: i32) -> u32 {
-    x.rotate_left(n as u32)
-}
-
-#[inline]
+#[inline(always)]
 #[allow(non_snake_case)]
 fn P0(x: u32) -> u32 {
-    x ^ rotl(x, 9) ^ rotl(x, 17)
+    x ^ x.rotate_left(9) ^ x.rotate_left(17)
 }
 
-#[inline]
+#[inline(always)]
 #[allow(non_snake_case)]
 fn P1(x: u32) -> u32 {
-    x ^ rotl(x, 15) ^ rotl(x, 23)
+    x ^ x.rotate_left(15) ^ x.rotate_left(23)
 }
 
-#[inline]
-#[allow(non_snake_case)]
-fn FF(x: u32, y: u32, z: u32, j: i32) -> u32 {
-    if j < 16 {
-        x ^ y ^ z
-    } else {
-        (x & y) | (x & z) | (y & z)
+/// Round constants T_j <<< j, so that no rotation is needed inside the rounds.
+const T_ROTL: [u32; 64] = {
+    let mut t = [0u32; 64];
+    let mut j = 0;
+    while j < 64 {
+        let tj: u32 = if j < 16 { 0x79cc4519 } else { 0x7a879d8a };
+        t[j] = tj.rotate_left(j as u32 % 32);
+        j += 1;
     }
-}
-
-#[inline]
+    t
+};
+
+/// Compresses a single 64 byte block into the chaining value.
+///
+/// The message expansion keeps only the 68 words W_j; W'_j = W_j ^ W_{j+4} is
+/// formed inside the rounds instead of in a second array, and the first 16
+/// rounds are split off so FF/GG need no branch on j.
 #[allow(non_snake_case)]
-fn GG(x: u32, y: u32, z: u32, j: i32) -> u32 {
-    if j < 16 {
-        x ^ y ^ z
-    } else {
-        (x & y) | ((!x) & z)
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 68];
+    for (w, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes(bytes.try_into().unwrap());
     }
-}
-
-#[inline]
-#[allow(non_snake_case)]
-fn T(j: i32) -> u32 {
-    if j < 16 {
-        0x79cc4519
-    } else {
-        0x7a879d8a
+    for i in 16..68 {
+        w[i] = P1(w[i - 16] ^ w[i - 9] ^ w[i - 3].rotate_left(15))
+            ^ w[i - 13].rotate_left(7)
+            ^ w[i - 6];
     }
-}
 
-// 分块哈希
-#[allow(non_snake_case)]
-pub fn crypto_hashblocks_sm3(statebytes: &mut [u8], mut input: &[u8]) {
-    let mut state = [0u32; 8];
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
 
-    let mut a = load_bigendian_32(&statebytes[0..4]);
-    state[0] = a;
-    let mut b = load_bigendian_32(&statebytes[4..8]);
-    state[1] = b;
-    let mut c = load_bigendian_32(&statebytes[8..12]);
-    state[2] = c;
-    let mut d = load_bigendian_32(&statebytes[12..16]);
-    state[3] = d;
-    let mut e = load_bigendian_32(&statebytes[16..20]);
-    state[4] = e;
-    let mut f = load_bigendian_32(&statebytes[20..24]);
-    state[5] = f;
-    let mut g = load_bigendian_32(&statebytes[24..28]);
-    state[6] = g;
-    let mut h = load_bigendian_32(&statebytes[28..32]);
-    state[7] = h;
-
-    let mut w = [0u32; 68];
-    let mut W = [0u32; 64];
-
-    while input.len() >= 64 {
-        a = state[0];
-        b = state[1];
-        c = state[2];
-        d = state[3];
-        e = state[4];
-        f = state[5];
-        g = state[6];
-        h = state[7];
-
-        for i in 0..16 {
-            w[i] = load_bigendian_32(&input[i * 4..(i + 1) * 4]);
-        }
-        for i in 16..68 {
-            w[i] = P1(w[i - 16] ^ w[i - 9] ^ rotl(w[i - 3], 15)) ^ rotl(w[i - 13], 7) ^ w[i - 6];
-        }
-        for i in 0..64 {
-            W[i] = w[i] ^ w[i + 4];
-        }
-
-        for j in 0..64 {
-            let SS1 = rotl(rotl(a, 12).wrapping_add(e).wrapping_add(rotl(T(j), j)), 7);
-            let SS2 = SS1 ^ rotl(a, 12);
-            let TT1 = FF(a, b, c, j)
+    macro_rules! round {
+        ($j:expr, $ff:expr, $gg:expr) => {
+            let a12 = a.rotate_left(12);
+            let SS1 = a12.wrapping_add(e).wrapping_add(T_ROTL[$j]).rotate_left(7);
+            let SS2 = SS1 ^ a12;
+            let TT1 = $ff
                 .wrapping_add(d)
                 .wrapping_add(SS2)
-                .wrapping_add(W[j as usize]);
-            let TT2 = GG(e, f, g, j)
-                .wrapping_add(h)
-                .wrapping_add(SS1)
-                .wrapping_add(w[j as usize]);
+                .wrapping_add(w[$j] ^ w[$j + 4]);
+            let TT2 = $gg.wrapping_add(h).wrapping_add(SS1).wrapping_add(w[$j]);
             d = c;
-            c = rotl(b, 9);
+            c = b.rotate_left(9);
             b = a;
             a = TT1;
             h = g;
-            g = rotl(f, 19);
+            g = f.rotate_left(19);
             f = e;
             e = P0(TT2);
-        }
-
-        state[0] ^= a;
-        state[1] ^= b;
-        state[2] ^= c;
-        state[3] ^= d;
-        state[4] ^= e;
-        state[5] ^= f;
-        state[6] ^= g;
-        state[7] ^= h;
-
-        input = &input[64..];
+        };
+    }
+
+    for j in 0..16 {
+        round!(j, a ^ b ^ c, e ^ f ^ g);
+    }
+    for j in 16..64 {
+        round!(j, (a & b) | (a & c) | (b & c), (e & f) | (!e & g));
     }
 
-    store_bigendian_32(&mut statebytes[0..4], state[0]);
-    store_bigendian_32(&mut statebytes[4..8], state[1]);
-    store_bigendian_32(&mut statebytes[8..12], state[2]);
-    store_bigendian_32(&mut statebytes[12..16], state[3]);
-    store_bigendian_32(&mut statebytes[16..20], state[4]);
-    store_bigendian_32(&mut statebytes[20..24], state[5]);
-    store_bigendian_32(&mut statebytes[24..28], state[6]);
-    store_bigendian_32(&mut statebytes[28..32], state[7]);
+    state[0] ^= a;
+    state[1] ^= b;
+    state[2] ^= c;
+    state[3] ^= d;
+    state[4] ^= e;
+    state[5] ^= f;
+    state[6] ^= g;
+    state[7] ^= h;
 }
 
-pub fn sm3_inc_init(state: &mut [u8]) {
-    // state 前 32 字节存哈希结果，后 8 字节存已处理的消息的长度
-    for i in 0..32 {
-        state[i] = IV_256[i];
+// 分块哈希：状态只在首尾各转换一次，中间逐块直接在输入上压缩
+pub fn crypto_hashblocks_sm3(statebytes: &mut [u8], input: &[u8]) {
+    let mut state = [0u32; 8];
+    for i in 0..8 {
+        state[i] = load_bigendian_32(&statebytes[i * 4..]);
     }
-    for i in 32..40 {
-        state[i] = 0;
+
+    for block in input.chunks_exact(SPX_SM3_BLOCK_BYTES) {
+        compress(&mut state, block);
+    }
+
+    for i in 0..8 {
+        store_bigendian_32(&mut statebytes[i * 4..], state[i]);
     }
 }
 
+pub fn sm3_inc_init(state: &mut [u8]) {
+    // state 前 32 字节存哈希结果，后 8 字节存已处理的消息的长度
+    state[..32].copy_from_slice(&IV_256);
+    state[32..40].fill(0);
+}
+
 pub fn sm3_inc_blocks(state: &mut [u8], input: &[u8], inblocks: usize) {
     let mut bytes = load_bigendian_64(&state[32..40]);
 
-    crypto_hashblocks_sm3(state, &input[..64 * inblocks]);
-    bytes += (64 * inblocks) as u64;
+    crypto_hashblocks_sm3(state, &input[..SPX_SM3_BLOCK_BYTES * inblocks]);
+    bytes += (SPX_SM3_BLOCK_BYTES * inblocks) as u64;
 
     store_bigendian_64(&mut state[32..40], bytes);
 }
@@ -205,31 +146,12 @@ pub fn sm3_inc_finalize(out: &mut [u8], state: &mut [u8], input: &[u8], inlen: u
     // 填充开始时先填一个 0x80
     padded[rem_len] = 0x80;
 
+    // 填充区已清零，只需写入比特长度
     if rem_len < 56 {
-        for i in rem_len + 1..56 {
-            padded[i] = 0;
-        }
-        padded[56] = (bytes >> 53) as u8;
-        padded[57] = (bytes >> 45) as u8;
-        padded[58] = (bytes >> 37) as u8;
-        padded[59] = (bytes >> 29) as u8;
-        padded[60] = (bytes >> 21) as u8;
-        padded[61] = (bytes >> 13) as u8;
-        padded[62] = (bytes >> 5) as u8;
-        padded[63] = (bytes << 3) as u8;
+        store_bigendian_64(&mut padded[56..64], bytes << 3);
         crypto_hashblocks_sm3(state, &padded[..64]);
     } else {
-        for i in rem_len + 1..120 {
-            padded[i] = 0;
-        }
-        padded[120] = (bytes >> 53) as u8;
-        padded[121] = (bytes >> 45) as u8;
-        padded[122] = (bytes >> 37) as u8;
-        padded[123] = (bytes >> 29) as u8;
-        padded[124] = (bytes >> 21) as u8;
-        padded[125] = (bytes >> 13) as u8;
-        padded[126] = (bytes >> 5) as u8;
-        padded[127] = (bytes << 3) as u8;
+        store_bigendian_64(&mut padded[120..128], bytes << 3);
         crypto_hashblocks_sm3(state, &padded);
     }
 
@@ -237,37 +159,56 @@ pub fn sm3_inc_finalize(out: &mut [u8], state: &mut [u8], input: &[u8], inlen: u
     out[..32].copy_from_slice(&state[..32]);
 }
 
+/// Length of `inlen` bytes once SM3 padding has been appended.
+pub const fn sm3_padded_len(inlen: usize) -> usize {
+    (inlen + 9).div_ceil(SPX_SM3_BLOCK_BYTES) * SPX_SM3_BLOCK_BYTES
+}
+
+/// Like `sm3_inc_finalize`, but writes the padding directly behind the input
+/// in `buf`, so the tail is not copied into a separate padding block.
+/// `buf` must hold at least `sm3_padded_len(inlen)` bytes.
+pub fn sm3_inc_finalize_in_place(out: &mut [u8], state: &mut [u8], buf: &mut [u8], inlen: usize) {
+    let bytes = load_bigendian_64(&state[32..40]) + inlen as u64;
+    let padded_len = sm3_padded_len(inlen);
+
+    buf[inlen] = 0x80;
+    buf[inlen + 1..padded_len - 8].fill(0);
+    store_bigendian_64(&mut buf[padded_len - 8..padded_len], bytes << 3);
+    crypto_hashblocks_sm3(state, &buf[..padded_len]);
+
+    out[..32].copy_from_slice(&state[..32]);
+}
+
 pub fn sm3(out: &mut [u8], input: &[u8], inlen: usize) {
     let mut state = [0u8; 40];
     sm3_inc_init(&mut state);
     sm3_inc_finalize(out, &mut state, input, inlen);
 }
 
-/// mgf1 function based on the SHA-256 hash function
+/// mgf1 function based on the SM3 hash function
 /// Note that inlen should be sufficiently small that it still allows for
 /// an array to be allocated on the stack. Typically 'input' is merely a seed.
 /// Outputs outlen number of bytes
-#[cfg(feature = "robust")]
-pub fn mgf1_256(out: &mut [u8], outlen: usize, input: &[u8]) {
-    const INLEN: usize = SPX_N + SPX_SM3_ADDR_BYTES;
-    let mut inbuf = [0u8; INLEN + 4];
+pub fn mgf1_256(out: &mut [u8], outlen: usize, input: &[u8], inlen: usize) {
+    const MAX_INLEN: usize = 2 * SPX_MAX_N + SPX_SM3_OUTPUT_BYTES;
+    let mut inbuf = [0u8; MAX_INLEN + 4];
     let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
 
-    inbuf[..INLEN].copy_from_slice(&input[..INLEN]);
+    inbuf[..inlen].copy_from_slice(&input[..inlen]);
 
     // While we can fit in at least another full block of SM3 output..
     let mut i = 0;
     let mut idx = 0;
     while (i + 1) * SPX_SM3_OUTPUT_BYTES <= outlen {
-        u32_to_bytes(&mut inbuf[INLEN..], i as u32);
-        sm3(&mut out[idx..], &inbuf, INLEN + 4);
+        u32_to_bytes(&mut inbuf[inlen..], i as u32);
+        sm3(&mut out[idx..], &inbuf, inlen + 4);
         idx += SPX_SM3_OUTPUT_BYTES;
         i += 1;
     }
     // Until we cannot anymore, and we fill the remainder.
     if outlen > i * SPX_SM3_OUTPUT_BYTES {
-        u32_to_bytes(&mut inbuf[INLEN..], i as u32);
-        sm3(&mut outbuf, &inbuf, INLEN + 4);
+        u32_to_bytes(&mut inbuf[inlen..], i as u32);
+        sm3(&mut outbuf, &inbuf, inlen + 4);
         let end = outlen - i * SPX_SM3_OUTPUT_BYTES;
         out[idx..idx + end].copy_from_slice(&outbuf[..end]);
     }
@@ -276,70 +217,36 @@ pub fn mgf1_256(out: &mut [u8], outlen: usize, input: &[u8]) {
 /// Absorb the constant pub_seed using one round of the compression function
 /// This initializes state_seeded , which can then be
 /// reused input thash
-pub fn seed_state(ctx: &mut SpxCtx) {
+pub fn seed_state<P: SpxParams>(ctx: &mut SpxCtx) {
     let mut block = [0u8; SPX_SM3_BLOCK_BYTES];
 
-    block[..SPX_N].copy_from_slice(&ctx.pub_seed[..SPX_N]);
-    block[SPX_N..SPX_SM3_BLOCK_BYTES].fill(0);
+    block[..P::N].copy_from_slice(&ctx.pub_seed[..P::N]);
 
-    // block has been properly initialized for SHA-256
     sm3_inc_init(&mut ctx.state_seeded);
     sm3_inc_blocks(&mut ctx.state_seeded, &block, 1);
 }
 
-// TODO: Refactor and get rid of code duplication
-// inlen / buffer size is the only difference
-pub fn mgf1_256_2(out: &mut [u8], outlen: usize, input: &[u8]) {
-    const INLEN: usize = 2 * SPX_N + SPX_SM3_OUTPUT_BYTES;
-    let mut inbuf = [0u8; INLEN + 4];
-    let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
-
-    inbuf[..INLEN].copy_from_slice(&input[..INLEN]);
-
-    // While we can fit in at least another full block of SM3 output..
-    let mut i = 0;
-    let mut idx = 0;
-    while (i + 1) * SPX_SM3_OUTPUT_BYTES <= outlen {
-        u32_to_bytes(&mut inbuf[INLEN..], i as u32);
-        sm3(&mut out[idx..], &inbuf, INLEN + 4);
-        idx += SPX_SM3_OUTPUT_BYTES;
-        i += 1;
-    }
-    // Until we cannot anymore, and we fill the remainder.
-    if outlen > i * SPX_SM3_OUTPUT_BYTES {
-        u32_to_bytes(&mut inbuf[INLEN..], i as u32);
-        sm3(&mut outbuf, &inbuf, INLEN + 4);
-        let end = outlen - i * SPX_SM3_OUTPUT_BYTES;
-        out[idx..idx + end].copy_from_slice(&outbuf[..end]);
-    }
-}
-
 // TODO: mfg1 tests instead
 #[cfg(test)]
-#[cfg(all(feature = "sm3", feature = "f128", feature = "robust"))]
 mod tests {
     use super::*;
+
+    /// GB/T 32905-2016, appendix A.1: the digest of "abc".
     #[test]
     fn sm3_finalize() {
-        let buf = [
-            0, 46, 130, 247, 82, 182, 99, 36, 30, 6, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 4, 124, 153,
-            53, 160, 176, 118, 148, 170, 12, 109, 16, 228, 219, 107, 26, 221,
-        ];
-        let mut sm3_state = [
-            20, 22, 52, 101, 4, 22, 68, 118, 244, 194, 114, 161, 208, 242, 205, 126, 223, 57, 106,
-            139, 71, 255, 239, 55, 65, 254, 4, 118, 170, 37, 3, 106, 0, 0, 0, 0, 0, 0, 0, 64,
-        ];
-        let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
         let expected = [
-            151, 41, 244, 77, 28, 0, 51, 80, 20, 166, 116, 190, 217, 139, 37, 105, 21, 55, 45, 28,
-            40, 232, 167, 118, 61, 28, 222, 215, 214, 154, 24, 82,
+            0x66, 0xc7, 0xf0, 0xf4, 0x62, 0xee, 0xed, 0xd9, 0xd1, 0xf2, 0xd4, 0x6b, 0xdc, 0x10,
+            0xe4, 0xe2, 0x41, 0x67, 0xc4, 0x87, 0x5c, 0xf2, 0xf7, 0xa2, 0x29, 0x7d, 0xa0, 0x2b,
+            0x8f, 0x4b, 0xa8, 0xe0,
         ];
-        sm3_inc_finalize(
-            &mut outbuf,
-            &mut sm3_state,
-            &buf,
-            SPX_SM3_ADDR_BYTES + SPX_N,
-        );
+        let mut sm3_state = [0u8; 8 + SPX_SM3_OUTPUT_BYTES];
+        let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
+        sm3_inc_init(&mut sm3_state);
+        sm3_inc_finalize(&mut outbuf, &mut sm3_state, b"abc", 3);
+        assert_eq!(outbuf, expected);
+
+        let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
+        sm3(&mut outbuf, b"abc", 3);
         assert_eq!(outbuf, expected);
     }
 }
@@ -1,10 +1,40 @@
-#[cfg(all(feature = "sm3", feature = "robust"))]
+#[cfg(feature = "sha2")]
+mod sha2_robust;
+#[cfg(feature = "sha2")]
+mod sha2_simple;
+#[cfg(feature = "shake")]
+mod shake_robust;
+#[cfg(feature = "shake")]
+mod shake_simple;
+#[cfg(feature = "sm3")]
 mod sm3_robust;
-#[cfg(all(feature = "sm3", feature = "simple"))]
+#[cfg(feature = "sm3")]
 mod sm3_simple;
 
-#[cfg(all(feature = "sm3", feature = "simple"))]
-pub use sm3_simple::*;
+use crate::context::SpxCtx;
+use crate::params::{HashFunction, SpxParams};
 
-#[cfg(all(feature = "sm3", feature = "robust"))]
-pub use sm3_robust::*;
+/// Takes an array of inblocks concatenated arrays of N bytes.
+/// When input is None the input is read from out.
+pub fn thash<P: SpxParams>(
+    out: &mut [u8],
+    input: Option<&[u8]>,
+    inblocks: usize,
+    ctx: &SpxCtx,
+    addr: &[u8],
+) {
+    match (P::HASH, P::ROBUST) {
+        #[cfg(feature = "sm3")]
+        (HashFunction::Sm3, true) => sm3_robust::thash::<P>(out, input, inblocks, ctx, addr),
+        #[cfg(feature = "sm3")]
+        (HashFunction::Sm3, false) => sm3_simple::thash::<P>(out, input, inblocks, ctx, addr),
+        #[cfg(feature = "sha2")]
+        (HashFunction::Sha2, true) => sha2_robust::thash::<P>(out, input, inblocks, ctx, addr),
+        #[cfg(feature = "sha2")]
+        (HashFunction::Sha2, false) => sha2_simple::thash::<P>(out, input, inblocks, ctx, addr),
+        #[cfg(feature = "shake")]
+        (HashFunction::Shake, true) => shake_robust::thash::<P>(out, input, inblocks, ctx, addr),
+        #[cfg(feature = "shake")]
+        (HashFunction::Shake, false) => shake_simple::thash::<P>(out, input, inblocks, ctx, addr),
+    }
+}
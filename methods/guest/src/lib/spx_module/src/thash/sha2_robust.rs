@@ -0,0 +1,60 @@
+use crate::{context::SpxCtx, params::*, sha2::*};
+
+/// Takes an array of inblocks concatenated arrays of N bytes.
+/// For n > 16 only the F function (inblocks == 1) uses SHA-256; H and T_l
+/// use SHA-512, for both the bitmask and the hash itself.
+pub fn thash<P: SpxParams>(
+    out: &mut [u8],
+    input: Option<&[u8]>,
+    inblocks: usize,
+    ctx: &SpxCtx,
+    addr: &[u8],
+) {
+    let sha512 = P::N >= 24 && inblocks > 1;
+    let mut outbuf = [0u8; SPX_SHA512_OUTPUT_BYTES];
+    let mut buf = [0u8; SPX_MAX_N + sha256_padded_len(SPX_SHA256_ADDR_BYTES + SPX_MAX_WOTS_BYTES)];
+    let mut bitmask = [0u8; SPX_MAX_WOTS_BYTES];
+
+    buf[..P::N].copy_from_slice(&ctx.pub_seed[..P::N]);
+    buf[P::N..P::N + SPX_SHA256_ADDR_BYTES].copy_from_slice(&addr[..SPX_SHA256_ADDR_BYTES]);
+    if sha512 {
+        mgf1_512(
+            &mut bitmask,
+            inblocks * P::N,
+            &buf,
+            P::N + SPX_SHA256_ADDR_BYTES,
+        );
+    } else {
+        mgf1_256(
+            &mut bitmask,
+            inblocks * P::N,
+            &buf,
+            P::N + SPX_SHA256_ADDR_BYTES,
+        );
+    }
+
+    let input = input.unwrap_or(out);
+    for i in 0..inblocks * P::N {
+        buf[P::N + SPX_SHA256_ADDR_BYTES + i] = input[i] ^ bitmask[i];
+    }
+
+    // Retrieve precomputed state containing pub_seed
+    if sha512 {
+        let mut sha2_state = ctx.state_seeded_512;
+        sha512_inc_finalize(
+            &mut outbuf,
+            &mut sha2_state,
+            &buf[P::N..],
+            SPX_SHA256_ADDR_BYTES + inblocks * P::N,
+        );
+    } else {
+        let mut sha2_state = ctx.state_seeded;
+        sha256_inc_finalize_in_place(
+            &mut outbuf,
+            &mut sha2_state,
+            &mut buf[P::N..],
+            SPX_SHA256_ADDR_BYTES + inblocks * P::N,
+        );
+    }
+    out[..P::N].copy_from_slice(&outbuf[..P::N]);
+}
@@ -0,0 +1,39 @@
+use crate::{context::SpxCtx, params::*, sha2::*};
+
+/// Takes an array of inblocks concatenated arrays of N bytes.
+/// For n > 16 only the F function (inblocks == 1) uses SHA-256; H and T_l
+/// use SHA-512.
+pub fn thash<P: SpxParams>(
+    out: &mut [u8],
+    input: Option<&[u8]>,
+    inblocks: usize,
+    ctx: &SpxCtx,
+    addr: &[u8],
+) {
+    let mut outbuf = [0u8; SPX_SHA512_OUTPUT_BYTES];
+    let mut buf = [0u8; sha256_padded_len(SPX_SHA256_ADDR_BYTES + SPX_MAX_WOTS_BYTES)];
+
+    buf[..SPX_SHA256_ADDR_BYTES].copy_from_slice(&addr[..SPX_SHA256_ADDR_BYTES]);
+    buf[SPX_SHA256_ADDR_BYTES..SPX_SHA256_ADDR_BYTES + inblocks * P::N]
+        .copy_from_slice(&input.unwrap_or(out)[..inblocks * P::N]);
+
+    // Retrieve precomputed state containing pub_seed
+    if P::N >= 24 && inblocks > 1 {
+        let mut sha2_state = ctx.state_seeded_512;
+        sha512_inc_finalize(
+            &mut outbuf,
+            &mut sha2_state,
+            &buf,
+            SPX_SHA256_ADDR_BYTES + inblocks * P::N,
+        );
+    } else {
+        let mut sha2_state = ctx.state_seeded;
+        sha256_inc_finalize_in_place(
+            &mut outbuf,
+            &mut sha2_state,
+            &mut buf,
+            SPX_SHA256_ADDR_BYTES + inblocks * P::N,
+        );
+    }
+    out[..P::N].copy_from_slice(&outbuf[..P::N]);
+}
@@ -0,0 +1,31 @@
+use crate::{context::SpxCtx, params::*};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+
+/// Takes an array of inblocks concatenated arrays of N bytes.
+pub fn thash<P: SpxParams>(
+    out: &mut [u8],
+    input: Option<&[u8]>,
+    inblocks: usize,
+    ctx: &SpxCtx,
+    addr: &[u8],
+) {
+    let mut buf = [0u8; SPX_MAX_WOTS_BYTES];
+    let mut bitmask = [0u8; SPX_MAX_WOTS_BYTES];
+
+    let mut hasher = Shake256::default();
+    hasher.update(&ctx.pub_seed[..P::N]);
+    hasher.update(addr);
+    hasher.finalize_xof().read(&mut bitmask[..inblocks * P::N]);
+
+    let input = input.unwrap_or(out);
+    for i in 0..inblocks * P::N {
+        buf[i] = input[i] ^ bitmask[i];
+    }
+
+    let mut hasher = Shake256::default();
+    hasher.update(&ctx.pub_seed[..P::N]);
+    hasher.update(addr);
+    hasher.update(&buf[..inblocks * P::N]);
+    hasher.finalize_xof().read(&mut out[..P::N]);
+}
@@ -0,0 +1,18 @@
+use crate::{context::SpxCtx, params::*};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+
+/// Takes an array of inblocks concatenated arrays of N bytes.
+pub fn thash<P: SpxParams>(
+    out: &mut [u8],
+    input: Option<&[u8]>,
+    inblocks: usize,
+    ctx: &SpxCtx,
+    addr: &[u8],
+) {
+    let mut hasher = Shake256::default();
+    hasher.update(&ctx.pub_seed[..P::N]);
+    hasher.update(addr);
+    hasher.update(&input.unwrap_or(out)[..inblocks * P::N]);
+    hasher.finalize_xof().read(&mut out[..P::N]);
+}
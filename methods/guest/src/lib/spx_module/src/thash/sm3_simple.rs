@@ -1,26 +1,28 @@
-use crate::{context::SpxCtx, params::*, sm3::*, utils::*};
+use crate::{context::SpxCtx, params::*, sm3::*};
 
-/// Takes an array of inblocks concatenated arrays of SPX_N bytes.
-pub fn thash<const N: usize>(out: &mut [u8], input: Option<&[u8]>, ctx: &SpxCtx, addr: &[u32])
-where
-    [(); SPX_N + SPX_SM3_ADDR_BYTES + N * SPX_N]: Sized,
-{
+/// Takes an array of inblocks concatenated arrays of N bytes.
+pub fn thash<P: SpxParams>(
+    out: &mut [u8],
+    input: Option<&[u8]>,
+    inblocks: usize,
+    ctx: &SpxCtx,
+    addr: &[u8],
+) {
     let mut outbuf = [0u8; SPX_SM3_OUTPUT_BYTES];
-    let mut sm3_state = [0u8; 40];
-    let mut buf = [0u8; SPX_N + SPX_SM3_ADDR_BYTES + N * SPX_N];
+    let mut buf = [0u8; sm3_padded_len(SPX_SM3_ADDR_BYTES + SPX_MAX_WOTS_BYTES)];
 
     // Retrieve precomputed state containing pub_seed
-    sm3_state.copy_from_slice(&ctx.state_seeded);
+    let mut sm3_state = ctx.state_seeded;
 
-    buf[..SPX_SM3_ADDR_BYTES].copy_from_slice(&address_to_bytes(&addr)[..SPX_SM3_ADDR_BYTES]);
-    buf[SPX_SM3_ADDR_BYTES..SPX_SM3_ADDR_BYTES + N * SPX_N]
-        .copy_from_slice(&input.unwrap_or(out)[..N * SPX_N]);
+    buf[..SPX_SM3_ADDR_BYTES].copy_from_slice(&addr[..SPX_SM3_ADDR_BYTES]);
+    buf[SPX_SM3_ADDR_BYTES..SPX_SM3_ADDR_BYTES + inblocks * P::N]
+        .copy_from_slice(&input.unwrap_or(out)[..inblocks * P::N]);
 
-    sm3_inc_finalize(
+    sm3_inc_finalize_in_place(
         &mut outbuf,
         &mut sm3_state,
-        &buf,
-        SPX_SM3_ADDR_BYTES + N * SPX_N,
+        &mut buf,
+        SPX_SM3_ADDR_BYTES + inblocks * P::N,
     );
-    out[..SPX_N].copy_from_slice(&outbuf[..SPX_N]);
+    out[..P::N].copy_from_slice(&outbuf[..P::N]);
 }
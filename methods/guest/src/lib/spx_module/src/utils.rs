@@ -8,7 +8,7 @@ pub fn ull_to_bytes(out: &mut [u8], outlen: usize, mut input: u64) {
     // Iterate over out in decreasing order, for big-endianness.
     for i in (0..outlen).rev() {
         out[i] = (input & 0xff) as u8;
-        input = input >> 8;
+        input >>= 8;
     }
 }
 
@@ -19,18 +19,38 @@ pub fn u32_to_bytes(out: &mut [u8], input: u32) {
     out[3] = input as u8;
 }
 
+#[cfg(any(feature = "sm3", feature = "sha2"))]
+pub fn load_bigendian_32(x: &[u8]) -> u32 {
+    u32::from_be_bytes(x[..4].try_into().unwrap())
+}
+
+#[cfg(any(feature = "sm3", feature = "sha2"))]
+pub fn load_bigendian_64(x: &[u8]) -> u64 {
+    u64::from_be_bytes(x[..8].try_into().unwrap())
+}
+
+#[cfg(any(feature = "sm3", feature = "sha2"))]
+pub fn store_bigendian_32(x: &mut [u8], u: u32) {
+    x[..4].copy_from_slice(&u.to_be_bytes());
+}
+
+#[cfg(any(feature = "sm3", feature = "sha2"))]
+pub fn store_bigendian_64(x: &mut [u8], u: u64) {
+    x[..8].copy_from_slice(&u.to_be_bytes());
+}
+
 /// Converts the inlen bytes in 'in' from big-endian byte order to an integer.
 pub fn bytes_to_ull(input: &[u8], inlen: usize) -> u64 {
     let mut retval = 0u64;
     for i in 0..inlen {
         retval |= (input[i] as u64) << (8 * (inlen - 1 - i));
     }
-    return retval;
+    retval
 }
 
 /// Computes a root node given a leaf and an auth path.
 /// Expects address to be complete other than the tree_height and tree_index.
-pub fn compute_root(
+pub fn compute_root<P: SpxParams>(
     root: &mut [u8],
     leaf: &[u8],
     mut leaf_idx: u32,
@@ -38,61 +58,127 @@ pub fn compute_root(
     auth_path: &[u8],
     tree_height: u32,
     ctx: &SpxCtx,
-    addr: &mut [u32; 8],
+    addr: &mut [u8; SPX_ADDR_BYTES],
 ) {
-    let mut buffer = [0u8; 2 * SPX_N];
+    let mut buffer = [0u8; 2 * SPX_MAX_N];
     let mut idx = 0usize;
 
     // If leaf_idx is odd (last bit = 1), current path element is a right child
     // and auth_path has to go left. Otherwise it is the other way around.
     if (leaf_idx & 1) != 0 {
-        buffer[SPX_N..].copy_from_slice(&leaf[..SPX_N]);
-        buffer[..SPX_N].copy_from_slice(&auth_path[..SPX_N]);
+        buffer[P::N..2 * P::N].copy_from_slice(&leaf[..P::N]);
+        buffer[..P::N].copy_from_slice(&auth_path[..P::N]);
     } else {
-        buffer[..SPX_N].copy_from_slice(&leaf[..SPX_N]);
-        buffer[SPX_N..].copy_from_slice(&auth_path[..SPX_N]);
+        buffer[..P::N].copy_from_slice(&leaf[..P::N]);
+        buffer[P::N..2 * P::N].copy_from_slice(&auth_path[..P::N]);
     }
-    idx += SPX_N;
+    idx += P::N;
 
     for i in 0..(tree_height - 1) {
         leaf_idx >>= 1;
         idx_offset >>= 1;
         // Set the address of the node we're creating.
-        set_tree_height(addr, i + 1);
-        set_tree_index(addr, leaf_idx + idx_offset);
+        set_tree_height::<P>(addr, i + 1);
+        set_tree_index::<P>(addr, leaf_idx + idx_offset);
 
         // Pick the right or left neighbour, depending on parity of the node.
         if (leaf_idx & 1) != 0 {
-            let tmp_buffer = buffer.clone();
-            thash::<2>(&mut buffer[SPX_N..], Some(&tmp_buffer), ctx, addr);
-            buffer[..SPX_N].copy_from_slice(&auth_path[idx..][..SPX_N]);
+            let tmp_buffer = buffer;
+            thash::<P>(&mut buffer[P::N..], Some(&tmp_buffer), 2, ctx, addr);
+            buffer[..P::N].copy_from_slice(&auth_path[idx..][..P::N]);
         } else {
-            thash::<2>(&mut buffer, None, ctx, addr);
-            buffer[SPX_N..].copy_from_slice(&auth_path[idx..][..SPX_N]);
+            thash::<P>(&mut buffer, None, 2, ctx, addr);
+            buffer[P::N..2 * P::N].copy_from_slice(&auth_path[idx..][..P::N]);
         }
-        idx += SPX_N;
+        idx += P::N;
     }
 
     // The last iteration is exceptional; we do not copy an auth_path node.
     leaf_idx >>= 1;
     idx_offset >>= 1;
-    set_tree_height(addr, tree_height);
-    set_tree_index(addr, leaf_idx + idx_offset);
-    thash::<2>(root, Some(&buffer), ctx, addr);
+    set_tree_height::<P>(addr, tree_height);
+    set_tree_index::<P>(addr, leaf_idx + idx_offset);
+    thash::<P>(root, Some(&buffer), 2, ctx, addr);
 }
 
-pub fn bytes_to_address(addr: &mut [u32], bytes: &[u8; 32]) {
-    for i in 0..8 {
-        let mut addr_i = [0u8; 4];
-        addr_i.copy_from_slice(&bytes[i * 4..][..4]);
-        addr[i] = u32::from_ne_bytes(addr_i);
-    }
-}
+/// Generate the entire Merkle tree, computing the authentication path for
+/// leaf_idx, and the resulting root node using Merkle's TreeHash algorithm.
+/// Expects the layer and tree parts of the tree_addr to be set, as well as the
+/// tree type (i.e. SPX_ADDR_TYPE_HASHTREE or SPX_ADDR_TYPE_FORSTREE)
+///
+/// Applies the offset idx_offset to indices before building addresses, so that
+/// it is possible to continue counting indices across trees.
+pub fn treehash<P: SpxParams, F>(
+    root: &mut [u8],
+    auth_path: &mut [u8],
+    ctx: &SpxCtx,
+    leaf_idx: u32,
+    idx_offset: u32,
+    tree_height: u32,
+    mut gen_leaf: F,
+    tree_addr: &mut [u8; SPX_ADDR_BYTES],
+) where
+    F: FnMut(&mut [u8], &SpxCtx, u32),
+{
+    // This is where we keep the intermediate nodes
+    let mut stack = [0u8; SPX_MAX_TREE_HEIGHT * SPX_MAX_N];
+    let max_idx = (1u32 << tree_height) - 1;
+
+    let mut idx = 0u32;
+    loop {
+        // Current logical node is at index[N]. We do this to minimize
+        // the number of copies needed during a thash
+        let mut current = [0u8; 2 * SPX_MAX_N];
+        gen_leaf(&mut current[P::N..2 * P::N], ctx, idx + idx_offset);
+
+        // Now combine the freshly generated right node with previously
+        // generated left ones
+        let mut internal_idx_offset = idx_offset;
+        let mut internal_idx = idx;
+        let mut internal_leaf = leaf_idx;
+        let mut h = 0u32; // The height we are in the Merkle tree
+        loop {
+            // Check if we hit the top of the tree
+            if h == tree_height {
+                // We hit the root; return it
+                root[..P::N].copy_from_slice(&current[P::N..2 * P::N]);
+                return;
+            }
+
+            // Check if the node we have is a part of the
+            // authentication path; if it is, write it out
+            if (internal_idx ^ internal_leaf) == 0x01 {
+                auth_path[h as usize * P::N..][..P::N].copy_from_slice(&current[P::N..2 * P::N]);
+            }
+
+            // Check if we're at a left child; if so, stop going up the stack
+            // Exception: if we've reached the end of the tree, keep on going
+            // (so we combine the last 4 nodes into the one root node in two
+            // more iterations)
+            if (internal_idx & 1) == 0 && idx < max_idx {
+                break;
+            }
+
+            // Ok, we're at a right node
+            // Now combine the left and right logical nodes together
+
+            // Set the address of the node we're creating.
+            internal_idx_offset >>= 1;
+            set_tree_height::<P>(tree_addr, h + 1);
+            set_tree_index::<P>(tree_addr, internal_idx / 2 + internal_idx_offset);
+
+            current[..P::N].copy_from_slice(&stack[h as usize * P::N..][..P::N]);
+            let tmp_current = current;
+            thash::<P>(&mut current[P::N..], Some(&tmp_current), 2, ctx, tree_addr);
+
+            h += 1;
+            internal_idx >>= 1;
+            internal_leaf >>= 1;
+        }
 
-pub fn address_to_bytes(addr: &[u32]) -> [u8; 32] {
-    let mut out = [0u8; 32];
-    for i in 0..8 {
-        out[i * 4..][..4].copy_from_slice(&addr[i].to_ne_bytes()); //TODO: Check on BE in QEMU
+        // We've hit a left child; save the current for when we get the
+        // corresponding right right
+        stack[h as usize * P::N..][..P::N].copy_from_slice(&current[P::N..2 * P::N]);
+        idx += 1;
     }
-    out
 }
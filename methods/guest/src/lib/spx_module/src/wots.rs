@@ -1,5 +1,6 @@
 use crate::address::*;
 use crate::context::SpxCtx;
+use crate::hash::*;
 use crate::params::*;
 use crate::thash::*;
 use crate::utils::*;
@@ -12,21 +13,21 @@ use crate::utils::*;
 /// out and in have to be n-byte arrays.
 /// Interprets in as start-th value of the chain.
 /// addr has to contain the address of the chain.
-pub fn gen_chain(
+pub fn gen_chain<P: SpxParams>(
     out: &mut [u8],
     input: &[u8],
     start: u32,
     steps: u32,
     ctx: &SpxCtx,
-    addr: &mut [u32],
+    addr: &mut [u8],
 ) {
-    out[..SPX_N].copy_from_slice(&input[..SPX_N]);
+    out[..P::N].copy_from_slice(&input[..P::N]);
 
     // Iterate 'steps' calls to the hash function.
     let mut i = start;
-    while i < (start + steps) && i < SPX_WOTS_W as u32 {
-        set_hash_addr(addr, i);
-        thash::<1>(out, None, ctx, addr);
+    while i < (start + steps) && i < P::WOTS_W as u32 {
+        set_hash_addr::<P>(addr, i);
+        thash::<P>(out, None, 1, ctx, addr);
         i += 1;
     }
 }
@@ -34,7 +35,7 @@ pub fn gen_chain(
 /// base_w algorithm as described in draft.
 /// Interprets an array of bytes as integers in base w.
 /// This only works when log_w is a divisor of 8.
-pub fn base_w(output: &mut [u32], out_len: u32, input: &[u8]) {
+pub fn base_w<P: SpxParams>(output: &mut [u32], out_len: u32, input: &[u8]) {
     let mut idx = 0;
     let mut out = 0;
     let mut total = 0u8;
@@ -46,52 +47,58 @@ pub fn base_w(output: &mut [u32], out_len: u32, input: &[u8]) {
             idx += 1;
             bits += 8;
         }
-        bits -= SPX_WOTS_LOGW;
-        output[out] = ((total >> bits) & (SPX_WOTS_W - 1) as u8) as u32;
+        bits -= P::WOTS_LOGW;
+        output[out] = ((total >> bits) & (P::WOTS_W - 1) as u8) as u32;
         out += 1;
     }
 }
 
 /// Computes the WOTS+ checksum over a message (in base_w).
-pub fn wots_checksum(csum_base_w: &mut [u32]) {
+pub fn wots_checksum<P: SpxParams>(csum_base_w: &mut [u32]) {
     let mut csum = 0u32;
-    let mut csum_bytes = [0u8; (SPX_WOTS_LEN2 * SPX_WOTS_LOGW + 7) / 8];
+    let mut csum_bytes = [0u8; 4];
+    let csum_sizeof = (P::WOTS_LEN2 * P::WOTS_LOGW + 7) / 8;
 
     // Compute checksum.
-    for i in 0..SPX_WOTS_LEN1 {
-        csum += SPX_WOTS_W as u32 - 1 - csum_base_w[i] as u32;
+    for i in 0..P::WOTS_LEN1 {
+        csum += P::WOTS_W as u32 - 1 - csum_base_w[i];
     }
 
     // Convert checksum to base_w.
     // Make sure expected empty zero bits are the least significant bits.
-    csum = csum << ((8 - ((SPX_WOTS_LEN2 * SPX_WOTS_LOGW) % 8)) % 8);
-    let csum_sizeof = csum_bytes.len();
+    csum <<= (8 - ((P::WOTS_LEN2 * P::WOTS_LOGW) % 8)) % 8;
     ull_to_bytes(&mut csum_bytes, csum_sizeof, csum as u64);
-    base_w(
-        &mut csum_base_w[SPX_WOTS_LEN1..],
-        SPX_WOTS_LEN2 as u32,
+    base_w::<P>(
+        &mut csum_base_w[P::WOTS_LEN1..],
+        P::WOTS_LEN2 as u32,
         &csum_bytes,
     );
 }
 
 /// Takes a message and derives the matching chain lengths.
-pub fn chain_lengths(lengths: &mut [u32], msg: &[u8]) {
-    base_w(lengths, SPX_WOTS_LEN1 as u32, msg);
-    wots_checksum(lengths);
+pub fn chain_lengths<P: SpxParams>(lengths: &mut [u32], msg: &[u8]) {
+    base_w::<P>(lengths, P::WOTS_LEN1 as u32, msg);
+    wots_checksum::<P>(lengths);
 }
 
 /// Takes a WOTS signature and an n-byte message, computes a WOTS public key.
 /// Writes the computed public key to 'pk'.
-pub fn wots_pk_from_sig(pk: &mut [u8], sig: &[u8], msg: &[u8], ctx: &SpxCtx, addr: &mut [u32]) {
-    let mut lengths = [0u32; SPX_WOTS_LEN];
-    chain_lengths(&mut lengths, msg);
-
-    for i in 0..SPX_WOTS_LEN {
-        set_chain_addr(addr, i as u32);
-        let steps = SPX_WOTS_W as u32 - 1 - lengths[i];
-        gen_chain(
-            &mut pk[i * SPX_N..],
-            &sig[i * SPX_N..],
+pub fn wots_pk_from_sig<P: SpxParams>(
+    pk: &mut [u8],
+    sig: &[u8],
+    msg: &[u8],
+    ctx: &SpxCtx,
+    addr: &mut [u8],
+) {
+    let mut lengths = [0u32; SPX_MAX_WOTS_LEN];
+    chain_lengths::<P>(&mut lengths, msg);
+
+    for i in 0..P::WOTS_LEN {
+        set_chain_addr::<P>(addr, i as u32);
+        let steps = P::WOTS_W as u32 - 1 - lengths[i];
+        gen_chain::<P>(
+            &mut pk[i * P::N..],
+            &sig[i * P::N..],
             lengths[i],
             steps,
             ctx,
@@ -99,3 +106,73 @@ pub fn wots_pk_from_sig(pk: &mut [u8], sig: &[u8], msg: &[u8], ctx: &SpxCtx, add
         );
     }
 }
+
+/// State shared between the Merkle tree traversal and WOTS leaf generation.
+/// wots_sig is only written when the leaf with index wots_sign_leaf is
+/// generated.
+pub struct LeafInfo<'a> {
+    pub wots_sig: &'a mut [u8],
+    pub wots_sign_leaf: u32,
+    pub wots_steps: [u32; SPX_MAX_WOTS_LEN],
+    pub leaf_addr: [u8; SPX_ADDR_BYTES],
+    pub pk_addr: [u8; SPX_ADDR_BYTES],
+}
+
+/// This generates a WOTS public key
+/// It also generates the WOTS signature if leaf_info indicates
+/// that we're signing with this WOTS key
+pub fn wots_gen_leaf<P: SpxParams>(
+    dest: &mut [u8],
+    ctx: &SpxCtx,
+    leaf_idx: u32,
+    info: &mut LeafInfo,
+) {
+    let mut pk_buffer = [0u8; SPX_MAX_WOTS_BYTES];
+
+    // Set wots_k to the step if we're generating a signature, !0 if we're not
+    let wots_k_mask = if leaf_idx == info.wots_sign_leaf {
+        0
+    } else {
+        !0u32
+    };
+
+    set_keypair_addr::<P>(&mut info.leaf_addr, leaf_idx);
+    set_keypair_addr::<P>(&mut info.pk_addr, leaf_idx);
+
+    for i in 0..P::WOTS_LEN {
+        let buffer = &mut pk_buffer[i * P::N..][..P::N];
+        let wots_k = info.wots_steps[i] | wots_k_mask;
+
+        // Start with the secret seed
+        set_chain_addr::<P>(&mut info.leaf_addr, i as u32);
+        set_hash_addr::<P>(&mut info.leaf_addr, 0);
+        set_type::<P>(&mut info.leaf_addr, SPX_ADDR_TYPE_WOTSPRF);
+
+        prf_addr::<P>(buffer, ctx, &info.leaf_addr);
+
+        set_type::<P>(&mut info.leaf_addr, SPX_ADDR_TYPE_WOTS);
+
+        // Iterate down the WOTS chain
+        let mut k = 0u32;
+        loop {
+            // Check if this is the value that needs to be saved as a
+            // part of the WOTS signature
+            if k == wots_k {
+                info.wots_sig[i * P::N..][..P::N].copy_from_slice(buffer);
+            }
+
+            // Check if we hit the top of the chain
+            if k == P::WOTS_W as u32 - 1 {
+                break;
+            }
+
+            // Iterate one step on the chain
+            set_hash_addr::<P>(&mut info.leaf_addr, k);
+            thash::<P>(buffer, None, 1, ctx, &info.leaf_addr);
+            k += 1;
+        }
+    }
+
+    // Do the final thash to generate the public keys
+    thash::<P>(dest, Some(&pk_buffer), P::WOTS_LEN, ctx, &info.pk_addr);
+}
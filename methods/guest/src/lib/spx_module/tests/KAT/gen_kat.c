@@ -0,0 +1,228 @@
+/*
+ * Generates the known answer tests in this directory from the spxlibAPI C
+ * implementation. Build it once per parameter set and tree hash variant, e.g.
+ *
+ *   D=../../../../../../../MutipleWallet_frontend/spxlibAPI
+ *   for P in 128s 128f 192s 192f 256s 256f; do for T in simple robust; do
+ *     gcc -O3 -std=c99 -DPARAMS=sphincs-SM3-$P \
+ *       -DHASH=\"sm3\" -DMODE=\"$P\" -DTHASH=\"$T\" \
+ *       -I$D/include -I$D/include/hash -I$D/include/signature \
+ *       -I$D/include/params -I$D/include/utils -o gen_kat gen_kat.c \
+ *       $D/src/signature/{address,merkle,wots,wotsx1,fors,TSS_sign}.c \
+ *       $D/src/utils/{utils,utilsx1}.c \
+ *       $D/src/hash/{SM3,hash_SM3,thash_SM3_$T}.c
+ *     ./gen_kat
+ *   done; done
+ *
+ * which writes sphincs-sm3-<P>-<T>.rsp and sphincs-sm3-<P>-<T>.steps.
+ *
+ * The sha2 and shake simple sets come from the PQClean round 3.1 sources
+ * (as shipped in the pqcrypto-sphincsplus 0.7 crate) instead, built with
+ * -DPQCLEAN, e.g. for H in sha2 shake:
+ *
+ *   C=pqclean/common; S=pqclean/crypto_sign/sphincs-$H-$P-simple/clean
+ *   gcc -O3 -std=c99 -DPQCLEAN -DHASH=\"$H\" -DMODE=\"$P\" \
+ *     -DTHASH=\"simple\" -I$C -I$S -o gen_kat gen_kat.c $S/*.c \
+ *     $C/sha2.c $C/fips202.c
+ *
+ * PQClean carries no robust variants; those are produced by gen_kat.rs from
+ * the pqc_sphincsplus 0.2 crate, after checking that it reproduces the simple
+ * vectors above.
+ *
+ * The .rsp file follows the NIST layout, except that `seed` is the keypair
+ * seed handed to tss_crypto_sign_seed_keypair rather than a DRBG seed, and
+ * signatures are deterministic (optrand = PK.seed).
+ *
+ * The .steps file holds the intermediate values of tss_crypto_sign_verify for
+ * each test, one hex buffer per line: the message digest, the hypertree index,
+ * the leaf index, the FORS public key, the bottom layer WOTS public key and
+ * the root of every layer concatenated.
+ */
+#include <stdio.h>
+#include <string.h>
+#include <stdint.h>
+
+#ifdef PQCLEAN
+#include "api.h"
+#include "context.h"
+#else
+#include "TSS_api.h"
+#endif
+#include "address.h"
+#include "fors.h"
+#include "hash.h"
+#include "thash.h"
+#include "utils.h"
+#include "wots.h"
+
+#define KATNUM 2
+
+#ifdef PQCLEAN
+#define tss_crypto_sign_seed_keypair SPX_NAMESPACE(crypto_sign_seed_keypair)
+#define crypto_sign SPX_NAMESPACE(crypto_sign)
+#define tss_crypto_sign_verify SPX_NAMESPACE(crypto_sign_verify)
+#define CRYPTO_SEEDBYTES (3 * SPX_N)
+#define CRYPTO_PUBLICKEYBYTES SPX_PK_BYTES
+#define CRYPTO_SECRETKEYBYTES SPX_SK_BYTES
+typedef size_t smlen_t;
+#else
+typedef unsigned long long smlen_t;
+#endif
+
+static unsigned char optrand[SPX_N];
+
+#ifdef PQCLEAN
+int PQCLEAN_randombytes(uint8_t *x, size_t xlen)
+{
+    memcpy(x, optrand, xlen);
+    return 0;
+}
+#else
+void randombytes(unsigned char *x, unsigned long long xlen)
+{
+    memcpy(x, optrand, xlen);
+}
+#endif
+
+static uint32_t xorshift32(uint32_t *state)
+{
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    return *state;
+}
+
+static void fill(unsigned char *buf, size_t len, uint32_t *state)
+{
+    for (size_t i = 0; i < len; i++) {
+        buf[i] = (unsigned char)xorshift32(state);
+    }
+}
+
+static void hex(FILE *fp, const char *label, const unsigned char *buf, size_t len)
+{
+    if (label) {
+        fprintf(fp, "%s = ", label);
+    }
+    for (size_t i = 0; i < len; i++) {
+        fprintf(fp, "%02X", buf[i]);
+    }
+    fprintf(fp, "\n");
+}
+
+static void steps(FILE *fp, const unsigned char *sig, const unsigned char *m,
+                  size_t mlen, const unsigned char *pk)
+{
+    spx_ctx ctx;
+    unsigned char mhash[SPX_FORS_MSG_BYTES];
+    unsigned char wots_pk[SPX_WOTS_BYTES];
+    unsigned char bottom_wots_pk[SPX_WOTS_BYTES];
+    unsigned char roots[SPX_D * SPX_N];
+    unsigned char root[SPX_N];
+    unsigned char leaf[SPX_N];
+    unsigned char buf[8];
+    uint64_t tree;
+    uint32_t idx_leaf;
+    uint32_t wots_addr[8] = {0};
+    uint32_t tree_addr[8] = {0};
+    uint32_t wots_pk_addr[8] = {0};
+
+    memcpy(ctx.pub_seed, pk, SPX_N);
+    initialize_hash_function(&ctx);
+
+    set_type(wots_addr, SPX_ADDR_TYPE_WOTS);
+    set_type(tree_addr, SPX_ADDR_TYPE_HASHTREE);
+    set_type(wots_pk_addr, SPX_ADDR_TYPE_WOTSPK);
+
+    hash_message(mhash, &tree, &idx_leaf, sig, pk, m, mlen, &ctx);
+    hex(fp, NULL, mhash, SPX_FORS_MSG_BYTES);
+    ull_to_bytes(buf, 8, tree);
+    hex(fp, NULL, buf, 8);
+    ull_to_bytes(buf, 4, idx_leaf);
+    hex(fp, NULL, buf, 4);
+    sig += SPX_N;
+
+    set_tree_addr(wots_addr, tree);
+    set_keypair_addr(wots_addr, idx_leaf);
+
+    fors_pk_from_sig(root, sig, mhash, &ctx, wots_addr);
+    hex(fp, NULL, root, SPX_N);
+    sig += SPX_FORS_BYTES;
+
+    for (unsigned int i = 0; i < SPX_D; i++) {
+        set_layer_addr(tree_addr, i);
+        set_tree_addr(tree_addr, tree);
+        copy_subtree_addr(wots_addr, tree_addr);
+        set_keypair_addr(wots_addr, idx_leaf);
+        copy_keypair_addr(wots_pk_addr, wots_addr);
+
+        wots_pk_from_sig(wots_pk, sig, root, &ctx, wots_addr);
+        if (i == 0) {
+            memcpy(bottom_wots_pk, wots_pk, SPX_WOTS_BYTES);
+        }
+        sig += SPX_WOTS_BYTES;
+
+        thash(leaf, wots_pk, SPX_WOTS_LEN, &ctx, wots_pk_addr);
+        compute_root(root, leaf, idx_leaf, 0, sig, SPX_TREE_HEIGHT, &ctx, tree_addr);
+        memcpy(roots + i * SPX_N, root, SPX_N);
+        sig += SPX_TREE_HEIGHT * SPX_N;
+
+        idx_leaf = (tree & ((1 << SPX_TREE_HEIGHT) - 1));
+        tree = tree >> SPX_TREE_HEIGHT;
+    }
+
+    hex(fp, NULL, bottom_wots_pk, SPX_WOTS_BYTES);
+    hex(fp, NULL, roots, SPX_D * SPX_N);
+}
+
+int main(void)
+{
+    const char *name = "sphincs-" HASH "-" MODE "-" THASH;
+    static unsigned char msg[33 * KATNUM];
+    static unsigned char sm[SPX_BYTES + 33 * KATNUM];
+    unsigned char seed[CRYPTO_SEEDBYTES];
+    unsigned char pk[CRYPTO_PUBLICKEYBYTES];
+    unsigned char sk[CRYPTO_SECRETKEYBYTES];
+    smlen_t smlen;
+    uint32_t state = 0x5350582b;
+    char path[64];
+    FILE *rsp, *stp;
+
+    snprintf(path, sizeof path, "%s.rsp", name);
+    rsp = fopen(path, "w");
+    snprintf(path, sizeof path, "%s.steps", name);
+    stp = fopen(path, "w");
+    if (!rsp || !stp) {
+        return 1;
+    }
+
+    fprintf(rsp, "# %s\n\n", name);
+    for (int i = 0; i < KATNUM; i++) {
+        size_t mlen = 33 * (i + 1);
+
+        fill(seed, sizeof seed, &state);
+        fill(msg, mlen, &state);
+        tss_crypto_sign_seed_keypair(pk, sk, seed);
+        memcpy(optrand, pk, SPX_N);
+        crypto_sign(sm, &smlen, msg, mlen, sk);
+        if (tss_crypto_sign_verify(sm, SPX_BYTES, msg, mlen, pk)) {
+            return 1;
+        }
+
+        fprintf(rsp, "count = %d\n", i);
+        hex(rsp, "seed", seed, sizeof seed);
+        fprintf(rsp, "mlen = %zu\n", mlen);
+        hex(rsp, "msg", msg, mlen);
+        hex(rsp, "pk", pk, sizeof pk);
+        hex(rsp, "sk", sk, sizeof sk);
+        fprintf(rsp, "smlen = %llu\n", (unsigned long long)smlen);
+        hex(rsp, "sm", sm, smlen);
+        fprintf(rsp, "\n");
+
+        steps(stp, sm, msg, mlen, pk);
+    }
+
+    fclose(rsp);
+    fclose(stp);
+    return 0;
+}
@@ -0,0 +1,140 @@
+// Generates the sha2 and shake robust known answer tests in this directory
+// from the pqc_sphincsplus 0.2 crate, whose simple sets reproduce the PQClean
+// vectors produced by gen_kat.c byte for byte. Copy this file into that
+// crate's src/, add `#[cfg(all(test, feature = "KAT"))] mod gen_kat;` to its
+// lib.rs and run, for each parameter set,
+//
+//   cargo +nightly test --release --lib gen_kat \
+//     --features KAT,<sha2|shake>,<s128|f128|...>,robust
+//
+// The output has the same layout as the one of gen_kat.c.
+
+extern crate std;
+use crate::{
+    address::*, context::SpxCtx, fors::*, hash::*, params::*, sign::*, thash::*, utils::*, wots::*,
+};
+use std::{fmt::Write as _, format, string::String, vec};
+
+fn xorshift32(s: &mut u32) -> u32 {
+    *s ^= *s << 13;
+    *s ^= *s >> 17;
+    *s ^= *s << 5;
+    *s
+}
+fn fill(b: &mut [u8], s: &mut u32) {
+    for x in b.iter_mut() {
+        *x = xorshift32(s) as u8;
+    }
+}
+fn hex(b: &[u8]) -> String {
+    let mut o = String::new();
+    for x in b {
+        write!(o, "{:02X}", x).unwrap();
+    }
+    o
+}
+
+fn steps(out: &mut String, sig: &[u8], m: &[u8], pk: &[u8]) {
+    let mut ctx = SpxCtx::default();
+    ctx.pub_seed.copy_from_slice(&pk[..SPX_N]);
+    initialize_hash_function(&mut ctx);
+    let (mut mhash, mut tree, mut idx_leaf) = ([0u8; SPX_FORS_MSG_BYTES], 0u64, 0u32);
+    let (mut wots_addr, mut tree_addr, mut wots_pk_addr) = ([0u32; 8], [0u32; 8], [0u32; 8]);
+    set_type(&mut wots_addr, SPX_ADDR_TYPE_WOTS);
+    set_type(&mut tree_addr, SPX_ADDR_TYPE_HASHTREE);
+    set_type(&mut wots_pk_addr, SPX_ADDR_TYPE_WOTSPK);
+    hash_message(
+        &mut mhash,
+        &mut tree,
+        &mut idx_leaf,
+        sig,
+        pk,
+        m,
+        m.len(),
+        &ctx,
+    );
+    let mut b = [0u8; 8];
+    writeln!(out, "{}", hex(&mhash)).unwrap();
+    ull_to_bytes(&mut b, 8, tree);
+    writeln!(out, "{}", hex(&b)).unwrap();
+    ull_to_bytes(&mut b, 4, idx_leaf as u64);
+    writeln!(out, "{}", hex(&b[..4])).unwrap();
+    let mut idx = SPX_N;
+    set_tree_addr(&mut wots_addr, tree);
+    set_keypair_addr(&mut wots_addr, idx_leaf);
+    let mut root = [0u8; SPX_N];
+    fors_pk_from_sig(&mut root, &sig[idx..], &mhash, &ctx, &mut wots_addr);
+    writeln!(out, "{}", hex(&root)).unwrap();
+    idx += SPX_FORS_BYTES;
+    let mut roots = vec![];
+    let mut bottom = vec![];
+    for i in 0..SPX_D {
+        set_layer_addr(&mut tree_addr, i as u32);
+        set_tree_addr(&mut tree_addr, tree);
+        copy_subtree_addr(&mut wots_addr, &mut tree_addr);
+        set_keypair_addr(&mut wots_addr, idx_leaf);
+        copy_keypair_addr(&mut wots_pk_addr, &wots_addr);
+        let mut wots_pk = [0u8; SPX_WOTS_BYTES];
+        wots_pk_from_sig(&mut wots_pk, &sig[idx..], &root, &ctx, &mut wots_addr);
+        if i == 0 {
+            bottom = wots_pk.to_vec();
+        }
+        idx += SPX_WOTS_BYTES;
+        let mut leaf = [0u8; SPX_N];
+        thash::<SPX_WOTS_LEN>(&mut leaf, Some(&wots_pk), &ctx, &wots_pk_addr);
+        compute_root(
+            &mut root,
+            &leaf,
+            idx_leaf,
+            0,
+            &sig[idx..],
+            SPX_TREE_HEIGHT as u32,
+            &ctx,
+            &mut tree_addr,
+        );
+        roots.extend_from_slice(&root);
+        idx += SPX_TREE_HEIGHT * SPX_N;
+        idx_leaf = (tree & ((1 << SPX_TREE_HEIGHT) - 1)) as u32;
+        tree >>= SPX_TREE_HEIGHT;
+    }
+    writeln!(out, "{}", hex(&bottom)).unwrap();
+    writeln!(out, "{}", hex(&roots)).unwrap();
+}
+
+#[test]
+fn gen_kat() {
+    let name = format!("sphincs-{}-{}-{}", HASH, MODE, THASH);
+    let mut rsp = format!("# {}\n\n", name);
+    let mut stp = String::new();
+    let mut state = 0x5350582bu32;
+    for i in 0..2 {
+        let mlen = 33 * (i + 1);
+        let mut seed = [0u8; CRYPTO_SEEDBYTES];
+        let mut msg = vec![0u8; mlen];
+        fill(&mut seed, &mut state);
+        fill(&mut msg, &mut state);
+        let (mut pk, mut sk) = ([0u8; SPX_PK_BYTES], [0u8; SPX_SK_BYTES]);
+        crypto_sign_keypair(&mut pk, &mut sk, Some(&seed));
+        let mut sig = vec![0u8; SPX_BYTES];
+        crypto_sign_signature(&mut sig, &msg, &sk, Some(&pk[..SPX_N]));
+        assert!(crypto_sign_verify(&sig, &msg, &pk).is_ok());
+        let mut sm = sig.clone();
+        sm.extend_from_slice(&msg);
+        write!(
+            rsp,
+            "count = {}\nseed = {}\nmlen = {}\nmsg = {}\npk = {}\nsk = {}\nsmlen = {}\nsm = {}\n\n",
+            i,
+            hex(&seed),
+            mlen,
+            hex(&msg),
+            hex(&pk),
+            hex(&sk),
+            sm.len(),
+            hex(&sm)
+        )
+        .unwrap();
+        steps(&mut stp, &sig, &msg, &pk);
+    }
+    std::fs::write(format!("{}.rsp", name), rsp).unwrap();
+    std::fs::write(format!("{}.steps", name), stp).unwrap();
+}
@@ -0,0 +1,118 @@
+// Generates the slh-dsa-<hash>-<mode>.rsp known answer tests in this directory
+// from the RustCrypto slh-dsa 0.1 crate (FIPS 205, checked against the NIST
+// ACVP vectors in its own test suite). Build it as a binary crate depending on
+// slh-dsa 0.1 (with signature pinned to 2.3.0-pre.4), sha2 0.10 and sha3 0.10
+// and run it from this directory.
+//
+// Each file holds a pure SLH-DSA signature with a context string, signed
+// deterministically, and a HashSLH-DSA signature with hedged randomness.
+// Seeds and messages come from the same xorshift32 stream as gen_kat.c.
+
+use sha2::{Digest, Sha256, Sha512};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake128, Shake256};
+use slh_dsa::*;
+use std::fmt::Write as _;
+
+fn xorshift32(s: &mut u32) -> u32 {
+    *s ^= *s << 13;
+    *s ^= *s >> 17;
+    *s ^= *s << 5;
+    *s
+}
+
+fn fill(len: usize, s: &mut u32) -> Vec<u8> {
+    (0..len).map(|_| xorshift32(s) as u8).collect()
+}
+
+fn hex(b: &[u8]) -> String {
+    b.iter().map(|x| format!("{:02X}", x)).collect()
+}
+
+fn xof<X: Default + Update + ExtendableOutput>(msg: &[u8], len: usize) -> Vec<u8> {
+    let mut h = X::default();
+    h.update(msg);
+    let mut out = vec![0u8; len];
+    h.finalize_xof().read(&mut out);
+    out
+}
+
+/// DER encoded OID and digest of the pre-hash functions of FIPS 205 table 11.
+fn pre_hash(ph: &str, msg: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let oid = |last: u8| {
+        vec![
+            0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, last,
+        ]
+    };
+    match ph {
+        "sha256" => (oid(0x01), Sha256::digest(msg).to_vec()),
+        "sha512" => (oid(0x03), Sha512::digest(msg).to_vec()),
+        "shake128" => (oid(0x0b), xof::<Shake128>(msg, 32)),
+        "shake256" => (oid(0x0c), xof::<Shake256>(msg, 64)),
+        _ => unreachable!(),
+    }
+}
+
+fn generate<P: ParameterSet>(name: &str, n: usize, ph: &str) {
+    let mut state = 0x5350582bu32;
+    let mut rsp = format!("# {}\n\n", name);
+    for count in 0..2 {
+        let seed = fill(3 * n, &mut state);
+        let mlen = 33 * (count + 1);
+        let msg = fill(mlen, &mut state);
+        let ctx = fill(16 * count + 8, &mut state);
+        let sk = SigningKey::<P>::slh_keygen_internal(&seed[..n], &seed[n..2 * n], &seed[2 * n..]);
+        let pk = AsRef::<VerifyingKey<P>>::as_ref(&sk).to_vec();
+
+        let (ph, addrnd, m_prime) = if count == 0 {
+            ("", vec![], [&[0, ctx.len() as u8], &ctx[..], &msg].concat())
+        } else {
+            let (oid, digest) = pre_hash(ph, &msg);
+            let addrnd = fill(n, &mut state);
+            (
+                ph,
+                addrnd,
+                [&[1, ctx.len() as u8], &ctx[..], &oid, &digest].concat(),
+            )
+        };
+        let sig = if addrnd.is_empty() {
+            sk.slh_sign_internal(&m_prime, None)
+        } else {
+            sk.slh_sign_internal(&m_prime, Some(&addrnd))
+        };
+        if count == 0 {
+            assert_eq!(sig, sk.try_sign_with_context(&msg, &ctx, None).unwrap());
+        }
+        assert!(AsRef::<VerifyingKey<P>>::as_ref(&sk)
+            .slh_verify_internal(&m_prime, &sig)
+            .is_ok());
+
+        writeln!(rsp, "count = {}", count).unwrap();
+        writeln!(rsp, "seed = {}", hex(&seed)).unwrap();
+        writeln!(rsp, "pk = {}", hex(&pk)).unwrap();
+        writeln!(rsp, "sk = {}", hex(&sk.to_vec())).unwrap();
+        writeln!(rsp, "ph = {}", ph).unwrap();
+        writeln!(rsp, "ctx = {}", hex(&ctx)).unwrap();
+        writeln!(rsp, "addrnd = {}", hex(&addrnd)).unwrap();
+        writeln!(rsp, "mlen = {}", mlen).unwrap();
+        writeln!(rsp, "msg = {}", hex(&msg)).unwrap();
+        writeln!(rsp, "sig = {}", hex(&sig.to_vec())).unwrap();
+        writeln!(rsp).unwrap();
+    }
+    std::fs::write(format!("{}.rsp", name), rsp).unwrap();
+}
+
+fn main() {
+    generate::<Sha2_128s>("slh-dsa-sha2-128s", 16, "sha256");
+    generate::<Sha2_128f>("slh-dsa-sha2-128f", 16, "sha512");
+    generate::<Sha2_192s>("slh-dsa-sha2-192s", 24, "sha256");
+    generate::<Sha2_192f>("slh-dsa-sha2-192f", 24, "sha512");
+    generate::<Sha2_256s>("slh-dsa-sha2-256s", 32, "sha256");
+    generate::<Sha2_256f>("slh-dsa-sha2-256f", 32, "sha512");
+    generate::<Shake128s>("slh-dsa-shake-128s", 16, "shake128");
+    generate::<Shake128f>("slh-dsa-shake-128f", 16, "shake256");
+    generate::<Shake192s>("slh-dsa-shake-192s", 24, "shake128");
+    generate::<Shake192f>("slh-dsa-shake-192f", 24, "shake256");
+    generate::<Shake256s>("slh-dsa-shake-256s", 32, "shake128");
+    generate::<Shake256f>("slh-dsa-shake-256f", 32, "shake256");
+}
@@ -18,98 +18,158 @@ include!(concat!(env!("OUT_DIR"), "/methods.rs"));
 #[cfg(test)]
 mod tests {
     use risc0_zkvm::{default_executor, ExecutorEnv};
-    use serde::{Deserialize, Serialize};
-    use base64::{engine::general_purpose, Engine as _};
-    use alloy_sol_types::{SolType,sol};
-    use std::fs;
-
-    #[derive(Debug, Serialize, Deserialize)]
-    #[allow(non_snake_case)]
-    pub struct Sm3Signature {
-        mlen: u32,
-        pk: String,  // Base64 编码的公钥
-        Sig: String, // Base64 编码的签名
-    }
-
-    type BoolSol = sol! { bool };
-
-    #[test]
-    fn proves_sig_is_valid() {
+    use alloy_sol_types::{SolType, sol};
+    use risc0_zkvm::sha::{Impl, Sha256};
+    use spx_host::{GuestInput, SignatureBundle, Status, VerificationJournal};
 
-        let json_str = fs::read_to_string("./../sig.json").unwrap();
-        let sig_: Sm3Signature = serde_json::from_str(&json_str).unwrap();
-        let pk_bytes = general_purpose::STANDARD.decode(&sig_.pk).unwrap();
-        let sig_bytes = general_purpose::STANDARD.decode(&sig_.Sig).unwrap();
+    const PARAM_SET: &str = "sm3-128s-simple";
 
-        let pk = pk_bytes[..].to_vec();
-        let sm = sig_bytes[..].to_vec();
-
-        let mut pk_sm = Vec::new();
-        pk_sm.extend_from_slice(&pk);
-        pk_sm.extend_from_slice(&sm);
-
-        let send = <sol! { bytes }>::abi_encode(&pk_sm);
+    /// 读取 sig.json
+    fn load_sig() -> SignatureBundle {
+        SignatureBundle::load("./../sig.json").unwrap()
+    }
 
+    fn execute(send: &[u8]) -> VerificationJournal {
         let env = ExecutorEnv::builder()
-            .write_slice(&send)
+            .write_slice(send)
             .build()
             .unwrap();
 
         // NOTE: Use the executor to run tests without proving.
         let session_info = default_executor().execute(env, super::SPXVRFY_ELF).unwrap();
-        let x: bool = BoolSol::abi_decode(&session_info.journal.bytes, true).unwrap();
+        VerificationJournal::decode(&session_info.journal.bytes).unwrap()
+    }
 
-        assert_eq!(x, true);
+    #[test]
+    fn proves_sig_is_valid() {
+        let bundle = load_sig();
+        let journal = execute(&GuestInput::new(PARAM_SET, bundle.clone()).encode());
+
+        assert!(journal.verified);
+        assert_eq!(journal.status, Status::Verified);
+        assert!(journal.matches(&bundle));
+        assert_eq!(journal.message_hash.as_slice(), Impl::hash_bytes(&bundle.message).as_bytes());
+        assert_eq!(journal.param_set, PARAM_SET);
     }
 
     #[test]
     fn proves_sig_is_not_valid() {
-        let json_str = fs::read_to_string("./../sig.json").unwrap();
-        let sig_: Sm3Signature = serde_json::from_str(&json_str).unwrap();
-        let pk_bytes = general_purpose::STANDARD.decode(&sig_.pk).unwrap();
-        let sig_bytes = general_purpose::STANDARD.decode(&sig_.Sig).unwrap();
+        let mut bundle = load_sig();
 
-        let pk = pk_bytes[..].to_vec();
-        let mut sm = sig_bytes[..].to_vec();
+        bundle.signature[3] = 0x33; // 修改签名的第一个字节
 
-        sm[3] = 0x33; // 修改签名的第一个字节
+        let journal = execute(&GuestInput::new(PARAM_SET, bundle.clone()).encode());
 
-        let mut pk_sm = Vec::new();
-        pk_sm.extend_from_slice(&pk);
-        pk_sm.extend_from_slice(&sm);
+        //这里要注意，修改签名后，验证结果应该是false
+        assert!(!journal.verified);
+        assert_eq!(journal.status, Status::InvalidSignature);
+        assert!(journal.matches(&bundle));
+    }
 
-        let send = <sol! { bytes }>::abi_encode(&pk_sm);
+    #[test]
+    fn proves_pk_is_not_true() {
+        let mut bundle = load_sig();
 
-        let env = ExecutorEnv::builder()
-            .write_slice(&send)
-            .build()
-            .unwrap();
+        bundle.public_key[3] = 0x33; // 修改签名的第一个字节
 
-        // NOTE: Use the executor to run tests without proving.
-        let session_info = default_executor().execute(env, super::SPXVRFY_ELF).unwrap();
-        let x: bool = BoolSol::abi_decode(&session_info.journal.bytes, true).unwrap();
+        let journal = execute(&GuestInput::new(PARAM_SET, bundle.clone()).encode());
 
         //这里要注意，修改签名后，验证结果应该是false
-        assert_eq!(x, false);
+        assert!(!journal.verified);
+        assert_eq!(journal.status, Status::InvalidSignature);
+        assert!(journal.matches(&bundle));
     }
 
     #[test]
-    fn proves_pk_is_not_true() {
-        let json_str = fs::read_to_string("./../sig.json").unwrap();
-        let sig_: Sm3Signature = serde_json::from_str(&json_str).unwrap();
-        let pk_bytes = general_purpose::STANDARD.decode(&sig_.pk).unwrap();
-        let sig_bytes = general_purpose::STANDARD.decode(&sig_.Sig).unwrap();
+    fn proves_input_is_malformed() {
+        let send = GuestInput::new(PARAM_SET, load_sig()).encode();
+
+        // 截断的输入无法解码，guest 仍然要产出 journal
+        let journal = execute(&send[..send.len() / 2]);
+        assert!(!journal.verified);
+        assert_eq!(journal.status, Status::MalformedInput);
+        assert!(journal.public_key.is_empty());
+    }
 
-        let mut pk = pk_bytes[..].to_vec();
-        let sm = sig_bytes[..].to_vec();
+    #[test]
+    fn proves_input_is_rejected() {
+        let bundle = load_sig();
+
+        let mut unsupported = GuestInput::new(PARAM_SET, bundle.clone());
+        unsupported.version = 2;
+        assert_eq!(execute(&unsupported.encode()).status, Status::UnsupportedVersion);
+
+        let unknown = GuestInput::new("sm3-128s-fast", bundle.clone());
+        let journal = execute(&unknown.encode());
+        assert_eq!(journal.status, Status::UnknownParamSet);
+        assert_eq!(journal.param_set, "sm3-128s-fast");
+
+        let mut short_pk = GuestInput::new(PARAM_SET, bundle.clone());
+        short_pk.bundle.public_key.remove(0);
+        let journal = execute(&short_pk.encode());
+        assert_eq!(journal.status, Status::BadLength);
+        assert_eq!(journal.public_key, short_pk.bundle.public_key);
+
+        let mut short_sig = GuestInput::new(PARAM_SET, bundle);
+        short_sig.bundle.signature.remove(0);
+        let journal = execute(&short_sig.encode());
+        assert_eq!(journal.status, Status::BadLength);
+        assert!(!journal.verified);
+    }
 
-        pk[3] = 0x33; // 修改签名的第一个字节
+    sol! {
+        struct SpxBatchItem {
+            bytes publicKey;
+            bytes signature;
+            bytes message;
+        }
+
+        struct SpxBatchJournal {
+            string paramSet;
+            uint32 count;
+            uint32 verifiedCount;
+            bytes results;
+            bytes32 root;
+            uint8 status;
+        }
+    }
 
-        let mut pk_sm = Vec::new();
-        pk_sm.extend_from_slice(&pk);
-        pk_sm.extend_from_slice(&sm);
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        Impl::hash_bytes(data).as_bytes().try_into().unwrap()
+    }
+
+    fn batch_leaf(item: &SpxBatchItem, verified: bool) -> [u8; 32] {
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&sha256(PARAM_SET.as_bytes()));
+        preimage.extend_from_slice(&sha256(&item.publicKey));
+        preimage.extend_from_slice(&sha256(&item.message));
+        preimage.push(verified as u8);
+        sha256(&preimage)
+    }
 
-        let send = <sol! { bytes }>::abi_encode(&pk_sm);
+    #[test]
+    fn proves_batch_results_and_root() {
+        let bundle = load_sig();
+        let pk = &bundle.public_key;
+
+        let valid = SpxBatchItem {
+            publicKey: pk.clone().into(),
+            signature: bundle.signature.clone().into(),
+            message: bundle.message.clone().into(),
+        };
+        let mut forged_sig = bundle.signature.clone();
+        forged_sig[3] ^= 0x33;
+        let forged = SpxBatchItem {
+            signature: forged_sig.into(),
+            ..valid.clone()
+        };
+        let short_pk = SpxBatchItem {
+            publicKey: pk[1..].to_vec().into(),
+            ..valid.clone()
+        };
+        let items = vec![valid, forged, short_pk];
+
+        let send = <sol! { (string, SpxBatchItem[]) }>::abi_encode_params(&(PARAM_SET.to_string(), items.clone()));
 
         let env = ExecutorEnv::builder()
             .write_slice(&send)
@@ -117,11 +177,49 @@ mod tests {
             .unwrap();
 
         // NOTE: Use the executor to run tests without proving.
-        let session_info = default_executor().execute(env, super::SPXVRFY_ELF).unwrap();
-        let x: bool = BoolSol::abi_decode(&session_info.journal.bytes, true).unwrap();
+        let session_info = default_executor().execute(env, super::SPXBATCHVRFY_ELF).unwrap();
+        let journal = SpxBatchJournal::abi_decode(&session_info.journal.bytes, true).unwrap();
+
+        assert_eq!(journal.status, Status::Verified as u8);
+        assert_eq!(journal.paramSet, PARAM_SET);
+        assert_eq!(journal.count, 3);
+        assert_eq!(journal.verifiedCount, 1);
+        assert_eq!(journal.results.as_ref(), &[0b001]);
+
+        // 三个叶子：前两个先合并，第三个落单直接上移
+        let leaves: Vec<_> = items
+            .iter()
+            .zip([true, false, false])
+            .map(|(item, verified)| batch_leaf(item, verified))
+            .collect();
+        let root = sha256(&[sha256(&[leaves[0], leaves[1]].concat()), leaves[2]].concat());
+        assert_eq!(journal.root.as_slice(), root.as_slice());
+    }
 
-        //这里要注意，修改签名后，验证结果应该是false
-        assert_eq!(x, false);
+    #[test]
+    fn commits_a_failed_batch_journal() {
+        let execute_batch = |input: &[u8]| {
+            let env = ExecutorEnv::builder().write_slice(input).build().unwrap();
+            let session_info = default_executor().execute(env, super::SPXBATCHVRFY_ELF).unwrap();
+            SpxBatchJournal::abi_decode(&session_info.journal.bytes, true).unwrap()
+        };
+
+        let journal = execute_batch(b"not an abi-encoded batch");
+        assert_eq!(journal.status, Status::MalformedInput as u8);
+        assert_eq!(journal.count, 0);
+        assert_eq!(journal.verifiedCount, 0);
+        assert!(journal.results.is_empty());
+        assert!(journal.root.is_zero());
+
+        let send = <sol! { (string, SpxBatchItem[]) }>::abi_encode_params(&(
+            "sm3-128s-fast".to_string(),
+            Vec::<SpxBatchItem>::new(),
+        ));
+        let journal = execute_batch(&send);
+        assert_eq!(journal.status, Status::UnknownParamSet as u8);
+        assert_eq!(journal.paramSet, "sm3-128s-fast");
+        assert_eq!(journal.count, 0);
+        assert!(journal.root.is_zero());
     }
 
 }
@@ -0,0 +1,54 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signs a message with libspx under a fresh key and prints the signature in
+//! the sig.json format:
+//!
+//! ```sh
+//! cargo run -p spxlib --example fixture -- "transfer 1 ETH" > fresh.json
+//! ```
+//!
+//! Without an argument the message is `helloworld\0`, the one of sig.json.
+
+use anyhow::{Context, Result};
+use rand::RngCore;
+use spx_host::{SignatureBundle, Sm3Signature};
+
+fn main() -> Result<()> {
+    let message = match std::env::args().nth(1) {
+        Some(message) => message.into_bytes(),
+        None => b"helloworld\0".to_vec(),
+    };
+
+    let mut seed = vec![0u8; spxlib::seed_bytes()];
+    rand::thread_rng().fill_bytes(&mut seed);
+    let keys = spxlib::seed_keypair(&seed)?;
+    let signature = spxlib::sign(&message, &keys.secret)?;
+    spx_sm3::vrfy(
+        spx_sm3::ParamSet::Sm3_128sSimple,
+        &signature,
+        &message,
+        &keys.public,
+    )
+    .context("spx_sm3 rejects the libspx signature")?;
+
+    let bundle = SignatureBundle {
+        public_key: keys.public,
+        signature,
+        message,
+    };
+    let json = serde_json::to_string_pretty(&Sm3Signature::from(&bundle))?;
+    println!("{json}");
+    Ok(())
+}
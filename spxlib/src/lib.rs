@@ -0,0 +1,403 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Safe wrapper of libspx, the C threshold signing library the frontend
+//! backend loads, with owned buffers and typed errors.
+//!
+//! libspx is built for [`PARAM_SET`] only. Its signatures are randomized,
+//! and they verify with `spx_sm3` like the ones of [`spx_tss`]:
+//!
+//! ```no_run
+//! let keys = spxlib::seed_keypair(&[7u8; 48])?;
+//! let signature = spxlib::sign(b"hello", &keys.secret)?;
+//! spxlib::verify(&signature, b"hello", &keys.public)?;
+//! # Ok::<(), spxlib::Error>(())
+//! ```
+
+use std::{
+    ffi::{c_char, c_int, CString},
+    fmt,
+};
+
+use num_bigint::BigUint;
+use spx_host::SignatureBundle;
+use spx_tss::Share;
+use spxlib_sys as sys;
+
+/// The parameter set libspx is built for.
+pub const PARAM_SET: &str = "sm3-128s-simple";
+
+/// Reason a libspx call was refused or failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A key, seed or signature does not have the size libspx expects
+    BadLength {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// The message is longer than the `int` lengths of signAPI
+    MessageTooLong(usize),
+    /// The shares cannot be passed to signAPI; see [`sign_shares`]
+    BadShares(String),
+    /// A libspx function returned a non-zero status
+    Failed { function: &'static str, status: i32 },
+    /// The signature does not verify
+    InvalidSignature,
+    /// signAPI reconstructed a key other than the expected one
+    PublicKeyMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BadLength {
+                what,
+                expected,
+                actual,
+            } => write!(f, "{what} is {actual} bytes, expected {expected}"),
+            Error::MessageTooLong(len) => write!(f, "a {len}-byte message is too long for signAPI"),
+            Error::BadShares(reason) => write!(f, "signAPI cannot use these shares: {reason}"),
+            Error::Failed { function, status } => write!(f, "{function} returned {status}"),
+            Error::InvalidSignature => f.write_str("the signature does not verify"),
+            Error::PublicKeyMismatch => {
+                f.write_str("signAPI reconstructed a different public key from the shares")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Size of a key seed, `SK.seed ‖ SK.prf ‖ PK.seed`.
+pub fn seed_bytes() -> usize {
+    // SAFETY: returns a constant
+    unsafe { sys::tss_crypto_sign_seedbytes() as usize }
+}
+
+/// Size of a public key, `PK.seed ‖ root`.
+pub fn public_key_bytes() -> usize {
+    // SAFETY: returns a constant
+    unsafe { sys::tss_crypto_sign_publickeybytes() as usize }
+}
+
+/// Size of a secret key, the seed followed by the root.
+pub fn secret_key_bytes() -> usize {
+    // SAFETY: returns a constant
+    unsafe { sys::tss_crypto_sign_secretkeybytes() as usize }
+}
+
+/// Size of a detached signature.
+pub fn signature_bytes() -> usize {
+    // SAFETY: returns a constant
+    unsafe { sys::tss_crypto_sign_bytes() as usize }
+}
+
+/// A libspx key pair.
+#[derive(Clone)]
+pub struct Keypair {
+    pub public: Vec<u8>,
+    pub secret: Vec<u8>,
+}
+
+/// Derives a key pair from a seed, `tss_crypto_sign_keypair`.
+pub fn seed_keypair(seed: &[u8]) -> Result<Keypair, Error> {
+    check_length("seed", seed_bytes(), seed.len())?;
+    let mut public = vec![0u8; public_key_bytes()];
+    let mut secret = vec![0u8; secret_key_bytes()];
+    // SAFETY: the buffers have the sizes the library writes and reads
+    let status = unsafe {
+        sys::tss_crypto_sign_keypair(public.as_mut_ptr(), secret.as_mut_ptr(), seed.as_ptr())
+    };
+    check_status("tss_crypto_sign_keypair", status)?;
+    Ok(Keypair { public, secret })
+}
+
+/// Signs `message`, `crypto_sign_signature`. The randomizer comes from
+/// `/dev/urandom`, so every call gives a different signature.
+pub fn sign(message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>, Error> {
+    check_length("secret key", secret_key_bytes(), secret_key.len())?;
+    let mut signature = vec![0u8; signature_bytes()];
+    let mut len = 0usize;
+    // SAFETY: the signature buffer has the size the library writes, and the
+    // message and key are read within their lengths
+    let status = unsafe {
+        sys::crypto_sign_signature(
+            signature.as_mut_ptr(),
+            &mut len,
+            message.as_ptr(),
+            message.len(),
+            secret_key.as_ptr(),
+        )
+    };
+    check_status("crypto_sign_signature", status)?;
+    check_length("signature", signature.len(), len)?;
+    Ok(signature)
+}
+
+/// Verifies a detached signature, `tss_crypto_sign_verify`.
+pub fn verify(signature: &[u8], message: &[u8], public_key: &[u8]) -> Result<(), Error> {
+    check_length("public key", public_key_bytes(), public_key.len())?;
+    check_length("signature", signature_bytes(), signature.len())?;
+    // SAFETY: all buffers are read within their lengths
+    let status = unsafe {
+        sys::tss_crypto_sign_verify(
+            signature.as_ptr(),
+            signature.len(),
+            message.as_ptr(),
+            message.len(),
+            public_key.as_ptr(),
+        )
+    };
+    if status != 0 {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Generates a 384-bit safe prime for sharing seeds, `GenPrimeAPI`. The C
+/// code prints the prime to stdout as well.
+pub fn gen_prime() -> BigUint {
+    // The decimal digits of a 384-bit number and the NUL
+    let mut buf = [0u8; 128];
+    // SAFETY: the buffer holds the decimal prime the library copies into it
+    unsafe { sys::GenPrimeAPI(buf.as_mut_ptr().cast::<c_char>()) };
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    BigUint::parse_bytes(&buf[..len], 10).expect("GenPrimeAPI writes a decimal number")
+}
+
+/// Signs `message` with the key reconstructed from `shares` modulo `prime`,
+/// `signAPI`.
+///
+/// signAPI does not reconstruct the way [`spx_tss::reconstruct`] does: its
+/// Lagrange coefficients wrap negative differences of x to unsigned words,
+/// so from two or more shares it reconstructs another key. It also reads the
+/// seed past its end when the reconstructed value is shorter than the seed.
+/// The reconstruction is therefore repeated here first, and shares whose C
+/// reconstruction would be short are refused.
+///
+/// With `public_key`, a key other than that one is refused, the check
+/// signAPI has commented out.
+pub fn sign_shares(
+    message: &[u8],
+    prime: &BigUint,
+    shares: &[Share],
+    public_key: Option<&[u8]>,
+) -> Result<SignatureBundle, Error> {
+    let mlen = c_int::try_from(message.len()).map_err(|_| Error::MessageTooLong(message.len()))?;
+    let seed = c_reconstruct(shares, prime)?;
+    if seed.bits().div_ceil(8) != seed_bytes() as u64 {
+        return Err(Error::BadShares(format!(
+            "they reconstruct a {}-bit seed, not one of {} bytes",
+            seed.bits(),
+            seed_bytes()
+        )));
+    }
+
+    let decimal = |n: &BigUint| CString::new(n.to_str_radix(10)).expect("digits have no NUL");
+    let ys: Vec<CString> = shares.iter().map(|share| decimal(&share.y)).collect();
+    let mut shards: Vec<sys::shards> = shares
+        .iter()
+        .zip(&ys)
+        .map(|(share, y)| sys::shards {
+            x: share.x as c_int,
+            y: y.as_ptr(),
+        })
+        .collect();
+    let prime = decimal(prime);
+    // signAPI only requires input_pk to be a non-zero decimal number
+    let input_pk = decimal(&BigUint::from_bytes_be(public_key.unwrap_or(&[1])));
+
+    let mut sm = vec![0u8; signature_bytes() + message.len()];
+    let mut sm_len: c_int = 0;
+    let mut output_pk = vec![0u8; public_key_bytes()];
+    let mut pk_len: c_int = 0;
+    // SAFETY: the shares were checked above to reconstruct a full seed, the
+    // strings are NUL-terminated decimals that outlive the call, and the
+    // output buffers have the sizes the library writes
+    let status = unsafe {
+        sys::signAPI(
+            message.as_ptr(),
+            mlen,
+            shards.len() as c_int,
+            shards.as_mut_ptr(),
+            prime.as_ptr().cast_mut(),
+            sm.as_mut_ptr(),
+            &mut sm_len,
+            input_pk.as_ptr(),
+            output_pk.as_mut_ptr(),
+            &mut pk_len,
+        )
+    };
+    check_status("signAPI", status)?;
+    check_length("signed message", sm.len(), sm_len as usize)?;
+    check_length("public key", output_pk.len(), pk_len as usize)?;
+    if public_key.is_some_and(|expected| expected != output_pk) {
+        return Err(Error::PublicKeyMismatch);
+    }
+
+    let message = sm.split_off(signature_bytes());
+    Ok(SignatureBundle {
+        public_key: output_pk,
+        signature: sm,
+        message,
+    })
+}
+
+/// The value `lagrange_interpolate_at_zero` of signAPI.c computes, wrap of
+/// `x_i - x_j` included, after checking that signAPI can parse the shares.
+fn c_reconstruct(shares: &[Share], prime: &BigUint) -> Result<BigUint, Error> {
+    let bad = |reason: &str| Err(Error::BadShares(reason.into()));
+    if shares.is_empty() {
+        return bad("no shares were given");
+    }
+    if c_int::try_from(shares.len()).is_err() {
+        return bad("there are too many");
+    }
+    if *prime < BigUint::from(2u32) {
+        return bad("the prime is below 2");
+    }
+    let xs: Vec<c_int> = shares
+        .iter()
+        .map(|share| c_int::try_from(share.x))
+        .collect::<Result<_, _>>()
+        .map_err(|_| Error::BadShares("an x does not fit a C int".into()))?;
+
+    let mut secret = BigUint::from(0u32);
+    for (i, share) in shares.iter().enumerate() {
+        let mut num = BigUint::from(1u32);
+        let mut den = BigUint::from(1u32);
+        for (j, &x_j) in xs.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let x_j = BigUint::from(x_j as u32) % prime;
+            num = num * ((prime - x_j) % prime) % prime;
+            // BN_set_word takes the int difference as an unsigned long
+            den = den * BigUint::from((xs[i] - xs[j]) as i64 as u64) % prime;
+        }
+        let Some(inv) = den.modinv(prime) else {
+            return bad("a Lagrange denominator is not invertible modulo the prime");
+        };
+        secret = (secret + num * inv % prime * &share.y) % prime;
+    }
+    Ok(secret)
+}
+
+fn check_length(what: &'static str, expected: usize, actual: usize) -> Result<(), Error> {
+    if expected != actual {
+        return Err(Error::BadLength {
+            what,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn check_status(function: &'static str, status: c_int) -> Result<(), Error> {
+    if status != 0 {
+        return Err(Error::Failed { function, status });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+    use spx_sm3::ParamSet;
+
+    const PARAMS: ParamSet = ParamSet::Sm3_128sSimple;
+
+    #[test]
+    fn c_signatures_verify_with_spx_sm3() {
+        assert_eq!(PARAMS.name(), PARAM_SET);
+        assert_eq!(seed_bytes(), PARAMS.seed_bytes());
+        assert_eq!(public_key_bytes(), PARAMS.public_key_bytes());
+        assert_eq!(signature_bytes(), PARAMS.signature_bytes());
+
+        let mut seed = vec![0u8; seed_bytes()];
+        rand::thread_rng().fill_bytes(&mut seed);
+        let keys = seed_keypair(&seed).unwrap();
+        assert_eq!(
+            keys.public,
+            spx_sm3::seed_keypair(PARAMS, &seed).unwrap().public
+        );
+
+        let message = b"helloworld\0";
+        let signature = sign(message, &keys.secret).unwrap();
+        assert_eq!(
+            spx_sm3::vrfy(PARAMS, &signature, message, &keys.public),
+            Ok(())
+        );
+        verify(&signature, message, &keys.public).unwrap();
+
+        let mut forged = signature.clone();
+        forged[100] ^= 1;
+        assert!(spx_sm3::vrfy(PARAMS, &forged, message, &keys.public).is_err());
+        assert_eq!(
+            verify(&forged, message, &keys.public),
+            Err(Error::InvalidSignature)
+        );
+        assert!(matches!(
+            seed_keypair(&seed[1..]),
+            Err(Error::BadLength { what: "seed", .. })
+        ));
+    }
+
+    #[test]
+    fn sign_shares_follows_the_c_reconstruction() {
+        // Seeded so that every reconstructed seed fills all 48 bytes
+        let mut rng = StdRng::seed_from_u64(21);
+        let message = b"transfer 1 ETH";
+
+        // A single share is the secret itself, so C and Rust agree
+        let key = spx_tss::deal(PARAMS, 1, 2, &mut rng).unwrap();
+        let bundle =
+            sign_shares(message, &key.prime, &key.shares[1..], Some(&key.public_key)).unwrap();
+        assert_eq!(bundle.message, message);
+        let (sig, pk) = (&bundle.signature, &bundle.public_key);
+        assert_eq!(spx_sm3::vrfy(PARAMS, sig, message, pk), Ok(()));
+        spx_host::GuestInput::new(PARAM_SET, bundle)
+            .validate()
+            .unwrap();
+
+        // From two shares signAPI reconstructs another key, which still
+        // signs validly
+        let key = spx_tss::deal(PARAMS, 2, 2, &mut rng).unwrap();
+        let result = sign_shares(message, &key.prime, &key.shares, Some(&key.public_key));
+        assert_eq!(result.unwrap_err(), Error::PublicKeyMismatch);
+        let other = sign_shares(message, &key.prime, &key.shares, None).unwrap();
+        assert_ne!(other.public_key, key.public_key);
+        let (sig, pk) = (&other.signature, &other.public_key);
+        assert_eq!(spx_sm3::vrfy(PARAMS, sig, message, pk), Ok(()));
+    }
+
+    #[test]
+    fn refuses_shares_signapi_mishandles() {
+        let prime = gen_prime();
+        assert_eq!(prime.bits(), 8 * seed_bytes() as u64);
+
+        // A seed of one byte, which signAPI would read 47 bytes past
+        let short = Share {
+            x: 1,
+            y: BigUint::from(5u32),
+        };
+        let err = sign_shares(b"m", &prime, &[short], None).unwrap_err();
+        assert!(matches!(err, Error::BadShares(_)), "{err}");
+        let err = sign_shares(b"m", &prime, &[], None).unwrap_err();
+        assert!(matches!(err, Error::BadShares(_)), "{err}");
+    }
+}
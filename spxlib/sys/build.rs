@@ -0,0 +1,62 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Builds libspx from the spxlibAPI sources as the Makefile does, or links a
+//! prebuilt `libspx.so` from `LIBSPX_LIB_DIR`.
+
+use std::{env, path::PathBuf};
+
+/// Sources of the Makefile's `SOURCES`, for `PARAMS_HEAD = 128s` and
+/// `THASH = simple`.
+const SOURCES: &[&str] = &[
+    "src/signature/address.c",
+    "src/signature/merkle.c",
+    "src/signature/wots.c",
+    "src/signature/wotsx1.c",
+    "src/signature/fors.c",
+    "src/signature/TSS_sign.c",
+    "src/utils/utils.c",
+    "src/utils/utilsx1.c",
+    "src/utils/randombytes.c",
+    "src/hash/SM3.c",
+    "src/hash/hash_SM3.c",
+    "src/hash/thash_SM3_simple.c",
+    "api/keygenAPI.c",
+    "api/signAPI.c",
+];
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=LIBSPX_LIB_DIR");
+    if let Ok(dir) = env::var("LIBSPX_LIB_DIR") {
+        println!("cargo:rustc-link-search=native={dir}");
+        println!("cargo:rustc-link-lib=dylib=spx");
+        return;
+    }
+
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("../../MutipleWallet_frontend/spxlibAPI");
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let include = root.join("include");
+    cc::Build::new()
+        .files(SOURCES.iter().map(|source| root.join(source)))
+        .include(&include)
+        .includes(["hash", "signature", "params", "utils"].map(|dir| include.join(dir)))
+        .define("PARAMS", "sphincs-SM3-128s")
+        .flag("-std=c99")
+        .opt_level(3)
+        .warnings(false)
+        .compile("spx");
+    println!("cargo:rustc-link-lib=crypto");
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Raw bindings to libspx, the SPHINCS+-SM3-128s-simple threshold signing
+//! library of `MutipleWallet_frontend/spxlibAPI`.
+//!
+//! The library is built from its sources, so gcc and the OpenSSL headers must
+//! be installed; set `LIBSPX_LIB_DIR` to link a prebuilt `libspx.so` instead.
+//! The `spxlib` crate wraps these in safe functions.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::os::raw::{c_char, c_int, c_uchar, c_ulonglong};
+
+/// A share `(x, y)` with `y` in decimal, the `shards` of signAPI.c.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct shards {
+    pub x: c_int,
+    pub y: *const c_char,
+}
+
+/// A party's blinded secret in decimal, the `Bsk` of keygenAPI.c.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Bsk {
+    pub blind_sk: *const c_char,
+}
+
+extern "C" {
+    pub fn tss_crypto_sign_secretkeybytes() -> c_ulonglong;
+    pub fn tss_crypto_sign_publickeybytes() -> c_ulonglong;
+    pub fn tss_crypto_sign_bytes() -> c_ulonglong;
+    pub fn tss_crypto_sign_seedbytes() -> c_ulonglong;
+
+    /// Derives `pk` and `sk` from the `seedbytes()`-byte `seed`.
+    pub fn tss_crypto_sign_seed_keypair(
+        pk: *mut c_uchar,
+        sk: *mut c_uchar,
+        seed: *const c_uchar,
+    ) -> c_int;
+
+    /// Same as `tss_crypto_sign_seed_keypair`.
+    pub fn tss_crypto_sign_keypair(
+        pk: *mut c_uchar,
+        sk: *mut c_uchar,
+        seed: *const c_uchar,
+    ) -> c_int;
+
+    /// Returns 0 if `sig` is a signature of `m` under `pk`.
+    pub fn tss_crypto_sign_verify(
+        sig: *const u8,
+        siglen: usize,
+        m: *const u8,
+        mlen: usize,
+        pk: *const u8,
+    ) -> c_int;
+
+    /// Writes the signed message `sig ‖ m` to `sm`, `bytes() + mlen` long.
+    pub fn crypto_sign(
+        sm: *mut c_uchar,
+        smlen: *mut c_ulonglong,
+        m: *const c_uchar,
+        mlen: c_ulonglong,
+        sk: *const c_uchar,
+    ) -> c_int;
+
+    /// Writes the detached signature of `m` to `sig`, `bytes()` long.
+    pub fn crypto_sign_signature(
+        sig: *mut u8,
+        siglen: *mut usize,
+        m: *const u8,
+        mlen: usize,
+        sk: *const u8,
+    ) -> c_int;
+
+    /// Reconstructs the seed from `t` shares modulo the decimal `prime` and
+    /// writes the signed message to `out_sm` and the public key to
+    /// `output_pk`. Returns 0 on success.
+    pub fn signAPI(
+        m: *const c_uchar,
+        mlen: c_int,
+        t: c_int,
+        shard: *mut shards,
+        prime: *mut c_char,
+        out_sm: *mut c_uchar,
+        sm_len: *mut c_int,
+        input_pk: *const c_char,
+        output_pk: *mut c_uchar,
+        out_pklen: *mut c_int,
+    ) -> c_int;
+
+    /// Returns 0 if the signed message `sm` with an `mlen`-byte message
+    /// verifies under `pk`.
+    pub fn vrfySigAPI(mlen: c_int, sm: *mut c_uchar, pk: *mut c_uchar) -> c_int;
+
+    /// Writes a 384-bit safe prime in decimal, NUL-terminated, to `p`.
+    pub fn GenPrimeAPI(p: *mut c_char);
+
+    /// Derives the key of the seed `Σ bsk mod p` and writes its public key
+    /// in decimal, NUL-terminated, to `out_pk`.
+    pub fn DKGAPI(bsk: *mut Bsk, n: c_int, p: *const c_char, out_pk: *mut c_char);
+}
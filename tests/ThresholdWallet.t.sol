@@ -6,6 +6,8 @@ import {console2} from "forge-std/console2.sol";
 import {Test} from "forge-std/Test.sol";
 import {IRiscZeroVerifier} from "risc0/IRiscZeroVerifier.sol";
 import {ThresholdWallet} from "../contracts/ThresholdWallet.sol";
+import {ThresholdWalletFactory} from "../contracts/ThresholdWalletFactory.sol";
+import {SpxJournal, SPX_STATUS_VERIFIED} from "../contracts/ISpxVrfy.sol";
 import {Elf} from "./Elf.sol";
 
 contract ThresholdWalletTest is RiscZeroCheats, Test {
@@ -13,6 +15,8 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
     address public userA;
     address public userB;
     bytes mockSeal;
+    bytes tssPublicKey;
+    bytes mockJournal;
 
     function setUp() public {
         userA = makeAddr("userA");
@@ -24,12 +28,15 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         vm.deal(address(wallet), 5 ether);
         
         mockSeal = hex"1234567890";
+        tssPublicKey = hex"8FE578F0EBE3898BB580D2E59F80EBE5DA762F4A67682844BF158486B8AD5AD0";
+        mockJournal = abi.encode(SpxJournal(tssPublicKey, sha256("hello world"), "sm3-128s-simple", true, SPX_STATUS_VERIFIED));
+
+        wallet.setTssPublicKey(tssPublicKey);
     }
 
     function test_WalletCreation() public {
         assertEq(wallet.owner(), address(this));
         assertEq(wallet.nonce(), 0);
-        assertEq(wallet.isVerified(), false);
         assertEq(address(wallet).balance, 5 ether);
     }
 
@@ -49,27 +56,32 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         assertEq(uint(txn.status), uint(ThresholdWallet.TransactionStatus.Pending));
     }
 
+    /// Journal of a verified signature of `message` by the wallet's TSS key,
+    /// with the verifier mocked to accept it.
+    function mockProof(bytes memory message) internal returns (bytes memory journal) {
+        journal = abi.encode(SpxJournal(tssPublicKey, sha256(message), "sm3-128s-simple", true, SPX_STATUS_VERIFIED));
+        vm.mockCall(
+            address(wallet.verifier()),
+            abi.encodeWithSelector(IRiscZeroVerifier.verify.selector, mockSeal, wallet.imageId(), sha256(journal)),
+            abi.encode()
+        );
+    }
+
     function test_ExecuteTransactionWithMockVerification() public {
 
         uint256 txNonce = wallet.submitTransaction(userA, 1 ether, "", 21000);
         
         assertEq(userA.balance, 0);
-        
-        bytes memory journal = abi.encode(true);
-        bytes32 journalHash = sha256(journal);
-        
-        bytes4 verifySelector = IRiscZeroVerifier.verify.selector;
-        
-        vm.mockCall(
-            address(wallet.verifier()),
-            abi.encodeWithSelector(verifySelector, mockSeal, wallet.imageId(), journalHash),
-            abi.encode()  
-        );
-        
-        bool success = wallet.executeTransaction(txNonce, mockSeal);
+
+        bytes memory message = wallet.transactionMessage(txNonce);
+        assertEq(message, abi.encode(block.chainid, address(wallet), txNonce, userA, 1 ether, bytes("")));
+        bytes memory journal = mockProof(message);
+        assertTrue(wallet.verifySignature(journal, mockSeal));
+
+        bool success = wallet.executeTransaction(txNonce, journal, mockSeal);
 
         assertTrue(success);
-        assertTrue(wallet.isVerified());
+        assertTrue(wallet.usedJournals(sha256(journal)));
         assertEq(userA.balance, 1 ether);
         
  
@@ -77,13 +89,41 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         assertEq(uint(txn.status), uint(ThresholdWallet.TransactionStatus.Executed));
     }
 
+    function test_ExecuteTransactionRejectsOtherMessages() public {
+        uint256 first = wallet.submitTransaction(userA, 1 ether, "", 21000);
+        uint256 second = wallet.submitTransaction(userB, 2 ether, "", 21000);
+
+        // A signature of something else, e.g. of "hello world"
+        vm.expectRevert(ThresholdWallet.MessageMismatch.selector);
+        wallet.executeTransaction(first, mockProof("hello world"), mockSeal);
+
+        // The signature of one transaction does not execute another
+        bytes memory journal = mockProof(wallet.transactionMessage(first));
+        vm.expectRevert(ThresholdWallet.MessageMismatch.selector);
+        wallet.executeTransaction(second, journal, mockSeal);
+
+        assertEq(userA.balance, 0);
+        assertEq(userB.balance, 0);
+    }
+
+    function test_ExecuteTransactionRejectsUsedJournals() public {
+        // An executed journal is marked used, on top of the transaction no
+        // longer being pending
+        uint256 txNonce = wallet.submitTransaction(userA, 1 ether, "", 21000);
+        bytes memory journal = mockProof(wallet.transactionMessage(txNonce));
+        wallet.executeTransaction(txNonce, journal, mockSeal);
+        assertTrue(wallet.usedJournals(sha256(journal)));
+
+        vm.expectRevert(ThresholdWallet.TransactionDoesNotExist.selector);
+        wallet.executeTransaction(txNonce, journal, mockSeal);
+    }
+
     function test_ExecuteTransactionWithMockVerificationFailure() public {
 
         uint256 txNonce = wallet.submitTransaction(userA, 1 ether, "", 21000);
         
-
-        bytes memory journal = abi.encode(true);
-        bytes32 journalHash = sha256(journal);
+        mockJournal = abi.encode(SpxJournal(tssPublicKey, sha256(wallet.transactionMessage(txNonce)), "sm3-128s-simple", true, SPX_STATUS_VERIFIED));
+        bytes32 journalHash = sha256(mockJournal);
         
 
         bytes4 verifySelector = IRiscZeroVerifier.verify.selector;
@@ -96,7 +136,7 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         
   
         vm.expectRevert(ThresholdWallet.ZKProofVerificationFailed.selector);
-        wallet.executeTransaction(txNonce, mockSeal);
+        wallet.executeTransaction(txNonce, mockJournal, mockSeal);
         
 
         ThresholdWallet.Transaction memory txn = wallet.getTransaction(txNonce);
@@ -106,6 +146,23 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         assertEq(userA.balance, 0);
     }
 
+    function test_ExecuteTransactionWithForeignPublicKey() public {
+        uint256 txNonce = wallet.submitTransaction(userA, 1 ether, "", 21000);
+
+        bytes memory journal = abi.encode(SpxJournal(hex"1234", sha256(wallet.transactionMessage(txNonce)), "sm3-128s-simple", true, SPX_STATUS_VERIFIED));
+
+        vm.mockCall(
+            address(wallet.verifier()),
+            abi.encodeWithSelector(IRiscZeroVerifier.verify.selector, mockSeal, wallet.imageId(), sha256(journal)),
+            abi.encode()
+        );
+
+        vm.expectRevert(ThresholdWallet.ZKProofVerificationFailed.selector);
+        wallet.executeTransaction(txNonce, journal, mockSeal);
+
+        assertEq(userA.balance, 0);
+    }
+
     function test_CancelTransaction() public {
     
         uint256 txNonce = wallet.submitTransaction(userA, 1 ether, "", 21000);
@@ -145,7 +202,7 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         
   
         vm.expectRevert(ThresholdWallet.TransactionTimedOut.selector);
-        wallet.executeTransaction(txNonce, mockSeal);
+        wallet.executeTransaction(txNonce, mockJournal, mockSeal);
     }
 
     function test_GasEstimation() public {
@@ -162,6 +219,21 @@ contract ThresholdWalletTest is RiscZeroCheats, Test {
         assertTrue(gasEstimateForContract > 21000); 
     }
 
+    function test_FactoryWalletOwnedByCaller() public {
+        ThresholdWalletFactory factory = new ThresholdWalletFactory(deployRiscZeroVerifier());
+
+        vm.prank(userA);
+        ThresholdWallet created = ThresholdWallet(payable(factory.createWallet()));
+
+        assertEq(created.owner(), userA);
+        assertEq(factory.getUserWallets(userA)[0], address(created));
+
+        vm.prank(userA);
+        created.setTssPublicKey(tssPublicKey);
+        vm.prank(userA);
+        assertEq(created.submitTransaction(userB, 0, "", 21000), 0);
+    }
+
     function deployEmptyContract() internal returns (address) {
         bytes memory bytecode = hex"6080604052348015600f57600080fd5b50603c80601d6000396000f3fe6080604052600080fdfea2646970667358221220d86a40f88e4c94dd5c1b6a493eb3af581a839881efe19322c231b30fac899ae564736f6c63430008170033";
         address addr;
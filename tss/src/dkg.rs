@@ -0,0 +1,582 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Distributed generation of a shared SPHINCS+ seed with Pedersen's protocol:
+//! every party deals a random contribution with Feldman VSS, and the seed is
+//! the sum of the contributions of the dealers that were not disqualified.
+//!
+//! Each round is a method of [`Party`] returning the messages to send, and
+//! the messages of a round are handed to the other parties with
+//! [`Party::receive`] before the next one:
+//!
+//! 1. [`Party::deal`] broadcasts commitments to the party's polynomial and
+//!    sends every other party its share.
+//! 2. [`Party::complain`] broadcasts a complaint against every dealer whose
+//!    share is missing or does not match its commitments.
+//! 3. [`Party::answer`] broadcasts the share of every party that complained.
+//! 4. [`Party::finish`] disqualifies the dealers with a complaint left
+//!    unanswered or answered with a wrong share, and adds up the shares of
+//!    the others into the party's key share.
+//!
+//! Broadcasts must reach every party alike, their sender included, so that
+//! all parties decide on the same record. [`simulate`] runs all parties in
+//! process.
+//!
+//! A SPHINCS+ public key is a hash tree root computed from the seed, not a
+//! function that can be evaluated share by share, so [`public_key`] needs
+//! `threshold` key shares and learns the seed, as `DKGAPI` does when it adds
+//! up the blinded secrets.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_bigint::{BigUint, RandBigInt};
+use rand::{CryptoRng, RngCore};
+use spx_sm3::ParamSet;
+
+use crate::{field, shamir, vss, Group, Share, SharedKey, TssError};
+
+/// What all parties agree on before the protocol starts.
+#[derive(Clone, Debug)]
+pub struct DkgConfig {
+    pub params: ParamSet,
+    pub threshold: usize,
+    pub parties: usize,
+    /// Commitment group; its order `q` is the prime the shares live in
+    pub group: Group,
+}
+
+impl DkgConfig {
+    /// Picks a prime of `8 * seed_bytes` bits for the shares, like
+    /// `GenPrimeAPI`, and a commitment group of `group_bits` bits around it.
+    pub fn generate<R: RngCore + CryptoRng>(
+        params: ParamSet,
+        threshold: usize,
+        parties: usize,
+        group_bits: u64,
+        rng: &mut R,
+    ) -> Result<Self, TssError> {
+        let q = field::random_prime(8 * params.seed_bytes() as u64, rng);
+        shamir::check_sharing(&BigUint::from(0u32), threshold, parties, &q)?;
+        Ok(Self {
+            params,
+            threshold,
+            parties,
+            group: Group::generate(&q, group_bits, rng)?,
+        })
+    }
+}
+
+/// Contents of a protocol message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// Round 1 broadcast: commitments to the sender's polynomial
+    Commitments(Vec<BigUint>),
+    /// Round 1, to one party: its share of the sender's polynomial
+    Share(BigUint),
+    /// Round 2 broadcast: the share from dealer `against` is wrong or missing
+    Complaint { against: u32 },
+    /// Round 3 broadcast: the sender's share for the complaining party `to`
+    Reveal { to: u32, y: BigUint },
+}
+
+/// A message from party `from`, to party `to` or broadcast to all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub from: u32,
+    pub to: Option<u32>,
+    pub payload: Payload,
+}
+
+/// The result of the protocol for one party.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    /// The party's share of the seed
+    pub share: Share,
+    /// Dealers whose contributions make up the seed
+    pub qualified: Vec<u32>,
+    /// Commitments to the polynomial sharing the seed, which every key share
+    /// verifies against
+    pub commitments: Vec<BigUint>,
+}
+
+/// One party of the protocol, at x = `id`.
+pub struct Party {
+    id: u32,
+    config: DkgConfig,
+    coefficients: Vec<BigUint>,
+    /// Commitments of each dealer, this party's included
+    commitments: BTreeMap<u32, Vec<BigUint>>,
+    /// Share of this party from each dealer
+    shares: BTreeMap<u32, BigUint>,
+    /// `(complainer, dealer)` pairs
+    complaints: BTreeSet<(u32, u32)>,
+    /// Shares revealed by `(dealer, complainer)`
+    reveals: BTreeMap<(u32, u32), BigUint>,
+}
+
+impl Party {
+    /// Starts party `id`, in 1..=`parties`, with a random contribution.
+    pub fn new<R: RngCore + CryptoRng>(
+        id: u32,
+        config: &DkgConfig,
+        rng: &mut R,
+    ) -> Result<Self, TssError> {
+        if id == 0 || id as usize > config.parties {
+            return Err(TssError::UnknownParty(id));
+        }
+        let group = &config.group;
+        let contribution = rng.gen_biguint_below(&group.q);
+        let coefficients = shamir::polynomial(&contribution, config.threshold, &group.q, rng);
+        let own = shamir::evaluate(&coefficients, id, &group.q);
+        Ok(Self {
+            id,
+            config: config.clone(),
+            commitments: BTreeMap::from([(id, vss::commit(group, &coefficients))]),
+            shares: BTreeMap::from([(id, own)]),
+            coefficients,
+            complaints: BTreeSet::new(),
+            reveals: BTreeMap::new(),
+        })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Round 1: commitments for all, and a share for each other party.
+    pub fn deal(&self) -> Vec<Message> {
+        let mut messages =
+            vec![self.broadcast(Payload::Commitments(self.commitments[&self.id].clone()))];
+        messages.extend(self.others().map(|to| Message {
+            from: self.id,
+            to: Some(to),
+            payload: Payload::Share(self.share_for(to)),
+        }));
+        messages
+    }
+
+    /// Round 2: complaints against the dealers whose share is wrong or
+    /// missing.
+    pub fn complain(&mut self) -> Vec<Message> {
+        let group = &self.config.group;
+        let bad: Vec<u32> = self
+            .others()
+            .filter(|dealer| {
+                let (Some(commitments), Some(y)) =
+                    (self.commitments.get(dealer), self.shares.get(dealer))
+                else {
+                    return true;
+                };
+                let share = Share {
+                    x: self.id,
+                    y: y.clone(),
+                };
+                !vss::verify(group, commitments, &share)
+            })
+            .collect();
+        bad.into_iter()
+            .map(|against| {
+                self.complaints.insert((self.id, against));
+                self.broadcast(Payload::Complaint { against })
+            })
+            .collect()
+    }
+
+    /// Round 3: the shares of the parties that complained about this one.
+    pub fn answer(&mut self) -> Vec<Message> {
+        let complainers: Vec<u32> = self
+            .complaints
+            .iter()
+            .filter(|&&(_, dealer)| dealer == self.id)
+            .map(|&(complainer, _)| complainer)
+            .collect();
+        complainers
+            .into_iter()
+            .map(|to| {
+                let y = self.share_for(to);
+                self.reveals.insert((self.id, to), y.clone());
+                self.broadcast(Payload::Reveal { to, y })
+            })
+            .collect()
+    }
+
+    /// Stores a message of the current round, this party's own broadcasts
+    /// included. A malformed message is refused and counts as not sent.
+    pub fn receive(&mut self, message: Message) -> Result<(), TssError> {
+        let from = message.from;
+        let own = from == self.id;
+        let bad = |reason| Err(TssError::BadMessage { from, reason });
+        if !self.is_party(from) || (own && message.to.is_some()) {
+            return bad("the sender is not another party");
+        }
+        if message.to.is_some_and(|to| to != self.id) {
+            return bad("the message is for another party");
+        }
+        let group = &self.config.group;
+        match message.payload {
+            Payload::Commitments(commitments) => {
+                if message.to.is_some() || commitments.len() != self.config.threshold {
+                    return bad("commitments must be broadcast, one per coefficient");
+                }
+                if !own && self.commitments.contains_key(&from) {
+                    return bad("the dealer already sent commitments");
+                }
+                self.commitments.insert(from, commitments);
+            }
+            Payload::Share(y) => {
+                if message.to.is_none() || y >= group.q {
+                    return bad("a share must be sent to its party and be below q");
+                }
+                if self.shares.contains_key(&from) {
+                    return bad("the dealer already sent a share");
+                }
+                self.shares.insert(from, y);
+            }
+            Payload::Complaint { against } => {
+                if message.to.is_some() || against == from || !self.is_party(against) {
+                    return bad("a complaint must be broadcast against another party");
+                }
+                self.complaints.insert((from, against));
+            }
+            Payload::Reveal { to, y } => {
+                if message.to.is_some() || !self.complaints.contains(&(to, from)) {
+                    return bad("a reveal must be broadcast and answer a complaint");
+                }
+                if to == self.id && y < group.q {
+                    // Checked against the commitments in finish()
+                    self.shares.insert(from, y.clone());
+                }
+                self.reveals.insert((from, to), y);
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends the protocol with this party's key share, or fails if fewer than
+    /// `threshold` dealers qualified.
+    pub fn finish(&self) -> Result<KeyShare, TssError> {
+        let group = &self.config.group;
+        let qualified: Vec<u32> = (1..=self.config.parties as u32)
+            .filter(|&dealer| self.is_qualified(dealer))
+            .collect();
+        if qualified.len() < self.config.threshold {
+            return Err(TssError::NotEnoughQualified {
+                qualified: qualified.len(),
+                threshold: self.config.threshold,
+            });
+        }
+
+        let mut y = BigUint::from(0u32);
+        for dealer in &qualified {
+            // A qualified dealer's share of this party verified in round 2
+            // or was revealed and verified in is_qualified()
+            y = (y + &self.shares[dealer]) % &group.q;
+        }
+        let all: Vec<&[BigUint]> = qualified
+            .iter()
+            .map(|dealer| self.commitments[dealer].as_slice())
+            .collect();
+        let key_share = KeyShare {
+            share: Share { x: self.id, y },
+            qualified,
+            commitments: vss::combine(group, &all),
+        };
+        debug_assert!(vss::verify(group, &key_share.commitments, &key_share.share));
+        Ok(key_share)
+    }
+
+    /// A dealer is qualified if it sent commitments and answered every
+    /// complaint against it with a share that matches them.
+    fn is_qualified(&self, dealer: u32) -> bool {
+        let Some(commitments) = self.commitments.get(&dealer) else {
+            return false;
+        };
+        self.complaints
+            .iter()
+            .filter(|&&(_, against)| against == dealer)
+            .all(|&(complainer, _)| {
+                self.reveals.get(&(dealer, complainer)).is_some_and(|y| {
+                    let share = Share {
+                        x: complainer,
+                        y: y.clone(),
+                    };
+                    vss::verify(&self.config.group, commitments, &share)
+                })
+            })
+    }
+
+    fn share_for(&self, x: u32) -> BigUint {
+        shamir::evaluate(&self.coefficients, x, &self.config.group.q)
+    }
+
+    fn broadcast(&self, payload: Payload) -> Message {
+        Message {
+            from: self.id,
+            to: None,
+            payload,
+        }
+    }
+
+    fn others(&self) -> impl Iterator<Item = u32> + '_ {
+        (1..=self.config.parties as u32).filter(move |&x| x != self.id)
+    }
+
+    fn is_party(&self, x: u32) -> bool {
+        x != 0 && x as usize <= self.config.parties
+    }
+}
+
+/// Derives the public key of the seed shared by `key_shares`, at least
+/// `threshold` of them, after checking each share against the commitments.
+pub fn public_key(config: &DkgConfig, key_shares: &[KeyShare]) -> Result<Vec<u8>, TssError> {
+    let Some(first) = key_shares.first() else {
+        return Err(TssError::NoShares);
+    };
+    if key_shares.len() < config.threshold {
+        return Err(TssError::TooFewShares {
+            given: key_shares.len(),
+            threshold: config.threshold,
+        });
+    }
+    if key_shares
+        .iter()
+        .any(|k| k.commitments != first.commitments)
+    {
+        return Err(TssError::Inconsistent);
+    }
+    let shares: Vec<Share> = key_shares.iter().map(|k| k.share.clone()).collect();
+    let secret = vss::reconstruct(&config.group, &first.commitments, &shares)?;
+    let seed = crate::seed_from_secret(config.params, &secret)?;
+    Ok(spx_sm3::seed_keypair(config.params, &seed)?.public)
+}
+
+/// Result of a simulated run, as every party agreed on it.
+#[derive(Clone, Debug)]
+pub struct DkgOutput {
+    pub key: SharedKey,
+    pub qualified: Vec<u32>,
+    pub commitments: Vec<BigUint>,
+}
+
+/// Runs the protocol among all parties in process.
+pub fn simulate<R: RngCore + CryptoRng>(
+    config: &DkgConfig,
+    rng: &mut R,
+) -> Result<DkgOutput, TssError> {
+    simulate_with(config, rng, Some)
+}
+
+/// Runs the protocol among all parties in process, passing every message
+/// through `network`, which may alter it or drop it by returning `None`. The
+/// sender of a broadcast gets it as altered, the way a cheating party
+/// commits to what it broadcasts.
+pub fn simulate_with<R, F>(
+    config: &DkgConfig,
+    rng: &mut R,
+    mut network: F,
+) -> Result<DkgOutput, TssError>
+where
+    R: RngCore + CryptoRng,
+    F: FnMut(Message) -> Option<Message>,
+{
+    let mut parties = (1..=config.parties as u32)
+        .map(|id| Party::new(id, config, rng))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut deliver = |parties: &mut Vec<Party>, messages: Vec<Message>| {
+        for message in messages.into_iter().filter_map(&mut network) {
+            for party in parties.iter_mut() {
+                let addressed = match message.to {
+                    Some(to) => to == party.id,
+                    None => true,
+                };
+                if addressed {
+                    // A refused message counts as not sent
+                    let _ = party.receive(message.clone());
+                }
+            }
+        }
+    };
+    let round: Vec<Message> = parties.iter().flat_map(Party::deal).collect();
+    deliver(&mut parties, round);
+    let round: Vec<Message> = parties.iter_mut().flat_map(Party::complain).collect();
+    deliver(&mut parties, round);
+    let round: Vec<Message> = parties.iter_mut().flat_map(Party::answer).collect();
+    deliver(&mut parties, round);
+
+    let key_shares = parties
+        .iter()
+        .map(Party::finish)
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = &key_shares[0];
+    if key_shares
+        .iter()
+        .any(|k| k.qualified != first.qualified || k.commitments != first.commitments)
+    {
+        return Err(TssError::Inconsistent);
+    }
+
+    Ok(DkgOutput {
+        key: SharedKey {
+            params: config.params,
+            prime: config.group.q.clone(),
+            threshold: config.threshold,
+            public_key: public_key(config, &key_shares)?,
+            shares: key_shares.iter().map(|k| k.share.clone()).collect(),
+        },
+        qualified: first.qualified.clone(),
+        commitments: first.commitments.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small enough for quick tests, far too small for real keys
+    const TEST_GROUP_BITS: u64 = 512;
+    const PARAMS: ParamSet = ParamSet::Sm3_128fSimple;
+
+    fn config(threshold: usize, parties: usize) -> DkgConfig {
+        let rng = &mut rand::thread_rng();
+        DkgConfig::generate(PARAMS, threshold, parties, TEST_GROUP_BITS, rng).unwrap()
+    }
+
+    fn signs(output: &DkgOutput, shares: &[Share]) {
+        let key = &output.key;
+        let bundle =
+            crate::sign(PARAMS, &key.prime, shares, b"dkg", Some(&key.public_key)).unwrap();
+        assert_eq!(
+            spx_sm3::vrfy(PARAMS, &bundle.signature, b"dkg", &key.public_key),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn honest_parties_share_a_key() {
+        let config = config(2, 3);
+        let output = simulate(&config, &mut rand::thread_rng()).unwrap();
+        assert_eq!(output.qualified, [1, 2, 3]);
+        assert_eq!(output.commitments.len(), 2);
+        let group = &config.group;
+        assert!(output
+            .key
+            .shares
+            .iter()
+            .all(|s| vss::verify(group, &output.commitments, s)));
+
+        signs(&output, &output.key.shares[1..]);
+        signs(
+            &output,
+            &[output.key.shares[2].clone(), output.key.shares[0].clone()],
+        );
+
+        // A key share corrupted after the protocol is traced to its party
+        let mut key_shares: Vec<KeyShare> = output
+            .key
+            .shares
+            .iter()
+            .map(|share| KeyShare {
+                share: share.clone(),
+                qualified: output.qualified.clone(),
+                commitments: output.commitments.clone(),
+            })
+            .collect();
+        assert_eq!(public_key(&config, &key_shares), Ok(output.key.public_key));
+        key_shares[1].share.y = (&key_shares[1].share.y + 1u32) % &group.q;
+        assert_eq!(
+            public_key(&config, &key_shares),
+            Err(TssError::InvalidShares(vec![2]))
+        );
+    }
+
+    #[test]
+    fn answered_complaints_keep_the_dealer() {
+        let config = config(2, 3);
+        let output = simulate_with(&config, &mut rand::thread_rng(), |mut message| {
+            if let (2, Some(1), Payload::Share(y)) =
+                (message.from, message.to, &mut message.payload)
+            {
+                *y += 1u32;
+            }
+            Some(message)
+        })
+        .unwrap();
+        // Party 1 complained, dealer 2 revealed its real share
+        assert_eq!(output.qualified, [1, 2, 3]);
+        signs(&output, &output.key.shares[..2]);
+    }
+
+    #[test]
+    fn cheating_dealers_are_disqualified() {
+        let config = config(2, 3);
+        let cheat = |mut message: Message| {
+            match (message.from, &mut message.payload) {
+                (2, Payload::Share(y)) | (2, Payload::Reveal { y, .. }) => *y += 1u32,
+                _ => {}
+            }
+            Some(message)
+        };
+        let output = simulate_with(&config, &mut rand::thread_rng(), cheat).unwrap();
+        assert_eq!(output.qualified, [1, 3]);
+        signs(&output, &output.key.shares[..2]);
+
+        // Without dealer 2 there are fewer qualified dealers than a 3-of-3
+        // threshold
+        let config = DkgConfig {
+            threshold: 3,
+            ..config
+        };
+        let result = simulate_with(&config, &mut rand::thread_rng(), cheat);
+        assert_eq!(
+            result.unwrap_err(),
+            TssError::NotEnoughQualified {
+                qualified: 2,
+                threshold: 3
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_bad_messages() {
+        let config = config(2, 3);
+        let mut rng = rand::thread_rng();
+        let mut party = Party::new(1, &config, &mut rng).unwrap();
+        let dealer = Party::new(2, &config, &mut rng).unwrap();
+        let deal = dealer.deal();
+
+        let mut commitments = deal[0].clone();
+        commitments.to = Some(1);
+        assert!(party.receive(commitments).is_err(), "sent to one party");
+        let for_3 = deal.iter().find(|m| m.to == Some(3)).unwrap().clone();
+        assert!(party.receive(for_3).is_err(), "for another party");
+        let reveal = Message {
+            from: 2,
+            to: None,
+            payload: Payload::Reveal {
+                to: 1,
+                y: BigUint::from(1u32),
+            },
+        };
+        assert!(party.receive(reveal).is_err(), "answers no complaint");
+
+        for message in deal.into_iter().filter(|m| m.to != Some(3)) {
+            party.receive(message).unwrap();
+        }
+        assert!(
+            party.receive(dealer.deal()[0].clone()).is_err(),
+            "sent twice"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Arithmetic modulo the prime the shares live in.
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+/// Miller-Rabin rounds, for a composite passing with probability below 2^-128.
+const ROUNDS: usize = 64;
+
+/// Trial divisors tried before Miller-Rabin.
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Returns a random prime of exactly `bits` bits.
+///
+/// `GenPrimeAPI` generates a safe prime of `8 * seed_bytes` bits; Shamir
+/// sharing only needs the field to be prime, so any prime will do.
+pub fn random_prime<R: RngCore + CryptoRng>(bits: u64, rng: &mut R) -> BigUint {
+    assert!(bits >= 2, "no prime has fewer than 2 bits");
+    loop {
+        let mut candidate = rng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, bits > 2);
+        if is_probable_prime(&candidate, rng) {
+            return candidate;
+        }
+    }
+}
+
+/// Tests `n` for primality with trial division and Miller-Rabin.
+pub fn is_probable_prime<R: RngCore>(n: &BigUint, rng: &mut R) -> bool {
+    let two = BigUint::from(2u32);
+    if n < &two {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n == &BigUint::from(p) {
+            return true;
+        }
+        if n.is_multiple_of(&BigUint::from(p)) {
+            return false;
+        }
+    }
+
+    let n_1 = n - 1u32;
+    let s = n_1.trailing_zeros().unwrap_or(0);
+    let d = &n_1 >> s;
+    'witness: for _ in 0..ROUNDS {
+        let a = rng.gen_biguint_range(&two, &n_1);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Inverse of `a` modulo the prime `p`, none for a multiple of `p`.
+pub(crate) fn inverse(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let a = a % p;
+    (!a.is_zero()).then(|| a.modpow(&(p - 2u32), p))
+}
+
+/// Lagrange coefficient of the point at `xs[i]` for interpolating at zero,
+/// `∏_{j≠i} x_j / (x_j - x_i) mod p`, none if two of the `xs` are equal
+/// modulo `p`.
+///
+/// This is the `num / den` of `lagrange_interpolate_at_zero` in signAPI.c,
+/// with `x_i - x_j` taken modulo `p`; the C code passes that difference to
+/// `BN_set_word` as an unsigned word, so it wraps to `2^64 - |x_i - x_j|`
+/// whenever `x_i < x_j`.
+pub fn lagrange_at_zero(xs: &[BigUint], i: usize, p: &BigUint) -> Option<BigUint> {
+    let mut num = BigUint::one();
+    let mut den = BigUint::one();
+    for (j, x_j) in xs.iter().enumerate() {
+        if j == i {
+            continue;
+        }
+        num = num * x_j % p;
+        den = den * ((p + x_j % p - &xs[i] % p) % p) % p;
+    }
+    Some(num * inverse(&den, p)? % p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_primes() {
+        let mut rng = rand::thread_rng();
+        let primes: Vec<u32> = (0..100)
+            .filter(|&n| is_probable_prime(&BigUint::from(n), &mut rng))
+            .collect();
+        assert_eq!(primes, SMALL_PRIMES);
+        // 2^127 - 1 is prime, 2^128 + 1 is not
+        assert!(is_probable_prime(
+            &((BigUint::one() << 127) - 1u32),
+            &mut rng
+        ));
+        assert!(!is_probable_prime(
+            &((BigUint::one() << 128) + 1u32),
+            &mut rng
+        ));
+
+        let p = random_prime(384, &mut rng);
+        assert_eq!(p.bits(), 384);
+        assert!(is_probable_prime(&p, &mut rng));
+    }
+
+    #[test]
+    fn interpolates_at_zero() {
+        // f(x) = 7 + 3x mod 11: f(1) = 10, f(3) = 5
+        let p = BigUint::from(11u32);
+        let xs = [BigUint::from(1u32), BigUint::from(3u32)];
+        let ys = [BigUint::from(10u32), BigUint::from(5u32)];
+        let secret = (0..2).fold(BigUint::zero(), |acc, i| {
+            acc + lagrange_at_zero(&xs, i, &p).unwrap() * &ys[i]
+        }) % &p;
+        assert_eq!(secret, BigUint::from(7u32));
+
+        let same = [BigUint::from(1u32), BigUint::from(12u32)];
+        assert_eq!(lagrange_at_zero(&same, 0, &p), None);
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The group share commitments live in: the subgroup of prime order `q` of
+//! the integers modulo a larger prime `p = k·q + 1`, where `q` is the prime
+//! the shares live in.
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+use crate::{field, TssError};
+
+/// Bits of `p` that keep discrete logarithms in the group hard.
+pub const GROUP_BITS: u64 = 2048;
+
+/// A Schnorr group: `g` generates the subgroup of order `q` modulo `p`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+impl Group {
+    /// Finds a prime `p` of `bits` bits with `q | p - 1` and a generator of
+    /// the subgroup of order `q`.
+    pub fn generate<R: RngCore + CryptoRng>(
+        q: &BigUint,
+        bits: u64,
+        rng: &mut R,
+    ) -> Result<Self, TssError> {
+        if bits <= q.bits() + 1 {
+            return Err(TssError::BadPrime { bits });
+        }
+        let p = loop {
+            // p = k·q + 1 is odd only for an even k
+            let mut k = rng.gen_biguint(bits - q.bits());
+            k.set_bit(bits - q.bits() - 1, true);
+            k.set_bit(0, false);
+            let p = k * q + 1u32;
+            if p.bits() == bits && field::is_probable_prime(&p, rng) {
+                break p;
+            }
+        };
+        let k = (&p - 1u32) / q;
+        let g = loop {
+            let h = rng.gen_biguint_range(&BigUint::from(2u32), &(&p - 1u32));
+            let g = h.modpow(&k, &p);
+            if !g.is_one() {
+                break g;
+            }
+        };
+        Ok(Self { p, q: q.clone(), g })
+    }
+
+    /// Checks a group received from elsewhere: `p` and `q` are prime, `q`
+    /// divides `p - 1`, and `g` has order `q`.
+    pub fn check<R: RngCore>(&self, rng: &mut R) -> Result<(), TssError> {
+        let valid = field::is_probable_prime(&self.q, rng)
+            && field::is_probable_prime(&self.p, rng)
+            && (&self.p - 1u32).is_multiple_of(&self.q)
+            && self.is_element(&self.g)
+            && !self.g.is_one();
+        if !valid {
+            return Err(TssError::BadPrime {
+                bits: self.p.bits(),
+            });
+        }
+        Ok(())
+    }
+
+    /// `g^exponent mod p`.
+    pub fn commit(&self, exponent: &BigUint) -> BigUint {
+        self.g.modpow(exponent, &self.p)
+    }
+
+    /// Whether `element` is in the subgroup of order `q`.
+    pub fn is_element(&self, element: &BigUint) -> bool {
+        !element.is_zero() && *element < self.p && self.power(element, &self.q).is_one()
+    }
+
+    /// `base^exponent mod p`.
+    pub(crate) fn power(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        base.modpow(exponent, &self.p)
+    }
+
+    /// `a·b mod p`.
+    pub(crate) fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % &self.p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_schnorr_groups() {
+        let mut rng = rand::thread_rng();
+        let q = field::random_prime(128, &mut rng);
+        let group = Group::generate(&q, 256, &mut rng).unwrap();
+        assert_eq!(group.p.bits(), 256);
+        group.check(&mut rng).unwrap();
+        assert!(group.is_element(&group.commit(&BigUint::from(5u32))));
+
+        let mut wrong = group.clone();
+        wrong.g = BigUint::from(2u32);
+        assert!(
+            wrong.check(&mut rng).is_err(),
+            "2 is unlikely in the subgroup"
+        );
+        assert!(Group::generate(&q, 129, &mut rng).is_err());
+    }
+}
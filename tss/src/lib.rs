@@ -0,0 +1,144 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Threshold signing with [`spx_sm3`], the Rust counterpart of the
+//! `signAPI.c` flow of spxlibAPI.
+//!
+//! The SPHINCS+ key seed `SK.seed ‖ SK.prf ‖ PK.seed`, read as a big-endian
+//! integer, is Shamir-shared over a prime field whose prime has as many bits
+//! as the seed. Any `t` of the `n` shares reconstruct it by Lagrange
+//! interpolation at zero, and the reconstructed key signs:
+//!
+//! ```no_run
+//! use spx_host::GuestInput;
+//! use spx_sm3::ParamSet;
+//!
+//! let params = ParamSet::Sm3_128sSimple;
+//! let key = spx_tss::deal(params, 2, 3, &mut rand::thread_rng())?;
+//! let shares = &key.shares[1..];
+//! let bundle = spx_tss::sign(params, &key.prime, shares, b"hello", Some(&key.public_key))?;
+//! GuestInput::new(params.name(), bundle).validate()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+
+use spx_sm3::SigError;
+
+pub mod dkg;
+mod field;
+mod group;
+mod shamir;
+mod sign;
+pub mod vss;
+
+pub use field::{is_probable_prime, lagrange_at_zero, random_prime};
+pub use group::{Group, GROUP_BITS};
+pub use shamir::{reconstruct, split, Share};
+pub use sign::{
+    deal, deal_verifiable, secret_from_seed, seed_from_secret, sign, sign_verified, SharedKey,
+    VerifiableKey,
+};
+
+/// Reason a sharing, reconstruction or threshold signature failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TssError {
+    /// The threshold is zero or above the number of parties
+    BadThreshold { threshold: usize, parties: usize },
+    /// The prime has too few bits for the number of parties or more bits
+    /// than the seed holds
+    BadPrime { bits: u64 },
+    /// The secret is not below the prime, or too large for the seed
+    SecretOutOfRange,
+    /// No shares were given
+    NoShares,
+    /// Fewer shares than the threshold were given
+    TooFewShares { given: usize, threshold: usize },
+    /// The shares of these parties, by x, do not match the commitments
+    InvalidShares(Vec<u32>),
+    /// A share has x = 0, the point whose value is the secret itself
+    ZeroIndex,
+    /// Two shares have the same x
+    DuplicateIndex(u32),
+    /// The value of the share at x is not below the prime
+    ShareOutOfRange(u32),
+    /// A share could not be parsed
+    BadShare(String),
+    /// The reconstructed key is not the expected one: a share is wrong, or
+    /// fewer shares than the threshold were given
+    PublicKeyMismatch,
+    /// x is not the index of one of the parties
+    UnknownParty(u32),
+    /// A DKG message from party `from` is malformed
+    BadMessage { from: u32, reason: &'static str },
+    /// Fewer dealers than the threshold were qualified by the DKG
+    NotEnoughQualified { qualified: usize, threshold: usize },
+    /// The DKG parties ended with different qualified dealers or
+    /// commitments
+    Inconsistent,
+    /// The reconstructed secret does not match the commitment to it
+    CommitmentMismatch,
+    /// The SPHINCS+ library rejected the seed or key
+    Sig(SigError),
+}
+
+impl fmt::Display for TssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TssError::BadThreshold { threshold, parties } => {
+                write!(f, "threshold {threshold} is not between 1 and the {parties} parties")
+            }
+            TssError::BadPrime { bits } => write!(f, "a {bits}-bit prime cannot share this seed"),
+            TssError::SecretOutOfRange => f.write_str("the secret does not fit the prime or seed"),
+            TssError::NoShares => f.write_str("no shares were given"),
+            TssError::TooFewShares { given, threshold } => {
+                write!(f, "{given} shares were given, the threshold is {threshold}")
+            }
+            TssError::InvalidShares(parties) => {
+                write!(f, "the shares of parties {parties:?} do not match the commitments")
+            }
+            TssError::ZeroIndex => f.write_str("a share has x = 0"),
+            TssError::DuplicateIndex(x) => write!(f, "two shares have x = {x}"),
+            TssError::ShareOutOfRange(x) => write!(f, "the share at x = {x} is not below the prime"),
+            TssError::BadShare(reason) => write!(f, "malformed share: {reason}"),
+            TssError::PublicKeyMismatch => f.write_str(
+                "the shares reconstruct a different public key: a share is wrong or too few were given",
+            ),
+            TssError::UnknownParty(x) => write!(f, "{x} is not one of the parties"),
+            TssError::BadMessage { from, reason } => {
+                write!(f, "malformed message from party {from}: {reason}")
+            }
+            TssError::NotEnoughQualified {
+                qualified,
+                threshold,
+            } => write!(
+                f,
+                "{qualified} dealers qualified, fewer than the threshold of {threshold}"
+            ),
+            TssError::Inconsistent => f.write_str("the parties disagree on the DKG result"),
+            TssError::CommitmentMismatch => {
+                f.write_str("the reconstructed secret does not match its commitment")
+            }
+            TssError::Sig(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TssError {}
+
+impl From<SigError> for TssError {
+    fn from(err: SigError) -> Self {
+        TssError::Sig(err)
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shamir secret sharing over the integers modulo a prime.
+
+use std::collections::BTreeSet;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::Zero;
+use rand::{CryptoRng, RngCore};
+
+use crate::{field, TssError};
+
+/// One party's share, the point `(x, f(x))` of the sharing polynomial `f`;
+/// the `shards` of signAPI.c.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub x: u32,
+    pub y: BigUint,
+}
+
+impl Share {
+    /// Parses a share whose value is in decimal, as the frontend stores it.
+    pub fn from_decimal(x: u32, y: &str) -> Result<Self, TssError> {
+        let y = BigUint::parse_bytes(y.as_bytes(), 10)
+            .ok_or_else(|| TssError::BadShare(format!("{y:?} is not a decimal number")))?;
+        Ok(Self { x, y })
+    }
+}
+
+/// Splits `secret` into shares at x = 1..=`parties`, any `threshold` of
+/// which reconstruct it: the values of a random polynomial of degree
+/// `threshold - 1` whose constant term is the secret.
+pub fn split<R: RngCore + CryptoRng>(
+    secret: &BigUint,
+    threshold: usize,
+    parties: usize,
+    prime: &BigUint,
+    rng: &mut R,
+) -> Result<Vec<Share>, TssError> {
+    check_sharing(secret, threshold, parties, prime)?;
+    let coefficients = polynomial(secret, threshold, prime, rng);
+    Ok(evaluate_all(&coefficients, parties, prime))
+}
+
+/// Checks that `secret` can be shared among `parties` with `threshold`.
+pub(crate) fn check_sharing(
+    secret: &BigUint,
+    threshold: usize,
+    parties: usize,
+    prime: &BigUint,
+) -> Result<(), TssError> {
+    if threshold == 0 || threshold > parties || u32::try_from(parties).is_err() {
+        return Err(TssError::BadThreshold { threshold, parties });
+    }
+    // The x of every party must be a distinct non-zero field element
+    if *prime <= BigUint::from(parties) {
+        return Err(TssError::BadPrime { bits: prime.bits() });
+    }
+    if secret >= prime {
+        return Err(TssError::SecretOutOfRange);
+    }
+    Ok(())
+}
+
+/// Reconstructs the secret from `shares` by Lagrange interpolation at zero,
+/// the `lagrange_interpolate_at_zero` of signAPI.c.
+///
+/// Shares from fewer parties than the threshold reconstruct an unrelated
+/// value rather than failing; compare the result with a known public key to
+/// tell.
+pub fn reconstruct(shares: &[Share], prime: &BigUint) -> Result<BigUint, TssError> {
+    if shares.is_empty() {
+        return Err(TssError::NoShares);
+    }
+    let mut seen = BTreeSet::new();
+    for share in shares {
+        if share.x == 0 {
+            return Err(TssError::ZeroIndex);
+        }
+        if !seen.insert(share.x) {
+            return Err(TssError::DuplicateIndex(share.x));
+        }
+        if share.y >= *prime {
+            return Err(TssError::ShareOutOfRange(share.x));
+        }
+    }
+
+    let xs: Vec<BigUint> = shares.iter().map(|share| BigUint::from(share.x)).collect();
+    let mut secret = BigUint::zero();
+    for (i, share) in shares.iter().enumerate() {
+        // Only fails for an x that is a multiple of the prime, or two x
+        // equal modulo it
+        let coefficient = field::lagrange_at_zero(&xs, i, prime)
+            .ok_or(TssError::BadPrime { bits: prime.bits() })?;
+        secret = (secret + coefficient * &share.y) % prime;
+    }
+    Ok(secret)
+}
+
+/// A random polynomial of degree `threshold - 1` with constant term
+/// `secret`, its coefficients constant term first.
+pub(crate) fn polynomial<R: RngCore + CryptoRng>(
+    secret: &BigUint,
+    threshold: usize,
+    prime: &BigUint,
+    rng: &mut R,
+) -> Vec<BigUint> {
+    let mut coefficients = vec![secret.clone()];
+    coefficients.extend((1..threshold).map(|_| rng.gen_biguint_below(prime)));
+    coefficients
+}
+
+/// The shares of x = 1..=`parties` of the polynomial with `coefficients`.
+pub(crate) fn evaluate_all(
+    coefficients: &[BigUint],
+    parties: usize,
+    prime: &BigUint,
+) -> Vec<Share> {
+    (1..=parties as u32)
+        .map(|x| Share {
+            x,
+            y: evaluate(coefficients, x, prime),
+        })
+        .collect()
+}
+
+/// Evaluates the polynomial with `coefficients`, constant term first, at `x`.
+pub(crate) fn evaluate(coefficients: &[BigUint], x: u32, prime: &BigUint) -> BigUint {
+    coefficients
+        .iter()
+        .rev()
+        .fold(BigUint::zero(), |acc, c| (acc * x + c) % prime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_prime;
+
+    #[test]
+    fn any_threshold_of_shares_reconstructs() {
+        let mut rng = rand::thread_rng();
+        let prime = random_prime(128, &mut rng);
+        let secret = rng.gen_biguint_below(&prime);
+        let shares = split(&secret, 3, 5, &prime, &mut rng).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let subset = [shares[c].clone(), shares[a].clone(), shares[b].clone()];
+                    assert_eq!(reconstruct(&subset, &prime).unwrap(), secret);
+                }
+            }
+        }
+        assert_eq!(reconstruct(&shares, &prime).unwrap(), secret, "all shares");
+        assert_ne!(
+            reconstruct(&shares[..2], &prime).unwrap(),
+            secret,
+            "too few"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut rng = rand::thread_rng();
+        let prime = BigUint::from(101u32);
+        let secret = BigUint::from(42u32);
+        let bad_threshold = |threshold, parties| TssError::BadThreshold { threshold, parties };
+        assert_eq!(
+            split(&secret, 0, 3, &prime, &mut rng),
+            Err(bad_threshold(0, 3))
+        );
+        assert_eq!(
+            split(&secret, 4, 3, &prime, &mut rng),
+            Err(bad_threshold(4, 3))
+        );
+        assert_eq!(
+            split(&secret, 2, 101, &prime, &mut rng),
+            Err(TssError::BadPrime { bits: 7 })
+        );
+        assert_eq!(
+            split(&prime, 2, 3, &prime, &mut rng),
+            Err(TssError::SecretOutOfRange)
+        );
+
+        let shares = split(&secret, 2, 3, &prime, &mut rng).unwrap();
+        assert_eq!(reconstruct(&[], &prime), Err(TssError::NoShares));
+        let zero = Share {
+            x: 0,
+            y: secret.clone(),
+        };
+        assert_eq!(reconstruct(&[zero], &prime), Err(TssError::ZeroIndex));
+        let twice = [shares[0].clone(), shares[0].clone()];
+        assert_eq!(
+            reconstruct(&twice, &prime),
+            Err(TssError::DuplicateIndex(1))
+        );
+        let large = Share {
+            x: 1,
+            y: prime.clone(),
+        };
+        assert_eq!(
+            reconstruct(&[large], &prime),
+            Err(TssError::ShareOutOfRange(1))
+        );
+
+        assert_eq!(
+            Share::from_decimal(2, "17").unwrap().y,
+            BigUint::from(17u32)
+        );
+        assert!(Share::from_decimal(2, "0x11").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dealing a shared SPHINCS+ key and signing with a threshold of its shares,
+//! optionally with commitments that let every share be checked.
+
+use num_bigint::{BigUint, RandBigInt};
+use rand::{CryptoRng, RngCore};
+use spx_host::SignatureBundle;
+use spx_sm3::ParamSet;
+
+use crate::{field, shamir, vss, Group, Share, TssError};
+
+/// A SPHINCS+ key whose seed is shared among parties.
+#[derive(Clone, Debug)]
+pub struct SharedKey {
+    pub params: ParamSet,
+    /// Prime of the field the shares live in, of `8 * seed_bytes` bits
+    pub prime: BigUint,
+    /// Number of shares needed to sign
+    pub threshold: usize,
+    pub public_key: Vec<u8>,
+    /// The share of party `i` is `shares[i]`, at x = `i + 1`
+    pub shares: Vec<Share>,
+}
+
+/// Generates a key for `params` and splits its seed into `parties` shares,
+/// `threshold` of which can sign.
+pub fn deal<R: RngCore + CryptoRng>(
+    params: ParamSet,
+    threshold: usize,
+    parties: usize,
+    rng: &mut R,
+) -> Result<SharedKey, TssError> {
+    let prime = field::random_prime(8 * params.seed_bytes() as u64, rng);
+    let secret = rng.gen_biguint_below(&prime);
+    let shares = shamir::split(&secret, threshold, parties, &prime, rng)?;
+    let keys = spx_sm3::seed_keypair(params, &seed_from_secret(params, &secret)?)?;
+    Ok(SharedKey {
+        params,
+        prime,
+        threshold,
+        public_key: keys.public,
+        shares,
+    })
+}
+
+/// A shared key dealt with Feldman VSS: the shares are modulo the group
+/// order, and each verifies against the commitments.
+#[derive(Clone, Debug)]
+pub struct VerifiableKey {
+    pub key: SharedKey,
+    pub group: Group,
+    pub commitments: Vec<BigUint>,
+}
+
+/// Like [`deal`], with commitments to the sharing in a group of
+/// `group_bits` bits.
+pub fn deal_verifiable<R: RngCore + CryptoRng>(
+    params: ParamSet,
+    threshold: usize,
+    parties: usize,
+    group_bits: u64,
+    rng: &mut R,
+) -> Result<VerifiableKey, TssError> {
+    let prime = field::random_prime(8 * params.seed_bytes() as u64, rng);
+    let group = Group::generate(&prime, group_bits, rng)?;
+    let secret = rng.gen_biguint_below(&prime);
+    let (shares, commitments) = vss::share(&secret, threshold, parties, &group, rng)?;
+    let keys = spx_sm3::seed_keypair(params, &seed_from_secret(params, &secret)?)?;
+    Ok(VerifiableKey {
+        key: SharedKey {
+            params,
+            prime,
+            threshold,
+            public_key: keys.public,
+            shares,
+        },
+        group,
+        commitments,
+    })
+}
+
+/// Encodes a secret as a seed for `params`, big-endian and zero-padded on
+/// the left to `seed_bytes`.
+///
+/// signAPI.c takes the minimal `BN_bn2bin` encoding instead, which is the
+/// same for secrets whose top byte is non-zero and too short otherwise.
+pub fn seed_from_secret(params: ParamSet, secret: &BigUint) -> Result<Vec<u8>, TssError> {
+    let bytes = secret.to_bytes_be();
+    let padding = params
+        .seed_bytes()
+        .checked_sub(bytes.len())
+        .ok_or(TssError::SecretOutOfRange)?;
+    let mut seed = vec![0u8; padding];
+    seed.extend_from_slice(&bytes);
+    Ok(seed)
+}
+
+/// Reads a seed as the big-endian integer that is shared.
+pub fn secret_from_seed(seed: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(seed)
+}
+
+/// Reconstructs the key from `shares` and signs `message` with it, the
+/// `signAPI` of signAPI.c with the signature detached from the message.
+///
+/// With `public_key`, a key other than that one is refused instead of
+/// signing: that is how a wrong share, or too few of them, shows.
+pub fn sign(
+    params: ParamSet,
+    prime: &BigUint,
+    shares: &[Share],
+    message: &[u8],
+    public_key: Option<&[u8]>,
+) -> Result<SignatureBundle, TssError> {
+    if prime.bits() > 8 * params.seed_bytes() as u64 {
+        return Err(TssError::BadPrime { bits: prime.bits() });
+    }
+    let secret = shamir::reconstruct(shares, prime)?;
+    sign_secret(params, &secret, message, public_key)
+}
+
+/// Checks each share against `commitments` before reconstructing the key
+/// and signing with it. A share that does not match is refused with
+/// [`TssError::InvalidShares`], naming its party, where [`sign`] can only
+/// tell that the key came out wrong.
+pub fn sign_verified(
+    params: ParamSet,
+    group: &Group,
+    commitments: &[BigUint],
+    shares: &[Share],
+    message: &[u8],
+) -> Result<SignatureBundle, TssError> {
+    if group.q.bits() > 8 * params.seed_bytes() as u64 {
+        return Err(TssError::BadPrime {
+            bits: group.q.bits(),
+        });
+    }
+    let secret = vss::reconstruct(group, commitments, shares)?;
+    sign_secret(params, &secret, message, None)
+}
+
+fn sign_secret(
+    params: ParamSet,
+    secret: &BigUint,
+    message: &[u8],
+    public_key: Option<&[u8]>,
+) -> Result<SignatureBundle, TssError> {
+    let keys = spx_sm3::seed_keypair(params, &seed_from_secret(params, secret)?)?;
+    if public_key.is_some_and(|expected| expected != keys.public) {
+        return Err(TssError::PublicKeyMismatch);
+    }
+    Ok(SignatureBundle {
+        signature: spx_sm3::sign(message, &keys)?,
+        public_key: keys.public,
+        message: message.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spx_host::GuestInput;
+
+    // The fast parameter sets sign in a fraction of the time of the small ones
+    const PARAMS: ParamSet = ParamSet::Sm3_128fSimple;
+
+    #[test]
+    fn threshold_of_shares_signs() {
+        let key = deal(PARAMS, 2, 3, &mut rand::thread_rng()).unwrap();
+        assert_eq!(key.prime.bits(), 8 * PARAMS.seed_bytes() as u64);
+
+        let shares = [key.shares[2].clone(), key.shares[0].clone()];
+        let bundle = sign(
+            PARAMS,
+            &key.prime,
+            &shares,
+            b"helloworld",
+            Some(&key.public_key),
+        )
+        .unwrap();
+        assert_eq!(bundle.public_key, key.public_key);
+        assert_eq!(
+            spx_sm3::vrfy(
+                PARAMS,
+                &bundle.signature,
+                &bundle.message,
+                &bundle.public_key
+            ),
+            Ok(())
+        );
+        GuestInput::new(PARAMS.name(), bundle).validate().unwrap();
+
+        let too_few = sign(
+            PARAMS,
+            &key.prime,
+            &shares[..1],
+            b"helloworld",
+            Some(&key.public_key),
+        );
+        assert_eq!(too_few.unwrap_err(), TssError::PublicKeyMismatch);
+        let mut wrong = shares.clone();
+        wrong[1].y = (&wrong[1].y + 1u32) % &key.prime;
+        let wrong = sign(
+            PARAMS,
+            &key.prime,
+            &wrong,
+            b"helloworld",
+            Some(&key.public_key),
+        );
+        assert_eq!(wrong.unwrap_err(), TssError::PublicKeyMismatch);
+    }
+
+    #[test]
+    fn verified_signing_names_corrupted_shares() {
+        let mut rng = rand::thread_rng();
+        let dealt = deal_verifiable(PARAMS, 2, 4, 512, &mut rng).unwrap();
+        let key = &dealt.key;
+        let sign_with = |shares: &[Share]| {
+            sign_verified(
+                PARAMS,
+                &dealt.group,
+                &dealt.commitments,
+                shares,
+                b"helloworld",
+            )
+        };
+
+        let bundle = sign_with(&key.shares[1..3]).unwrap();
+        assert_eq!(bundle.public_key, key.public_key);
+
+        let mut corrupted = key.shares.clone();
+        corrupted[2].y = (&corrupted[2].y + 1u32) % &key.prime;
+        assert_eq!(
+            sign_with(&corrupted).unwrap_err(),
+            TssError::InvalidShares(vec![3])
+        );
+        // Without the corrupted party the others still sign
+        corrupted.remove(2);
+        let bundle = sign_with(&corrupted).unwrap();
+        assert_eq!(bundle.public_key, key.public_key);
+    }
+
+    #[test]
+    fn pads_short_secrets() {
+        let params = ParamSet::Sm3_128sSimple;
+        let seed = seed_from_secret(params, &BigUint::from(0x0102u32)).unwrap();
+        assert_eq!(seed.len(), 48);
+        assert_eq!(seed[46..], [1, 2]);
+        assert!(seed[..46].iter().all(|&b| b == 0));
+        assert_eq!(secret_from_seed(&seed), BigUint::from(0x0102u32));
+
+        let too_large = BigUint::from(1u32) << 384;
+        assert_eq!(
+            seed_from_secret(params, &too_large),
+            Err(TssError::SecretOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feldman verifiable secret sharing: Shamir shares published together with
+//! commitments `C_k = g^{a_k}` to the coefficients `a_k` of the sharing
+//! polynomial, so that every party can check its share on its own.
+
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::{CryptoRng, RngCore};
+
+use crate::{shamir, Group, Share, TssError};
+
+/// Shares `secret` like [`shamir::split`], modulo the group order `q`, and
+/// returns the commitments to the polynomial with the shares.
+pub fn share<R: RngCore + CryptoRng>(
+    secret: &BigUint,
+    threshold: usize,
+    parties: usize,
+    group: &Group,
+    rng: &mut R,
+) -> Result<(Vec<Share>, Vec<BigUint>), TssError> {
+    shamir::check_sharing(secret, threshold, parties, &group.q)?;
+    let coefficients = shamir::polynomial(secret, threshold, &group.q, rng);
+    let shares = shamir::evaluate_all(&coefficients, parties, &group.q);
+    Ok((shares, commit(group, &coefficients)))
+}
+
+/// Commitments `g^{a_k}` to the coefficients `a_k` of a polynomial.
+pub(crate) fn commit(group: &Group, coefficients: &[BigUint]) -> Vec<BigUint> {
+    coefficients.iter().map(|a| group.commit(a)).collect()
+}
+
+/// Checks `share` against the `commitments` of the polynomial it claims to
+/// be a value of: `g^y = ∏ C_k^{x^k}`.
+pub fn verify(group: &Group, commitments: &[BigUint], share: &Share) -> bool {
+    if share.y >= group.q || !commitments.iter().all(|c| group.is_element(c)) {
+        return false;
+    }
+    let x = BigUint::from(share.x);
+    let mut x_k = BigUint::one();
+    let mut expected = BigUint::one();
+    for c in commitments {
+        expected = group.mul(&expected, &group.power(c, &x_k));
+        x_k = x_k * &x % &group.q;
+    }
+    group.commit(&share.y) == expected
+}
+
+/// Checks every share against `commitments`, and names the parties whose
+/// shares fail by their x.
+pub fn check_shares(
+    group: &Group,
+    commitments: &[BigUint],
+    shares: &[Share],
+) -> Result<(), TssError> {
+    let invalid: Vec<u32> = shares
+        .iter()
+        .filter(|share| !verify(group, commitments, share))
+        .map(|share| share.x)
+        .collect();
+    if !invalid.is_empty() {
+        return Err(TssError::InvalidShares(invalid));
+    }
+    Ok(())
+}
+
+/// Reconstructs the secret committed to by `commitments` from `shares`,
+/// checking each share first so that a wrong one is attributed to its party
+/// instead of yielding a wrong secret.
+pub fn reconstruct(
+    group: &Group,
+    commitments: &[BigUint],
+    shares: &[Share],
+) -> Result<BigUint, TssError> {
+    let Some(committed) = commitments.first() else {
+        return Err(TssError::BadThreshold {
+            threshold: 0,
+            parties: shares.len(),
+        });
+    };
+    check_shares(group, commitments, shares)?;
+    if shares.len() < commitments.len() {
+        return Err(TssError::TooFewShares {
+            given: shares.len(),
+            threshold: commitments.len(),
+        });
+    }
+    let secret = shamir::reconstruct(shares, &group.q)?;
+    if group.commit(&secret) != *committed {
+        return Err(TssError::CommitmentMismatch);
+    }
+    Ok(secret)
+}
+
+/// Commitments to the sum of polynomials, from the commitments to each.
+pub fn combine(group: &Group, commitments: &[&[BigUint]]) -> Vec<BigUint> {
+    let len = commitments.iter().map(|c| c.len()).max().unwrap_or(0);
+    (0..len)
+        .map(|k| {
+            commitments
+                .iter()
+                .filter_map(|c| c.get(k))
+                .fold(BigUint::one(), |acc, c| group.mul(&acc, c))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field;
+
+    #[test]
+    fn shares_verify_against_commitments() {
+        let mut rng = rand::thread_rng();
+        let q = field::random_prime(128, &mut rng);
+        let group = Group::generate(&q, 256, &mut rng).unwrap();
+
+        let (a, commitments_a) = share(&BigUint::from(7u32), 2, 3, &group, &mut rng).unwrap();
+        let (b, commitments_b) = share(&BigUint::from(9u32), 2, 3, &group, &mut rng).unwrap();
+        assert!(a.iter().all(|s| verify(&group, &commitments_a, s)));
+        assert!(!verify(&group, &commitments_b, &a[0]), "other polynomial");
+        let mut wrong = a[1].clone();
+        wrong.y = (&wrong.y + 1u32) % &q;
+        assert!(!verify(&group, &commitments_a, &wrong));
+
+        // The sums of the shares are shares of the sum
+        let joint = combine(&group, &[&commitments_a, &commitments_b]);
+        assert_eq!(joint[0], group.commit(&BigUint::from(16u32)));
+        for (a, b) in a.iter().zip(&b) {
+            let sum = Share {
+                x: a.x,
+                y: (&a.y + &b.y) % &q,
+            };
+            assert!(verify(&group, &joint, &sum));
+        }
+    }
+
+    #[test]
+    fn names_the_parties_with_corrupted_shares() {
+        let mut rng = rand::thread_rng();
+        let q = field::random_prime(128, &mut rng);
+        let group = Group::generate(&q, 256, &mut rng).unwrap();
+        let secret = BigUint::from(1234u32);
+        let (shares, commitments) = share(&secret, 3, 5, &group, &mut rng).unwrap();
+        assert_eq!(reconstruct(&group, &commitments, &shares[1..4]), Ok(secret));
+
+        let mut corrupted = shares.clone();
+        corrupted[1].y = (&corrupted[1].y + 1u32) % &q;
+        corrupted[3].y = BigUint::from(0u32);
+        assert_eq!(
+            reconstruct(&group, &commitments, &corrupted),
+            Err(TssError::InvalidShares(vec![2, 4]))
+        );
+        // A share moved to another party's x is caught too
+        corrupted[0].x = 5;
+        assert_eq!(
+            check_shares(&group, &commitments, &corrupted[..1]),
+            Err(TssError::InvalidShares(vec![5]))
+        );
+        // Valid shares below the threshold do not determine the secret
+        assert_eq!(
+            reconstruct(&group, &commitments, &shares[..2]),
+            Err(TssError::TooFewShares {
+                given: 2,
+                threshold: 3
+            })
+        );
+    }
+}